@@ -0,0 +1,245 @@
+use crate::{
+    chunk_manager::{Tile, TileMap},
+    events::{EventBus, GameEvent},
+    game_manager::Player,
+    inventory::Inventory,
+    mining_manager::MINING_RANGE,
+    tag::TagIndex,
+    util,
+};
+use hex::{
+    anyhow,
+    assets::Shape,
+    components::Trans,
+    nalgebra::Vector2,
+    parking_lot::RwLock,
+    winit::event::{Event, WindowEvent},
+    world::{system_manager::System, EntityManager, World},
+    Context, Control, Id,
+};
+use hex_instance::components::Instance;
+use std::{collections::HashMap, sync::Arc, time::Instant};
+
+pub const BUILD_RANGE: f32 = MINING_RANGE;
+pub const BUILD_COOLDOWN: f32 = 0.15;
+pub const PLAYER_CLEARANCE: f32 = 0.75;
+pub const GHOST_VALID_COLOR: [f32; 4] = [0.5, 1.0, 0.5, 0.6];
+pub const GHOST_INVALID_COLOR: [f32; 4] = [1.0, 0.3, 0.3, 0.6];
+
+pub struct Builder {
+    pub active: bool,
+    pub placing: bool,
+    pub range: f32,
+    pub cooldown: f32,
+}
+
+impl Builder {
+    pub fn new() -> Arc<RwLock<Self>> {
+        Arc::new(RwLock::new(Self {
+            active: false,
+            placing: false,
+            range: BUILD_RANGE,
+            cooldown: 0.0,
+        }))
+    }
+
+    pub fn toggle(&mut self) {
+        self.active = !self.active;
+        self.placing = false;
+    }
+}
+
+pub struct BuildManager {
+    pub player: Option<Id>,
+    pub tags: Arc<RwLock<TagIndex>>,
+    pub tile_map: Arc<RwLock<TileMap>>,
+    pub events: Arc<RwLock<EventBus>>,
+    pub shape: Arc<Shape>,
+    instances: HashMap<(String, bool), Arc<RwLock<Instance>>>,
+    ghost: Option<(Id, String, bool)>,
+    last_frame: Instant,
+}
+
+impl BuildManager {
+    pub fn new(
+        context: &Context,
+        tile_map: Arc<RwLock<TileMap>>,
+        events: Arc<RwLock<EventBus>>,
+        tags: Arc<RwLock<TagIndex>>,
+    ) -> anyhow::Result<Self> {
+        Ok(Self {
+            player: None,
+            tags,
+            tile_map,
+            events,
+            shape: Arc::new(Shape::rect(context, Vector2::new(1.0, 1.0))?),
+            instances: HashMap::new(),
+            ghost: None,
+            last_frame: Instant::now(),
+        })
+    }
+
+    pub fn can_place(
+        tile_map: &TileMap,
+        tile: (u32, u32),
+        player_position: Vector2<f32>,
+        range: f32,
+    ) -> bool {
+        let offset = TileMap::tile_position(tile) - player_position;
+
+        offset.magnitude() <= range
+            && (offset.x.abs() >= PLAYER_CLEARANCE || offset.y.abs() >= PLAYER_CLEARANCE)
+            && tile_map.is_loaded(tile)
+            && tile_map.get(tile).is_none()
+    }
+
+    fn instance(
+        &mut self,
+        context: &Context,
+        id: &str,
+        valid: bool,
+    ) -> anyhow::Result<Arc<RwLock<Instance>>> {
+        let key = (id.to_string(), valid);
+
+        if let Some(instance) = self.instances.get(&key) {
+            return Ok(instance.clone());
+        }
+
+        let path = Tile::file_map(id).ok_or_else(|| anyhow::anyhow!("no texture for {id}"))?;
+        let color = if valid {
+            GHOST_VALID_COLOR
+        } else {
+            GHOST_INVALID_COLOR
+        };
+        let instance = Instance::new(
+            context,
+            self.shape.clone(),
+            Arc::new(util::load_texture(context, &path)?),
+            color.into(),
+            0,
+        )?;
+
+        self.instances.insert(key, instance.clone());
+
+        Ok(instance)
+    }
+
+    fn clear_ghost(&mut self, em: &mut EntityManager) {
+        if let Some((e, _, _)) = self.ghost.take() {
+            em.rm(e);
+        }
+    }
+}
+
+impl System for BuildManager {
+    fn init(&mut self, _: Arc<RwLock<Context>>, _: Arc<RwLock<World>>) -> anyhow::Result<()> {
+        self.player = self.tags.read().first("player");
+
+        Ok(())
+    }
+
+    fn update(
+        &mut self,
+        control: Arc<RwLock<Control>>,
+        context: Arc<RwLock<Context>>,
+        world: Arc<RwLock<World>>,
+    ) -> anyhow::Result<()> {
+        let event = control.read().event.clone();
+
+        if let Event::WindowEvent {
+            event: WindowEvent::RedrawRequested,
+            window_id,
+        } = event
+        {
+            if window_id != context.read().window.id() {
+                return Ok(());
+            }
+
+            let now = Instant::now();
+            let delta = now.duration_since(self.last_frame).as_secs_f32();
+
+            self.last_frame = now;
+
+            let Some(player) = self.player else {
+                return Ok(());
+            };
+            let em = world.read().em.clone();
+            let mut em = em.write();
+            let (Some(builder), Some(inventory), Some(p), Some(t)) = (
+                em.get_component::<Builder>(player),
+                em.get_component::<Inventory>(player),
+                em.get_component::<Player>(player),
+                em.get_component::<Trans>(player),
+            ) else {
+                return Ok(());
+            };
+            let mut builder = builder.write();
+            let id = inventory
+                .read()
+                .selected()
+                .map(|s| s.id.clone())
+                .filter(|id| Tile::is_buildable(id));
+            let target = TileMap::tile_pos(p.read().aim);
+            let (Some(id), Some(tile), true) = (id, target, builder.active) else {
+                self.clear_ghost(&mut em);
+
+                return Ok(());
+            };
+            let valid = Self::can_place(
+                &self.tile_map.read(),
+                tile,
+                t.read().position(),
+                builder.range,
+            );
+
+            builder.cooldown = (builder.cooldown - delta).max(0.0);
+
+            if valid
+                && builder.placing
+                && builder.cooldown == 0.0
+                && inventory.write().remove(&id, 1)
+            {
+                let old = self.tile_map.write().set(tile, Some(id.clone()));
+
+                self.events.write().publish(GameEvent::TileChanged {
+                    tile,
+                    old,
+                    new: Some(id.clone()),
+                });
+
+                builder.cooldown = BUILD_COOLDOWN;
+            }
+
+            if self
+                .ghost
+                .as_ref()
+                .is_some_and(|(_, i, v)| *i != id || *v != valid)
+            {
+                self.clear_ghost(&mut em);
+            }
+
+            if self.ghost.is_none() {
+                let instance = self.instance(&context.read(), &id, valid)?;
+                let e = em.add(true);
+
+                em.add_component(e, instance);
+                em.add_component(
+                    e,
+                    Trans::new(Vector2::default(), 0.0, Vector2::new(1.0, 1.0)),
+                );
+
+                self.ghost = Some((e, id, valid));
+            }
+
+            if let Some(t) = self
+                .ghost
+                .as_ref()
+                .and_then(|(e, _, _)| em.get_component::<Trans>(*e))
+            {
+                t.write().set_position(TileMap::tile_position(tile));
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,275 @@
+use crate::{
+    events::{EventBus, GameEvent, Subscriber},
+    game_manager::{Player, PLAYER_MAX_SPEED},
+    tag::TagIndex,
+    util,
+};
+use hex::{
+    anyhow,
+    components::{Camera, Trans},
+    nalgebra::Vector2,
+    parking_lot::RwLock,
+    winit::{
+        dpi::PhysicalPosition,
+        event::{Event, WindowEvent},
+    },
+    world::{system_manager::System, World},
+    Context, Control, Id,
+};
+use noise::{NoiseFn, Perlin};
+use std::{sync::Arc, time::Instant};
+
+pub const CAMERA_SMOOTH_TIME: f32 = 0.15;
+pub const CAMERA_LOOK_AHEAD: f32 = 4.0;
+pub const CAMERA_LOOK_AHEAD_MUL: f32 = 0.25;
+pub const CAMERA_FREE_LOOK_SPEED: f32 = 20.0;
+pub const TRAUMA_DECAY: f32 = 1.5;
+pub const MAX_SHAKE_OFFSET: f32 = 0.75;
+pub const MAX_SHAKE_ANGLE: f32 = 0.05;
+pub const SHAKE_FREQUENCY: f64 = 25.0;
+pub const MINING_TRAUMA: f32 = 0.2;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum LookAhead {
+    Cursor,
+    Velocity,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum CameraMode {
+    Follow,
+    LookAhead(LookAhead),
+    Fixed,
+    FreeLook,
+}
+
+impl CameraMode {
+    pub fn next(self) -> Self {
+        match self {
+            Self::Follow => Self::LookAhead(LookAhead::Cursor),
+            Self::LookAhead(LookAhead::Cursor) => Self::LookAhead(LookAhead::Velocity),
+            Self::LookAhead(LookAhead::Velocity) => Self::Fixed,
+            Self::Fixed => Self::FreeLook,
+            Self::FreeLook => Self::Follow,
+        }
+    }
+}
+
+pub struct CameraController {
+    pub target: Id,
+    pub mode: CameraMode,
+    pub smooth_time: f32,
+    pub look_ahead: f32,
+    pub free_look_speed: f32,
+    pub pan: Vector2<f32>,
+    pub trauma: f32,
+    pub trauma_decay: f32,
+    pub max_shake_offset: f32,
+    pub max_shake_angle: f32,
+    position: Vector2<f32>,
+    velocity: Vector2<f32>,
+    time: f64,
+    perlin: Perlin,
+}
+
+impl CameraController {
+    pub fn new(target: Id, position: Vector2<f32>, seed: u32) -> Arc<RwLock<Self>> {
+        Arc::new(RwLock::new(Self {
+            target,
+            mode: CameraMode::LookAhead(LookAhead::Cursor),
+            smooth_time: CAMERA_SMOOTH_TIME,
+            look_ahead: CAMERA_LOOK_AHEAD,
+            free_look_speed: CAMERA_FREE_LOOK_SPEED,
+            pan: Vector2::default(),
+            trauma: 0.0,
+            trauma_decay: TRAUMA_DECAY,
+            max_shake_offset: MAX_SHAKE_OFFSET,
+            max_shake_angle: MAX_SHAKE_ANGLE,
+            position,
+            velocity: Vector2::default(),
+            time: 0.0,
+            perlin: Perlin::new(seed),
+        }))
+    }
+
+    pub fn add_trauma(&mut self, amount: f32) {
+        self.trauma = (self.trauma + amount).clamp(0.0, 1.0);
+    }
+
+    pub fn position(&self) -> Vector2<f32> {
+        self.position
+    }
+
+    pub fn snap(&mut self, position: Vector2<f32>) {
+        self.position = position;
+        self.velocity = Vector2::default();
+    }
+
+    pub fn update(&mut self, goal: Vector2<f32>, delta: f32) -> (Vector2<f32>, f32) {
+        match self.mode {
+            CameraMode::Fixed => {}
+            CameraMode::FreeLook => {
+                self.position += self.pan * self.free_look_speed * delta;
+                self.velocity = Vector2::default();
+            }
+            CameraMode::Follow | CameraMode::LookAhead(_) => {
+                let (position, velocity) =
+                    smooth_damp(self.position, goal, self.velocity, self.smooth_time, delta);
+
+                self.position = position;
+                self.velocity = velocity;
+            }
+        }
+
+        self.time += delta as f64;
+
+        let shake = self.trauma * self.trauma;
+        let t = self.time * SHAKE_FREQUENCY;
+        let offset = Vector2::new(
+            self.perlin.get([t, 0.0, 0.0]) as f32,
+            self.perlin.get([0.0, t, 0.0]) as f32,
+        ) * self.max_shake_offset
+            * shake;
+        let angle = self.perlin.get([0.0, 0.0, t]) as f32 * self.max_shake_angle * shake;
+
+        self.trauma = (self.trauma - self.trauma_decay * delta).max(0.0);
+
+        (self.position + offset, angle)
+    }
+}
+
+pub fn smooth_damp(
+    current: Vector2<f32>,
+    target: Vector2<f32>,
+    velocity: Vector2<f32>,
+    smooth_time: f32,
+    delta: f32,
+) -> (Vector2<f32>, Vector2<f32>) {
+    let omega = 2.0 / smooth_time.max(f32::EPSILON);
+    let x = omega * delta;
+    let exp = 1.0 / (1.0 + x + 0.48 * x * x + 0.235 * x * x * x);
+    let change = current - target;
+    let temp = (velocity + change * omega) * delta;
+
+    (
+        target + (change + temp) * exp,
+        (velocity - temp * omega) * exp,
+    )
+}
+
+pub struct CameraManager {
+    pub camera: Option<Id>,
+    pub tags: Arc<RwLock<TagIndex>>,
+    pub events: Arc<RwLock<EventBus>>,
+    pub subscriber: Subscriber,
+    pub mouse_position: Vector2<f32>,
+    pub last_frame: Instant,
+}
+
+impl CameraManager {
+    pub fn new(events: Arc<RwLock<EventBus>>, tags: Arc<RwLock<TagIndex>>) -> Self {
+        let subscriber = events.write().subscribe();
+
+        Self {
+            camera: None,
+            tags,
+            events,
+            subscriber,
+            mouse_position: Default::default(),
+            last_frame: Instant::now(),
+        }
+    }
+}
+
+impl System for CameraManager {
+    fn init(&mut self, _: Arc<RwLock<Context>>, _: Arc<RwLock<World>>) -> anyhow::Result<()> {
+        self.camera = self.tags.read().first("camera");
+
+        Ok(())
+    }
+
+    fn update(
+        &mut self,
+        control: Arc<RwLock<Control>>,
+        context: Arc<RwLock<Context>>,
+        world: Arc<RwLock<World>>,
+    ) -> anyhow::Result<()> {
+        let event = control.read().event.clone();
+
+        match event {
+            Event::WindowEvent {
+                event: WindowEvent::CursorMoved { position, .. },
+                window_id,
+            } if window_id == context.read().window.id() => {
+                self.mouse_position = Vector2::new(position.x as f32, position.y as f32);
+            }
+            Event::WindowEvent {
+                event: WindowEvent::RedrawRequested,
+                window_id,
+            } if window_id == context.read().window.id() => {
+                let now = Instant::now();
+                let delta = now.duration_since(self.last_frame).as_secs_f32();
+
+                self.last_frame = now;
+
+                let events = self.events.write().poll(self.subscriber);
+                let Some(camera_id) = self.camera else {
+                    return Ok(());
+                };
+                let em = world.read().em.clone();
+                let em = em.read();
+                let Some(controller) = em.get_component::<CameraController>(camera_id) else {
+                    return Ok(());
+                };
+                let mut controller = controller.write();
+                let target = controller.target;
+
+                for event in events {
+                    if matches!(event, GameEvent::TileMined { by, .. } if by == target) {
+                        controller.add_trauma(MINING_TRAUMA);
+                    }
+                }
+
+                let Some(target_position) = em
+                    .get_component::<Trans>(target)
+                    .map(|t| t.read().position())
+                else {
+                    return Ok(());
+                };
+                let camera_transform = em.get_component::<Trans>(camera_id).unwrap();
+                let mut camera_transform = camera_transform.write();
+                let offset = match controller.mode {
+                    CameraMode::LookAhead(LookAhead::Cursor) => {
+                        let camera = em.get_component::<Camera>(camera_id).unwrap();
+                        let cursor = util::screen_to_world(
+                            &camera.read(),
+                            &camera_transform,
+                            context.read().window.inner_size(),
+                            PhysicalPosition::new(
+                                self.mouse_position.x as f64,
+                                self.mouse_position.y as f64,
+                            ),
+                        )
+                        .map(|c| c - camera_transform.position())
+                        .unwrap_or_default()
+                            * CAMERA_LOOK_AHEAD_MUL;
+
+                        cursor.cap_magnitude(controller.look_ahead)
+                    }
+                    CameraMode::LookAhead(LookAhead::Velocity) => em
+                        .get_component::<Player>(target)
+                        .map(|p| p.read().velocity / PLAYER_MAX_SPEED * controller.look_ahead)
+                        .unwrap_or_default(),
+                    _ => Vector2::default(),
+                };
+                let (position, angle) = controller.update(target_position + offset, delta);
+
+                camera_transform.set_position(position);
+                camera_transform.set_rotation(angle);
+            }
+            _ => {}
+        }
+
+        Ok(())
+    }
+}
@@ -1,4 +1,4 @@
-use crate::util;
+use crate::{hazard_manager::Hazard, util};
 use hex::{
     anyhow,
     assets::Shape,
@@ -15,6 +15,8 @@ pub const ASTEROID_1: &str = "asteroid_1";
 pub const ASTEROID_2: &str = "asteroid_2";
 pub const SPACE: &str = "space";
 pub const METAL: &str = "metal";
+pub const RADIOACTIVE: &str = "radioactive";
+pub const ICE: &str = "ice";
 
 #[derive(Clone)]
 pub struct Chunk {
@@ -51,10 +53,12 @@ impl ChunkType {
     }
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, Clone)]
 pub struct ChunkData {
     pub position: [f32; 2],
     pub grid: Vec<Vec<Option<String>>>,
+    #[serde(default)]
+    pub hazards: Vec<Hazard>,
 }
 
 pub struct Tile {
@@ -104,6 +108,26 @@ impl Tile {
         }))
     }
 
+    pub fn radioactive(context: &Context) -> anyhow::Result<Arc<Self>> {
+        Ok(Arc::new(Self {
+            max: 1.0,
+            min: 0.25,
+            rand: 0.0,
+            instance: Self::new_instance(context, RADIOACTIVE)?,
+            id: RADIOACTIVE.to_string(),
+        }))
+    }
+
+    pub fn ice(context: &Context) -> anyhow::Result<Arc<Self>> {
+        Ok(Arc::new(Self {
+            max: 1.0,
+            min: 0.25,
+            rand: 0.0,
+            instance: Self::new_instance(context, ICE)?,
+            id: ICE.to_string(),
+        }))
+    }
+
     pub fn space(context: &Context) -> anyhow::Result<Arc<RwLock<Instance>>> {
         Self::new_instance(context, SPACE)
     }
@@ -113,15 +137,49 @@ impl Tile {
             context,
             Arc::new(Shape::rect(context, Vector2::new(1.0, 1.0))?),
             Arc::new(util::load_texture(context, &Self::file_map(id).unwrap())?),
-            Vector4::new(1.0, 1.0, 1.0, 1.0),
+            Vector4::from(Self::tint(id)),
             1,
         )
     }
 
+    pub fn tint(id: &str) -> [f32; 4] {
+        match id {
+            RADIOACTIVE => [0.6, 1.0, 0.4, 1.0],
+            ICE => [0.7, 0.9, 1.0, 1.0],
+            _ => [1.0, 1.0, 1.0, 1.0],
+        }
+    }
+
+    pub fn hardness(id: &str) -> f32 {
+        match id {
+            ASTEROID_1 | ASTEROID_2 => 0.5,
+            METAL => 2.0,
+            ICE => 0.25,
+            _ => 1.0,
+        }
+    }
+
+    pub fn map_color(id: Option<&str>) -> [u8; 4] {
+        match id {
+            Some(ASTEROID_1) => [120, 110, 100, 255],
+            Some(ASTEROID_2) => [90, 85, 80, 255],
+            Some(METAL) => [80, 200, 230, 255],
+            Some(RADIOACTIVE) => [120, 230, 70, 255],
+            Some(ICE) => [190, 230, 250, 255],
+            Some(_) => [255, 0, 255, 255],
+            None => [10, 10, 20, 255],
+        }
+    }
+
+    pub fn is_buildable(id: &str) -> bool {
+        !matches!(id, SPACE | RADIOACTIVE | ICE) && Self::file_map(id).is_some()
+    }
+
     pub fn file_map(id: &str) -> Option<String> {
         match id {
             ASTEROID_1 => Some("art/asteroid.png".into()),
-            ASTEROID_2 => Some("art/asteroid2.png".into()),
+            ASTEROID_2 | RADIOACTIVE => Some("art/asteroid2.png".into()),
+            ICE => Some("art/asteroid.png".into()),
             METAL => Some("art/metal.png".into()),
             SPACE => Some("art/space.png".into()),
             _ => None,
@@ -1,21 +1,36 @@
 pub mod chunk;
+pub mod raycast;
+pub mod tile_map;
 
-pub use chunk::{Chunk, ChunkData, ChunkType, Tile, ASTEROID_1, ASTEROID_2, METAL, SPACE};
-
-use crate::{state::State, tag::Tag};
+pub use chunk::{
+    Chunk, ChunkData, ChunkType, Tile, ASTEROID_1, ASTEROID_2, ICE, METAL, RADIOACTIVE, SPACE,
+};
+pub use raycast::{HitKind, RayHit};
+pub use tile_map::TileMap;
+
+use crate::{
+    events::{EventBus, GameEvent},
+    exploration::{Exploration, EXPLORATION_SAVE_TIME, SENSOR_RANGE},
+    gravity_manager::Body,
+    hazard_manager::{Hazard, HazardConfig},
+    module_manager::ShipStats,
+    prefab::{ChunkEntities, ComponentRegistry, EntityData, Persistent, Prefabs},
+    spatial_manager::SpatialIndex,
+    state::State,
+    tag::TagIndex,
+};
 use hex::{
     anyhow,
     components::{Camera, Trans},
     nalgebra::Vector2,
     parking_lot::RwLock,
     winit::event::{Event, WindowEvent},
-    world::{system_manager::System, World},
-    Context, Control,
+    world::{system_manager::System, EntityManager, World},
+    Context, Control, Id,
 };
 use hex_instance::components::Instance;
 use hex_physics::components::Collider;
 use noise::NoiseFn;
-use once_cell::sync::Lazy;
 use rand::prelude::*;
 use std::{
     collections::{HashMap, HashSet},
@@ -34,6 +49,9 @@ pub const MAX_CHUNK: u32 = MAX_MAP_SIZE / CHUNK_SIZE;
 pub const MIN_CHUNK: u32 = 2;
 pub const FRAME_LOAD_AMOUNT: usize = 1;
 pub const SAVE_DIR: &str = "save";
+pub const HAZARD_TILE_CHANCE: f64 = 0.3;
+pub const MINER_PREFAB: &str = "miner";
+pub const MINER_CHANCE: f64 = 0.15;
 
 pub struct ChunkManager {
     pub state: Arc<RwLock<State>>,
@@ -41,20 +59,49 @@ pub struct ChunkManager {
     pub space: Arc<RwLock<Instance>>,
     pub camera: Option<Arc<RwLock<Camera>>>,
     pub player_transform: Option<Arc<RwLock<Trans>>>,
+    pub player_stats: Option<Arc<RwLock<ShipStats>>>,
     pub last_update_time: Instant,
-    pub loaded: Arc<RwLock<HashSet<(u32, u32)>>>,
+    pub tile_map: Arc<RwLock<TileMap>>,
+    pub exploration: Arc<RwLock<Exploration>>,
+    pub events: Arc<RwLock<EventBus>>,
+    pub tags: Arc<RwLock<TagIndex>>,
+    pub registry: Arc<RwLock<ComponentRegistry>>,
+    pub spatial: Arc<RwLock<SpatialIndex>>,
+    pub prefabs: Arc<RwLock<Prefabs>>,
+    pub hazards: HazardConfig,
+    pub last_exploration_save: Instant,
 }
 
 impl ChunkManager {
-    pub fn new(context: &Context, state: Arc<RwLock<State>>) -> anyhow::Result<Self> {
+    pub fn new(
+        context: &Context,
+        state: Arc<RwLock<State>>,
+        tile_map: Arc<RwLock<TileMap>>,
+        exploration: Arc<RwLock<Exploration>>,
+        events: Arc<RwLock<EventBus>>,
+        tags: Arc<RwLock<TagIndex>>,
+        registry: Arc<RwLock<ComponentRegistry>>,
+        spatial: Arc<RwLock<SpatialIndex>>,
+        prefabs: Arc<RwLock<Prefabs>>,
+        hazards: HazardConfig,
+    ) -> anyhow::Result<Self> {
         Ok(Self {
             state,
             tiles: Self::chunk_texture_map(context)?,
             space: Tile::space(context)?,
             camera: None,
             player_transform: None,
+            player_stats: None,
             last_update_time: Instant::now(),
-            loaded: Arc::new(RwLock::new(HashSet::new())),
+            tile_map,
+            exploration,
+            events,
+            tags,
+            registry,
+            spatial,
+            prefabs,
+            hazards,
+            last_exploration_save: Instant::now(),
         })
     }
 
@@ -64,14 +111,14 @@ impl ChunkManager {
         map.insert(ASTEROID_1.into(), Tile::asteroid_1(context)?);
         map.insert(ASTEROID_2.into(), Tile::asteroid_2(context)?);
         map.insert(METAL.into(), Tile::metal(context)?);
+        map.insert(RADIOACTIVE.into(), Tile::radioactive(context)?);
+        map.insert(ICE.into(), Tile::ice(context)?);
 
         Ok(map)
     }
 
     pub fn chunk_pos(pos: Vector2<f32>) -> (u32, u32) {
-        let pos = pos / CHUNK_SIZE as f32;
-
-        (pos.x.ceil() as u32, pos.y.ceil() as u32)
+        Self::entity_chunk(pos).unwrap_or_default()
     }
 
     pub fn chunk_file((x, y): (u32, u32)) -> String {
@@ -104,81 +151,315 @@ impl ChunkManager {
             }
         }
 
+        if let Some(body) = Body::generate(state.seed, (pos.x as u32, pos.y as u32)) {
+            for (i, grid) in grid.iter_mut().enumerate() {
+                for (j, grid) in grid.iter_mut().enumerate() {
+                    let tile = (
+                        pos.x as u32 * CHUNK_SIZE + i as u32,
+                        pos.y as u32 * CHUNK_SIZE + j as u32,
+                    );
+                    let distance = (TileMap::tile_position(tile) - body.position).magnitude();
+
+                    if distance <= body.radius {
+                        *grid = Some(METAL.to_string());
+                    } else if distance <= body.radius + 1.0 {
+                        *grid = Some(ASTEROID_1.to_string());
+                    }
+                }
+            }
+        }
+
+        let hazards = Hazard::generate(&self.hazards, state.seed, (pos.x as u32, pos.y as u32));
+
+        for hazard in &hazards {
+            let Some(id) = hazard.kind.tile() else {
+                continue;
+            };
+
+            for (i, grid) in grid.iter_mut().enumerate() {
+                for (j, grid) in grid.iter_mut().enumerate() {
+                    let tile = (
+                        pos.x as u32 * CHUNK_SIZE + i as u32,
+                        pos.y as u32 * CHUNK_SIZE + j as u32,
+                    );
+                    let distance = (TileMap::tile_position(tile) - hazard.position()).magnitude();
+
+                    if distance > hazard.radius {
+                        continue;
+                    }
+
+                    match grid.as_deref() {
+                        Some(ASTEROID_1 | ASTEROID_2) => *grid = Some(id.to_string()),
+                        None if state.rng.gen_bool(HAZARD_TILE_CHANCE) => {
+                            *grid = Some(id.to_string())
+                        }
+                        _ => {}
+                    }
+                }
+            }
+        }
+
         Ok(ChunkData {
             position: pos.into(),
             grid,
+            hazards,
         })
     }
 
+    pub fn gen_entities(
+        &self,
+        (x, y): (u32, u32),
+        data: &ChunkData,
+    ) -> anyhow::Result<ChunkEntities> {
+        let mut entities = ChunkEntities::default();
+
+        if x < MIN_CHUNK || y < MIN_CHUNK {
+            return Ok(entities);
+        }
+
+        let mut state = self.state.write();
+
+        if !state.rng.gen_bool(MINER_CHANCE) {
+            return Ok(entities);
+        }
+
+        let open: Vec<_> = (0..CHUNK_SIZE)
+            .flat_map(|i| (0..CHUNK_SIZE).map(move |j| (i, j)))
+            .filter(|&(i, j)| data.grid[i as usize][j as usize].is_none())
+            .collect();
+
+        if let Some(&(i, j)) = open.choose(&mut state.rng) {
+            let position = TileMap::tile_position((x * CHUNK_SIZE + i, y * CHUNK_SIZE + j));
+
+            entities.entities.push(self.prefabs.read().instantiate(
+                MINER_PREFAB,
+                position,
+                EntityData::default(),
+            )?);
+        }
+
+        Ok(entities)
+    }
+
+    pub fn chunk_path(chunk: (u32, u32)) -> PathBuf {
+        PathBuf::from(SAVE_DIR)
+            .join("chunks")
+            .join(Self::chunk_file(chunk))
+    }
+
+    pub fn read_saved(chunk: (u32, u32)) -> anyhow::Result<Option<ChunkData>> {
+        let path = Self::chunk_path(chunk);
+
+        if Path::exists(&path) {
+            let content = fs::read_to_string(path)?;
+
+            Ok(Some(serde_json::from_str(content.as_str())?))
+        } else {
+            Ok(None)
+        }
+    }
+
+    pub fn read_chunk(&self, chunk @ (x, y): (u32, u32)) -> anyhow::Result<ChunkData> {
+        match Self::read_saved(chunk)? {
+            Some(data) => Ok(data),
+            None => {
+                let data = self.gen_chunk(Vector2::new(x as f32, y as f32))?;
+
+                Self::save_chunk(chunk, &data)?;
+                self.gen_entities(chunk, &data)?.write(chunk)?;
+
+                Ok(data)
+            }
+        }
+    }
+
     pub fn load_chunk(
         &self,
         world: Arc<RwLock<World>>,
         chunk @ (x, y): (u32, u32),
     ) -> anyhow::Result<()> {
         let em = world.read().em.clone();
-        let chunk = Lazy::new(|| {
-            let chunks_dir = PathBuf::from(SAVE_DIR).join("chunks");
+        let mut tile_map = self.tile_map.write();
+        let loaded = !tile_map.chunks.contains_key(&chunk);
+
+        if loaded {
+            let data = self.read_chunk(chunk)?;
+
+            tile_map.chunks.insert(chunk, data);
+            self.events
+                .write()
+                .publish(GameEvent::ChunkLoaded { chunk });
+        }
+
+        let TileMap {
+            chunks, entities, ..
+        } = &mut *tile_map;
+        let chunk = &chunks[&chunk];
+        let mut em = em.write();
+
+        for i in 0..(CHUNK_SIZE as usize) {
+            for j in 0..(CHUNK_SIZE as usize) {
+                if let Some(tile) = chunk.grid[i][j].as_ref().and_then(|id| self.tiles.get(id)) {
+                    let position = (CHUNK_SIZE * x + i as u32, CHUNK_SIZE * y + j as u32);
+
+                    if !entities.contains_key(&position) {
+                        let e = self.spawn_tile(&mut em, tile, position);
 
-            fs::create_dir_all(&chunks_dir).unwrap();
+                        entities.insert(position, e);
+                    }
+                }
+            }
+        }
 
-            let path = chunks_dir.join(Self::chunk_file(chunk));
-            let data = if Path::exists(&path) {
-                let content = fs::read_to_string(path).unwrap();
-                let data: ChunkData = serde_json::from_str(content.as_str()).unwrap();
+        if loaded {
+            let registry = self.registry.read();
 
-                data
-            } else {
-                let data = self.gen_chunk(Vector2::new(x as f32, y as f32)).unwrap();
-                let content = serde_json::to_string(&data).unwrap();
+            for data in ChunkEntities::read(chunk)?.entities {
+                registry.spawn(&mut em, &data)?;
+            }
+        }
 
-                fs::write(path, content).unwrap();
+        Ok(())
+    }
 
-                data
+    pub fn entity_chunk(position: Vector2<f32>) -> Option<(u32, u32)> {
+        TileMap::tile_pos(position).map(|t| TileMap::tile_chunk(t).0)
+    }
+
+    pub fn save_entities(
+        &self,
+        em: &mut EntityManager,
+        chunks: &[(u32, u32)],
+        remove: bool,
+    ) -> anyhow::Result<()> {
+        if chunks.is_empty() {
+            return Ok(());
+        }
+
+        let targets: HashSet<_> = chunks.iter().copied().collect();
+        let registry = self.registry.read();
+        let mut saved: HashMap<_, ChunkEntities> = HashMap::new();
+        let persistent: Vec<_> = {
+            let mut persistent = registry.persistent.write();
+
+            persistent.retain(|e| em.get_component::<Persistent>(*e).is_some());
+            persistent.iter().copied().collect()
+        };
+
+        for e in persistent {
+            let Some(chunk) = em
+                .get_component::<Trans>(e)
+                .and_then(|t| Self::entity_chunk(t.read().position()))
+                .filter(|c| targets.contains(c))
+            else {
+                continue;
             };
 
-            Chunk::load(data, &self.tiles)
-        });
+            saved
+                .entry(chunk)
+                .or_default()
+                .entities
+                .push(registry.save(em, e)?);
 
-        let mut em = em.write();
+            if remove {
+                registry.persistent.write().remove(&e);
+                self.spatial.write().remove(e);
+                self.tags.write().rm(em, e);
+            }
+        }
 
-        for i in 0..(CHUNK_SIZE as usize) {
-            for j in 0..(CHUNK_SIZE as usize) {
-                if let Some(instance) = chunk.grid[i][j].as_ref().map(|c| c.instance.clone()) {
-                    let position = Vector2::new(
-                        (CHUNK_SIZE * x) as f32 + i as f32,
-                        (CHUNK_SIZE * y) as f32 + j as f32,
-                    );
-                    let mut loaded = self.loaded.write();
+        for chunk in chunks {
+            saved.remove(chunk).unwrap_or_default().write(*chunk)?;
+        }
 
-                    if !loaded.contains(&(position.x as u32, position.y as u32)) {
-                        let e = em.add(true);
+        Ok(())
+    }
 
-                        em.add_component(e, ChunkType::new());
-                        em.add_component(e, instance);
+    pub fn spawn_tile(&self, em: &mut EntityManager, tile: &Tile, position: (u32, u32)) -> Id {
+        let e = em.add(true);
+        let world_position = TileMap::tile_position(position);
+
+        em.add_component(e, ChunkType::new());
+        em.add_component(e, tile.instance.clone());
+        em.add_component(e, Trans::new(world_position, 0.0, Vector2::new(1.0, 1.0)));
+
+        if tile.id == METAL {
+            em.add_component(
+                e,
+                Collider::rect(
+                    Vector2::new(1.0, 1.0),
+                    [0, 1].into(),
+                    [1].into(),
+                    true,
+                    false,
+                ),
+            );
+        }
 
-                        em.add_component(e, Trans::new(position, 0.0, Vector2::new(1.0, 1.0)));
+        self.spatial.write().insert(e, world_position, false);
 
-                        if chunk.grid[i][j]
-                            .as_ref()
-                            .map(|t| t.id == METAL)
-                            .unwrap_or(false)
-                        {
-                            em.add_component(
-                                e,
-                                Collider::rect(
-                                    Vector2::new(1.0, 1.0),
-                                    [0, 1].into(),
-                                    [1].into(),
-                                    true,
-                                    false,
-                                ),
-                            );
-                        }
+        e
+    }
+
+    pub fn save_chunk(chunk: (u32, u32), data: &ChunkData) -> anyhow::Result<()> {
+        fs::create_dir_all(PathBuf::from(SAVE_DIR).join("chunks"))?;
+        fs::write(Self::chunk_path(chunk), serde_json::to_string(data)?)?;
+
+        Ok(())
+    }
+
+    pub fn save_all(&self, em: &mut EntityManager) -> anyhow::Result<()> {
+        let mut tile_map = self.tile_map.write();
+
+        for chunk in tile_map.dirty.drain().collect::<Vec<_>>() {
+            if let Some(data) = tile_map.chunks.get(&chunk) {
+                Self::save_chunk(chunk, data)?;
+            }
+        }
+
+        let loaded: Vec<_> = tile_map.chunks.keys().copied().collect();
+
+        self.save_entities(em, &loaded, false)?;
 
-                        loaded.insert((position.x as u32, position.y as u32));
+        self.exploration.write().save()
+    }
+
+    pub fn unload_chunks<F>(&self, em: &mut EntityManager, keep: F) -> anyhow::Result<()>
+    where
+        F: Fn((u32, u32)) -> bool,
+    {
+        let mut tile_map = self.tile_map.write();
+        let unload: Vec<_> = tile_map
+            .chunks
+            .keys()
+            .copied()
+            .filter(|c| !keep(*c))
+            .collect();
+
+        self.save_entities(em, &unload, true)?;
+
+        for chunk @ (x, y) in unload {
+            for i in 0..CHUNK_SIZE {
+                for j in 0..CHUNK_SIZE {
+                    if let Some(e) = tile_map
+                        .entities
+                        .remove(&(CHUNK_SIZE * x + i, CHUNK_SIZE * y + j))
+                    {
+                        self.spatial.write().remove(e);
+                        em.rm(e);
                     }
                 }
             }
+
+            if let Some(data) = tile_map.chunks.remove(&chunk) {
+                if tile_map.dirty.remove(&chunk) {
+                    Self::save_chunk(chunk, &data)?;
+                }
+
+                self.events
+                    .write()
+                    .publish(GameEvent::ChunkUnloaded { chunk });
+            }
         }
 
         Ok(())
@@ -194,9 +475,11 @@ impl System for ChunkManager {
         let em = world.read().em.clone();
         let em = em.read();
 
-        self.camera = em.get_component::<Camera>(Tag("camera".to_string()).find(&em).unwrap());
-        self.player_transform =
-            em.get_component::<Trans>(Tag("player".to_string()).find(&em).unwrap());
+        let tags = self.tags.read();
+
+        self.camera = tags.query_one::<Camera>(&em, "camera").map(|(_, c)| c);
+        self.player_transform = tags.query_one::<Trans>(&em, "player").map(|(_, t)| t);
+        self.player_stats = tags.query_one::<ShipStats>(&em, "player").map(|(_, s)| s);
 
         Ok(())
     }
@@ -214,6 +497,14 @@ impl System for ChunkManager {
                 event: WindowEvent::RedrawRequested,
                 window_id,
             } if window_id == context.read().window.id() => {}
+            Event::WindowEvent {
+                event: WindowEvent::CloseRequested,
+                window_id,
+            } if window_id == context.read().window.id() => {
+                let em = world.read().em.clone();
+
+                self.save_all(&mut em.write())?;
+            }
             _ => {
                 let now = Instant::now();
 
@@ -241,46 +532,45 @@ impl System for ChunkManager {
                             .max(MIN_CHUNK),
                     );
                     let max = (
-                        (player_chunk.0 + offset_x).min(MAX_CHUNK),
-                        (player_chunk.1 + offset_y).min(MAX_CHUNK),
+                        (player_chunk.0 + offset_x).min(MAX_CHUNK - 1),
+                        (player_chunk.1 + offset_y).min(MAX_CHUNK - 1),
                     );
 
-                    for i in min.0..max.0 {
-                        for j in min.1..max.1 {
+                    for i in min.0..=max.0 {
+                        for j in min.1..=max.1 {
                             let chunk = (i, j);
 
-                            self.load_chunk(world.clone(), chunk).unwrap();
+                            self.load_chunk(world.clone(), chunk)?;
                         }
                     }
 
-                    let em = world.read().em.clone();
-                    let rm = {
-                        let mut rm = Vec::new();
-                        let em = em.read();
-                        let mut loaded = self.loaded.write();
-
-                        for e in em.entities() {
-                            if em.get_component::<ChunkType>(e).is_some() {
-                                let position =
-                                    em.get_component::<Trans>(e).unwrap().read().position();
-                                let dist = (player_pos - position).magnitude();
-
-                                if dist >= camera_dims.magnitude() * CHUNK_DIST {
-                                    rm.push(e);
-
-                                    loaded.remove(&(position.x as u32, position.y as u32));
-                                }
-                            }
-                        }
+                    if let Some(tile) = TileMap::tile_pos(player_pos) {
+                        let mut exploration = self.exploration.write();
+                        let (chunk, _) = TileMap::tile_chunk(tile);
 
-                        rm
-                    };
+                        let range = self
+                            .player_stats
+                            .as_ref()
+                            .map(|s| s.read().scanner_range.max(0.0) as u32)
+                            .unwrap_or(SENSOR_RANGE);
 
-                    let mut em = em.write();
+                        exploration.explore(chunk, range);
 
-                    for e in rm {
-                        em.rm(e);
+                        if exploration.dirty
+                            && now.duration_since(self.last_exploration_save)
+                                >= Duration::from_secs_f32(EXPLORATION_SAVE_TIME)
+                        {
+                            self.last_exploration_save = now;
+
+                            exploration.save()?;
+                        }
                     }
+
+                    let em = world.read().em.clone();
+
+                    self.unload_chunks(&mut em.write(), |(x, y)| {
+                        (min.0..=max.0).contains(&x) && (min.1..=max.1).contains(&y)
+                    })?;
                 }
             }
         }
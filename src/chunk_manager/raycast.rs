@@ -0,0 +1,259 @@
+use super::TileMap;
+use hex::nalgebra::Vector2;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum HitKind {
+    Solid,
+    Unloaded,
+}
+
+#[derive(Clone, Debug)]
+pub struct RayHit {
+    pub kind: HitKind,
+    pub tile: Option<(u32, u32)>,
+    pub id: Option<String>,
+    pub position: Vector2<f32>,
+    pub normal: Vector2<f32>,
+    pub distance: f32,
+}
+
+impl TileMap {
+    pub fn raycast(
+        &self,
+        origin: Vector2<f32>,
+        direction: Vector2<f32>,
+        max_distance: f32,
+    ) -> Option<RayHit> {
+        self.raycast_with(origin, direction, max_distance, |_| true)
+    }
+
+    pub fn raycast_with<F>(
+        &self,
+        origin: Vector2<f32>,
+        direction: Vector2<f32>,
+        max_distance: f32,
+        solid: F,
+    ) -> Option<RayHit>
+    where
+        F: Fn(&str) -> bool,
+    {
+        let direction = direction.try_normalize(f32::EPSILON)?;
+        let start = origin.add_scalar(0.5);
+        let mut cell = start.map(|p| p.floor() as i64);
+        let step = direction.map(|d| {
+            if d > 0.0 {
+                1
+            } else if d < 0.0 {
+                -1
+            } else {
+                0
+            }
+        });
+        let delta = direction.map(|d| {
+            if d == 0.0 {
+                f32::INFINITY
+            } else {
+                d.recip().abs()
+            }
+        });
+        let mut side = Vector2::new(
+            Self::boundary(start.x, cell.x, step.x, delta.x),
+            Self::boundary(start.y, cell.y, step.y, delta.y),
+        );
+        let mut normal = Vector2::zeros();
+        let mut distance = 0.0;
+
+        loop {
+            let tile = (u32::try_from(cell.x).ok(), u32::try_from(cell.y).ok());
+            let hit = |kind, tile, id| RayHit {
+                kind,
+                tile,
+                id,
+                position: origin + direction * distance,
+                normal,
+                distance,
+            };
+
+            let (Some(x), Some(y)) = tile else {
+                return Some(hit(HitKind::Unloaded, None, None));
+            };
+
+            if !self.is_loaded((x, y)) {
+                return Some(hit(HitKind::Unloaded, Some((x, y)), None));
+            }
+
+            if let Some(id) = self.get((x, y)).filter(|id| solid(id)) {
+                return Some(hit(HitKind::Solid, Some((x, y)), Some(id.clone())));
+            }
+
+            if side.x < side.y {
+                distance = side.x;
+                side.x += delta.x;
+                cell.x += step.x;
+                normal = Vector2::new(-step.x as f32, 0.0);
+            } else {
+                distance = side.y;
+                side.y += delta.y;
+                cell.y += step.y;
+                normal = Vector2::new(0.0, -step.y as f32);
+            }
+
+            if distance > max_distance {
+                return None;
+            }
+        }
+    }
+
+    /// True when nothing solid lies between `from` and `to`, or when the first
+    /// solid tile hit is the tile containing `to` itself.
+    pub fn visible(&self, from: Vector2<f32>, to: Vector2<f32>) -> bool {
+        let offset = to - from;
+        let target = Self::tile_pos(to);
+
+        match self.raycast(from, offset, offset.magnitude()) {
+            Some(hit) => hit.kind == HitKind::Solid && hit.tile == target,
+            None => true,
+        }
+    }
+
+    fn boundary(start: f32, cell: i64, step: i64, delta: f32) -> f32 {
+        match step {
+            1 => (cell as f32 + 1.0 - start) * delta,
+            -1 => (start - cell as f32) * delta,
+            _ => f32::INFINITY,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chunk_manager::{ChunkData, CHUNK_SIZE, METAL};
+
+    fn map(chunks: &[(u32, u32)], solid: &[(u32, u32)]) -> TileMap {
+        let mut tile_map = TileMap::default();
+
+        for &(x, y) in chunks {
+            tile_map.chunks.insert(
+                (x, y),
+                ChunkData {
+                    position: [x as f32, y as f32],
+                    grid: vec![vec![None; CHUNK_SIZE as usize]; CHUNK_SIZE as usize],
+                    hazards: Vec::new(),
+                },
+            );
+        }
+
+        for &tile in solid {
+            tile_map.set(tile, Some(METAL.to_string()));
+        }
+
+        tile_map
+    }
+
+    fn assert_near(a: f32, b: f32) {
+        assert!((a - b).abs() < 1e-4, "{a} != {b}");
+    }
+
+    #[test]
+    fn crosses_chunk_boundaries() {
+        let tile_map = map(&[(0, 0), (1, 0)], &[(20, 3)]);
+        let hit = tile_map
+            .raycast(Vector2::new(3.0, 3.0), Vector2::new(1.0, 0.0), 30.0)
+            .unwrap();
+
+        assert_eq!(hit.kind, HitKind::Solid);
+        assert_eq!(hit.tile, Some((20, 3)));
+        assert_eq!(hit.id.as_deref(), Some(METAL));
+        assert_eq!(hit.normal, Vector2::new(-1.0, 0.0));
+        assert_near(hit.distance, 16.5);
+        assert_near(hit.position.x, 19.5);
+    }
+
+    #[test]
+    fn stops_at_unloaded_chunks() {
+        let tile_map = map(&[(0, 0)], &[(20, 3)]);
+        let hit = tile_map
+            .raycast(Vector2::new(3.0, 3.0), Vector2::new(1.0, 0.0), 30.0)
+            .unwrap();
+
+        assert_eq!(hit.kind, HitKind::Unloaded);
+        assert_eq!(hit.tile, Some((CHUNK_SIZE, 3)));
+        assert_eq!(hit.id, None);
+        assert_near(hit.distance, CHUNK_SIZE as f32 - 3.5);
+    }
+
+    #[test]
+    fn starting_inside_a_solid_tile_hits_it() {
+        let tile_map = map(&[(0, 0)], &[(3, 3)]);
+        let hit = tile_map
+            .raycast(Vector2::new(3.2, 2.9), Vector2::new(1.0, 1.0), 10.0)
+            .unwrap();
+
+        assert_eq!(hit.kind, HitKind::Solid);
+        assert_eq!(hit.tile, Some((3, 3)));
+        assert_eq!(hit.normal, Vector2::zeros());
+        assert_near(hit.distance, 0.0);
+    }
+
+    #[test]
+    fn axis_aligned_directions() {
+        let tile_map = map(&[(0, 0)], &[(8, 5), (2, 5), (5, 8), (5, 2)]);
+        let origin = Vector2::new(5.0, 5.0);
+
+        for (direction, tile) in [
+            (Vector2::new(1.0, 0.0), (8, 5)),
+            (Vector2::new(-1.0, 0.0), (2, 5)),
+            (Vector2::new(0.0, 1.0), (5, 8)),
+            (Vector2::new(0.0, -1.0), (5, 2)),
+        ] {
+            let hit = tile_map.raycast(origin, direction, 10.0).unwrap();
+
+            assert_eq!(hit.kind, HitKind::Solid);
+            assert_eq!(hit.tile, Some(tile));
+            assert_eq!(hit.normal, -direction);
+            assert_near(hit.distance, 2.5);
+        }
+    }
+
+    #[test]
+    fn negative_coordinates_are_unloaded() {
+        let tile_map = map(&[(0, 0)], &[]);
+        let hit = tile_map
+            .raycast(Vector2::new(-3.0, 3.0), Vector2::new(1.0, 0.0), 10.0)
+            .unwrap();
+
+        assert_eq!(hit.kind, HitKind::Unloaded);
+        assert_eq!(hit.tile, None);
+        assert_near(hit.distance, 0.0);
+
+        let hit = tile_map
+            .raycast(Vector2::new(2.0, 3.0), Vector2::new(-1.0, 0.0), 10.0)
+            .unwrap();
+
+        assert_eq!(hit.kind, HitKind::Unloaded);
+        assert_eq!(hit.tile, None);
+        assert_near(hit.distance, 2.5);
+    }
+
+    #[test]
+    fn misses_beyond_max_distance() {
+        let tile_map = map(&[(0, 0)], &[(10, 3)]);
+
+        assert!(tile_map
+            .raycast(Vector2::new(3.0, 3.0), Vector2::new(1.0, 0.0), 5.0)
+            .is_none());
+        assert!(tile_map
+            .raycast(Vector2::new(3.0, 3.0), Vector2::zeros(), 5.0)
+            .is_none());
+    }
+
+    #[test]
+    fn visible_includes_target_tile() {
+        let tile_map = map(&[(0, 0)], &[(6, 3), (10, 3)]);
+
+        assert!(tile_map.visible(Vector2::new(3.0, 3.0), Vector2::new(5.0, 3.0)));
+        assert!(tile_map.visible(Vector2::new(3.0, 3.0), Vector2::new(6.0, 3.0)));
+        assert!(!tile_map.visible(Vector2::new(3.0, 3.0), Vector2::new(10.0, 3.0)));
+    }
+}
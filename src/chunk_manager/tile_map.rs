@@ -0,0 +1,72 @@
+use super::{ChunkData, CHUNK_SIZE};
+use hex::{nalgebra::Vector2, parking_lot::RwLock, Id};
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+};
+
+#[derive(Default)]
+pub struct TileMap {
+    pub chunks: HashMap<(u32, u32), ChunkData>,
+    pub entities: HashMap<(u32, u32), Id>,
+    pub dirty: HashSet<(u32, u32)>,
+    pub revisions: HashMap<(u32, u32), u64>,
+}
+
+impl TileMap {
+    pub fn new() -> Arc<RwLock<Self>> {
+        Arc::new(RwLock::new(Self::default()))
+    }
+
+    pub fn tile_pos(pos: Vector2<f32>) -> Option<(u32, u32)> {
+        let pos = pos.map(|p| p.round());
+
+        (pos.x >= 0.0 && pos.y >= 0.0).then_some((pos.x as u32, pos.y as u32))
+    }
+
+    pub fn tile_chunk((x, y): (u32, u32)) -> ((u32, u32), (usize, usize)) {
+        (
+            (x / CHUNK_SIZE, y / CHUNK_SIZE),
+            ((x % CHUNK_SIZE) as usize, (y % CHUNK_SIZE) as usize),
+        )
+    }
+
+    pub fn is_loaded(&self, tile: (u32, u32)) -> bool {
+        let (chunk, _) = Self::tile_chunk(tile);
+
+        self.chunks.contains_key(&chunk)
+    }
+
+    pub fn get(&self, tile: (u32, u32)) -> Option<&String> {
+        let (chunk, (i, j)) = Self::tile_chunk(tile);
+
+        self.chunks
+            .get(&chunk)
+            .and_then(|c| c.grid.get(i))
+            .and_then(|c| c.get(j))
+            .and_then(|t| t.as_ref())
+    }
+
+    pub fn get_at(&self, pos: Vector2<f32>) -> Option<&String> {
+        Self::tile_pos(pos).and_then(|t| self.get(t))
+    }
+
+    pub fn set(&mut self, tile: (u32, u32), id: Option<String>) -> Option<String> {
+        let (chunk, (i, j)) = Self::tile_chunk(tile);
+        let grid = self.chunks.get_mut(&chunk)?;
+        let prev = std::mem::replace(&mut grid.grid[i][j], id);
+
+        self.dirty.insert(chunk);
+        *self.revisions.entry(chunk).or_default() += 1;
+
+        prev
+    }
+
+    pub fn revision(&self, chunk: (u32, u32)) -> u64 {
+        self.revisions.get(&chunk).copied().unwrap_or_default()
+    }
+
+    pub fn tile_position((x, y): (u32, u32)) -> Vector2<f32> {
+        Vector2::new(x as f32, y as f32)
+    }
+}
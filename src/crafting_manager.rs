@@ -0,0 +1,496 @@
+use crate::{
+    hud_manager::Notifications,
+    inventory::{Inventory, ItemStack},
+    tag::TagIndex,
+};
+use hex::{
+    anyhow,
+    parking_lot::RwLock,
+    winit::event::{Event, WindowEvent},
+    world::{system_manager::System, World},
+    Context, Control, Id,
+};
+use serde_derive::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, VecDeque},
+    fs,
+    path::Path,
+    sync::Arc,
+    time::Instant,
+};
+
+pub const RECIPES_FILE: &str = "data/recipes.json";
+pub const CRAFT_TICK: f32 = 0.1;
+pub const MAX_CRAFT_JOBS: usize = 8;
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Recipe {
+    pub id: String,
+    pub inputs: Vec<ItemStack>,
+    pub outputs: Vec<ItemStack>,
+    pub time: f32,
+    #[serde(default)]
+    pub station: Option<String>,
+}
+
+impl Recipe {
+    pub fn has_inputs(&self, inventory: &Inventory) -> bool {
+        self.inputs.iter().all(|s| inventory.has(&s.id, s.count))
+    }
+
+    pub fn station_ok(&self, station: Option<&str>) -> bool {
+        self.station.is_none() || self.station.as_deref() == station
+    }
+}
+
+#[derive(Default, Debug)]
+pub struct Recipes {
+    pub recipes: BTreeMap<String, Recipe>,
+}
+
+impl Recipes {
+    pub fn load<P>(path: P) -> anyhow::Result<Arc<RwLock<Self>>>
+    where
+        P: AsRef<Path>,
+    {
+        let path = path.as_ref();
+        let recipes = if Path::exists(path) {
+            Self::from_list(serde_json::from_str(fs::read_to_string(path)?.as_str())?)
+        } else {
+            Self::default()
+        };
+
+        Ok(Arc::new(RwLock::new(recipes)))
+    }
+
+    pub fn from_list(list: Vec<Recipe>) -> Self {
+        Self {
+            recipes: list.into_iter().map(|r| (r.id.clone(), r)).collect(),
+        }
+    }
+
+    pub fn insert(&mut self, recipe: Recipe) {
+        self.recipes.insert(recipe.id.clone(), recipe);
+    }
+
+    pub fn get(&self, id: &str) -> Option<&Recipe> {
+        self.recipes.get(id)
+    }
+
+    pub fn available<'a>(
+        &'a self,
+        inventory: &'a Inventory,
+        station: Option<&'a str>,
+    ) -> impl Iterator<Item = &'a Recipe> {
+        self.recipes
+            .values()
+            .filter(move |r| r.station_ok(station) && r.has_inputs(inventory))
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct CraftJob {
+    pub recipe: String,
+    pub progress: f32,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum CraftError {
+    UnknownRecipe,
+    MissingInputs,
+    WrongStation,
+    QueueFull,
+}
+
+#[derive(Default, Debug)]
+pub struct Crafter {
+    pub queue: VecDeque<CraftJob>,
+    pub station: Option<String>,
+    pub selected: usize,
+    pub blocked: bool,
+}
+
+impl Crafter {
+    pub fn new() -> Arc<RwLock<Self>> {
+        Arc::new(RwLock::new(Self::default()))
+    }
+
+    pub fn enqueue(
+        &mut self,
+        recipes: &Recipes,
+        id: &str,
+        inventory: &mut Inventory,
+    ) -> Result<(), CraftError> {
+        let recipe = recipes.get(id).ok_or(CraftError::UnknownRecipe)?;
+
+        if self.queue.len() >= MAX_CRAFT_JOBS {
+            return Err(CraftError::QueueFull);
+        }
+
+        if !recipe.station_ok(self.station.as_deref()) {
+            return Err(CraftError::WrongStation);
+        }
+
+        if !recipe.has_inputs(inventory) {
+            return Err(CraftError::MissingInputs);
+        }
+
+        for input in &recipe.inputs {
+            inventory.remove(&input.id, input.count);
+        }
+
+        self.queue.push_back(CraftJob {
+            recipe: id.to_string(),
+            progress: 0.0,
+        });
+
+        Ok(())
+    }
+
+    pub fn cancel(&mut self, recipes: &Recipes, inventory: &mut Inventory) -> Option<CraftJob> {
+        let job = self.queue.pop_back()?;
+
+        if let Some(recipe) = recipes.get(&job.recipe) {
+            for input in &recipe.inputs {
+                inventory.add(&input.id, input.count);
+            }
+        }
+
+        Some(job)
+    }
+
+    pub fn tick(
+        &mut self,
+        recipes: &Recipes,
+        inventory: &mut Inventory,
+        delta: f32,
+    ) -> Vec<String> {
+        let mut done = Vec::new();
+        let mut delta = delta;
+
+        self.blocked = false;
+
+        while let Some(job) = self.queue.front_mut() {
+            let Some(recipe) = recipes.get(&job.recipe) else {
+                self.queue.pop_front();
+
+                continue;
+            };
+            let step = delta.min(recipe.time - job.progress).max(0.0);
+
+            job.progress += step;
+            delta -= step;
+
+            if job.progress < recipe.time {
+                break;
+            }
+
+            if recipe
+                .outputs
+                .iter()
+                .any(|s| inventory.space_for(&s.id) < s.count)
+            {
+                self.blocked = true;
+
+                break;
+            }
+
+            for output in &recipe.outputs {
+                inventory.add(&output.id, output.count);
+            }
+
+            done.push(recipe.id.clone());
+            self.queue.pop_front();
+        }
+
+        done
+    }
+
+    pub fn progress(&self, recipes: &Recipes) -> Option<(&str, f32)> {
+        let job = self.queue.front()?;
+        let time = recipes.get(&job.recipe).map(|r| r.time).unwrap_or(1.0);
+
+        Some((
+            job.recipe.as_str(),
+            if time > 0.0 {
+                (job.progress / time).clamp(0.0, 1.0)
+            } else {
+                1.0
+            },
+        ))
+    }
+
+    pub fn selected<'a>(&self, recipes: &'a Recipes) -> Option<&'a Recipe> {
+        recipes
+            .recipes
+            .values()
+            .nth(self.selected % recipes.recipes.len().max(1))
+    }
+}
+
+pub struct CraftingManager {
+    pub player: Option<Id>,
+    pub tags: Arc<RwLock<TagIndex>>,
+    pub recipes: Arc<RwLock<Recipes>>,
+    pub notifications: Arc<RwLock<Notifications>>,
+    pub accumulator: f32,
+    pub last_frame: Instant,
+}
+
+impl CraftingManager {
+    pub fn new(
+        recipes: Arc<RwLock<Recipes>>,
+        notifications: Arc<RwLock<Notifications>>,
+        tags: Arc<RwLock<TagIndex>>,
+    ) -> Self {
+        Self {
+            player: None,
+            tags,
+            recipes,
+            notifications,
+            accumulator: 0.0,
+            last_frame: Instant::now(),
+        }
+    }
+}
+
+impl System for CraftingManager {
+    fn init(&mut self, _: Arc<RwLock<Context>>, _: Arc<RwLock<World>>) -> anyhow::Result<()> {
+        self.player = self.tags.read().first("player");
+
+        Ok(())
+    }
+
+    fn update(
+        &mut self,
+        control: Arc<RwLock<Control>>,
+        context: Arc<RwLock<Context>>,
+        world: Arc<RwLock<World>>,
+    ) -> anyhow::Result<()> {
+        let event = control.read().event.clone();
+
+        if let Event::WindowEvent {
+            event: WindowEvent::RedrawRequested,
+            window_id,
+        } = event
+        {
+            if window_id != context.read().window.id() {
+                return Ok(());
+            }
+
+            let now = Instant::now();
+
+            self.accumulator += now.duration_since(self.last_frame).as_secs_f32();
+            self.last_frame = now;
+
+            if self.accumulator < CRAFT_TICK {
+                return Ok(());
+            }
+
+            let Some(player) = self.player else {
+                return Ok(());
+            };
+            let em = world.read().em.clone();
+            let em = em.read();
+            let (Some(crafter), Some(inventory)) = (
+                em.get_component::<Crafter>(player),
+                em.get_component::<Inventory>(player),
+            ) else {
+                return Ok(());
+            };
+            let mut crafter = crafter.write();
+            let was_blocked = crafter.blocked;
+            let recipes = self.recipes.read();
+            let mut done = Vec::new();
+
+            while self.accumulator >= CRAFT_TICK {
+                self.accumulator -= CRAFT_TICK;
+
+                done.extend(crafter.tick(&recipes, &mut inventory.write(), CRAFT_TICK));
+            }
+
+            let mut notifications = self.notifications.write();
+
+            for id in done {
+                notifications.push(format!("CRAFTED {}", id.to_uppercase()));
+            }
+
+            if crafter.blocked && !was_blocked {
+                notifications.push("CARGO FULL");
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stack(id: &str, count: u32) -> ItemStack {
+        ItemStack {
+            id: id.to_string(),
+            count,
+        }
+    }
+
+    fn recipes() -> Recipes {
+        Recipes::from_list(vec![
+            Recipe {
+                id: "plate".to_string(),
+                inputs: vec![stack("metal", 2)],
+                outputs: vec![stack("metal_plate", 1)],
+                time: 1.0,
+                station: None,
+            },
+            Recipe {
+                id: "circuit".to_string(),
+                inputs: vec![stack("silicate", 1), stack("metal_plate", 1)],
+                outputs: vec![stack("circuit", 1)],
+                time: 2.0,
+                station: Some("fabricator".to_string()),
+            },
+        ])
+    }
+
+    fn inventory(items: &[(&str, u32)]) -> Inventory {
+        let mut inventory = Inventory::default();
+
+        for (id, count) in items {
+            inventory.add(id, *count);
+        }
+
+        inventory
+    }
+
+    #[test]
+    fn unknown_recipe() {
+        let mut crafter = Crafter::default();
+        let mut inventory = inventory(&[]);
+
+        assert_eq!(
+            crafter.enqueue(&recipes(), "engine", &mut inventory),
+            Err(CraftError::UnknownRecipe)
+        );
+    }
+
+    #[test]
+    fn missing_inputs() {
+        let mut crafter = Crafter::default();
+        let mut inventory = inventory(&[("metal", 1)]);
+
+        assert_eq!(
+            crafter.enqueue(&recipes(), "plate", &mut inventory),
+            Err(CraftError::MissingInputs)
+        );
+        assert!(crafter.queue.is_empty());
+        assert_eq!(inventory.count("metal"), 1);
+    }
+
+    #[test]
+    fn wrong_station() {
+        let recipes = recipes();
+        let mut crafter = Crafter::default();
+        let mut inventory = inventory(&[("silicate", 1), ("metal_plate", 1)]);
+
+        assert_eq!(
+            crafter.enqueue(&recipes, "circuit", &mut inventory),
+            Err(CraftError::WrongStation)
+        );
+        assert_eq!(inventory.count("silicate"), 1);
+
+        crafter.station = Some("trading".to_string());
+
+        assert_eq!(
+            crafter.enqueue(&recipes, "circuit", &mut inventory),
+            Err(CraftError::WrongStation)
+        );
+
+        crafter.station = Some("fabricator".to_string());
+
+        assert_eq!(crafter.enqueue(&recipes, "circuit", &mut inventory), Ok(()));
+        assert_eq!(inventory.count("silicate"), 0);
+        assert_eq!(inventory.count("metal_plate"), 0);
+    }
+
+    #[test]
+    fn queue_full() {
+        let recipes = recipes();
+        let mut crafter = Crafter::default();
+        let mut inventory = inventory(&[("metal", 99)]);
+
+        for _ in 0..MAX_CRAFT_JOBS {
+            assert_eq!(crafter.enqueue(&recipes, "plate", &mut inventory), Ok(()));
+        }
+
+        assert_eq!(
+            crafter.enqueue(&recipes, "plate", &mut inventory),
+            Err(CraftError::QueueFull)
+        );
+        assert_eq!(crafter.queue.len(), MAX_CRAFT_JOBS);
+        assert_eq!(inventory.count("metal"), 99 - 2 * MAX_CRAFT_JOBS as u32);
+    }
+
+    #[test]
+    fn progress_carries_over_jobs() {
+        let recipes = recipes();
+        let mut crafter = Crafter::default();
+        let mut inventory = inventory(&[("metal", 6)]);
+
+        for _ in 0..3 {
+            crafter.enqueue(&recipes, "plate", &mut inventory).unwrap();
+        }
+
+        assert_eq!(
+            crafter.tick(&recipes, &mut inventory, 2.5),
+            vec!["plate"; 2]
+        );
+        assert_eq!(crafter.queue.len(), 1);
+        assert_eq!(crafter.progress(&recipes), Some(("plate", 0.5)));
+        assert_eq!(inventory.count("metal_plate"), 2);
+
+        assert_eq!(crafter.tick(&recipes, &mut inventory, 0.5), vec!["plate"]);
+        assert!(crafter.queue.is_empty());
+        assert_eq!(inventory.count("metal_plate"), 3);
+    }
+
+    #[test]
+    fn blocked_output() {
+        let recipes = recipes();
+        let mut crafter = Crafter::default();
+        let mut inventory = Inventory::with_size(1);
+
+        inventory.add("metal", 4);
+        crafter.enqueue(&recipes, "plate", &mut inventory).unwrap();
+
+        assert!(crafter.tick(&recipes, &mut inventory, 1.5).is_empty());
+        assert!(crafter.blocked);
+        assert_eq!(crafter.progress(&recipes), Some(("plate", 1.0)));
+
+        inventory.remove("metal", 2);
+
+        assert_eq!(crafter.tick(&recipes, &mut inventory, 0.0), vec!["plate"]);
+        assert!(!crafter.blocked);
+        assert_eq!(inventory.count("metal_plate"), 1);
+    }
+
+    #[test]
+    fn cancel_refunds_inputs() {
+        let recipes = recipes();
+        let mut crafter = Crafter::default();
+        let mut inventory = inventory(&[("metal", 4)]);
+
+        crafter.enqueue(&recipes, "plate", &mut inventory).unwrap();
+        crafter.enqueue(&recipes, "plate", &mut inventory).unwrap();
+        crafter.tick(&recipes, &mut inventory, 0.5);
+
+        assert_eq!(inventory.count("metal"), 0);
+        assert!(crafter.cancel(&recipes, &mut inventory).is_some());
+        assert_eq!(inventory.count("metal"), 2);
+        assert!(crafter.cancel(&recipes, &mut inventory).is_some());
+        assert_eq!(inventory.count("metal"), 4);
+        assert!(crafter.cancel(&recipes, &mut inventory).is_none());
+        assert_eq!(inventory.count("metal_plate"), 0);
+    }
+}
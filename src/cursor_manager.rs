@@ -0,0 +1,265 @@
+use crate::{
+    chunk_manager::{TileMap, TILE_SIZE},
+    font::{Font, Text},
+    game_manager::Player,
+    mining_manager::Miner,
+    tag::TagIndex,
+    util,
+};
+use hex::{
+    anyhow,
+    assets::Shape,
+    components::{Camera, Trans},
+    nalgebra::{Matrix2, Vector2, Vector4},
+    parking_lot::RwLock,
+    winit::{
+        dpi::PhysicalPosition,
+        event::{Event, WindowEvent},
+    },
+    world::{system_manager::System, World},
+    Context, Control, Id,
+};
+use hex_instance::components::Instance;
+use std::sync::Arc;
+
+pub const CROSSHAIR_SCALE: f32 = 0.75;
+pub const OUTLINE_WIDTH: u32 = 2;
+pub const TOOLTIP_PIXEL_MUL: f32 = 1.0 / 200.0;
+pub const TOOLTIP_OFFSET: f32 = 0.6;
+
+pub struct CursorManager {
+    pub player: Option<Id>,
+    pub camera: Option<Id>,
+    pub crosshair: Option<Id>,
+    pub outline: Option<Id>,
+    pub tags: Arc<RwLock<TagIndex>>,
+    pub tile_map: Arc<RwLock<TileMap>>,
+    pub crosshair_instance: Arc<RwLock<Instance>>,
+    pub outline_instance: Arc<RwLock<Instance>>,
+    pub tooltip: Text,
+    pub focused: bool,
+}
+
+impl CursorManager {
+    pub fn new(
+        context: &Context,
+        tile_map: Arc<RwLock<TileMap>>,
+        tags: Arc<RwLock<TagIndex>>,
+    ) -> anyhow::Result<Self> {
+        let shape = Arc::new(Shape::rect(context, Vector2::new(1.0, 1.0))?);
+        let crosshair_instance = Instance::new(
+            context,
+            shape.clone(),
+            Arc::new(util::load_texture(context, "art/crosshair.png")?),
+            Vector4::new(1.0, 1.0, 1.0, 1.0),
+            0,
+        )?;
+        let outline_instance = Instance::new(
+            context,
+            shape,
+            Arc::new(Self::outline_texture(context)),
+            Vector4::new(1.0, 1.0, 0.0, 1.0),
+            0,
+        )?;
+
+        Ok(Self {
+            player: None,
+            tags,
+            camera: None,
+            crosshair: None,
+            outline: None,
+            tile_map,
+            crosshair_instance,
+            outline_instance,
+            tooltip: Text::new(Font::new(context, Vector4::new(1.0, 1.0, 1.0, 1.0), 0)?),
+            focused: true,
+        })
+    }
+
+    fn outline_texture(context: &Context) -> hex::assets::Texture {
+        let mut img = Vec::with_capacity((TILE_SIZE * TILE_SIZE * 4) as usize);
+
+        for y in 0..TILE_SIZE {
+            for x in 0..TILE_SIZE {
+                let edge = x < OUTLINE_WIDTH
+                    || y < OUTLINE_WIDTH
+                    || x >= TILE_SIZE - OUTLINE_WIDTH
+                    || y >= TILE_SIZE - OUTLINE_WIDTH;
+
+                img.extend_from_slice(&[255, 255, 255, if edge { 255 } else { 0 }]);
+            }
+        }
+
+        util::pixel_texture(context, &img, TILE_SIZE, TILE_SIZE)
+    }
+
+    fn set_scale(world: &World, e: Option<Id>, scale: Vector2<f32>) {
+        if let Some(t) = e.and_then(|e| world.em.read().get_component::<Trans>(e)) {
+            t.write().set_scale(scale);
+        }
+    }
+}
+
+impl System for CursorManager {
+    fn init(
+        &mut self,
+        context: Arc<RwLock<Context>>,
+        world: Arc<RwLock<World>>,
+    ) -> anyhow::Result<()> {
+        let em = world.read().em.clone();
+        let mut em = em.write();
+
+        self.player = self.tags.read().first("player");
+        self.camera = self.tags.read().first("camera");
+
+        let crosshair = em.add(true);
+
+        em.add_component(crosshair, self.crosshair_instance.clone());
+        em.add_component(
+            crosshair,
+            Trans::new(Vector2::default(), 0.0, Vector2::default()),
+        );
+
+        self.crosshair = Some(crosshair);
+
+        let outline = em.add(true);
+
+        em.add_component(outline, self.outline_instance.clone());
+        em.add_component(
+            outline,
+            Trans::new(Vector2::default(), 0.0, Vector2::default()),
+        );
+
+        self.outline = Some(outline);
+
+        context.read().window.set_cursor_visible(false);
+
+        Ok(())
+    }
+
+    fn update(
+        &mut self,
+        control: Arc<RwLock<Control>>,
+        context: Arc<RwLock<Context>>,
+        world: Arc<RwLock<World>>,
+    ) -> anyhow::Result<()> {
+        let event = control.read().event.clone();
+
+        match event {
+            Event::WindowEvent {
+                event: WindowEvent::Focused(focused),
+                window_id,
+            } if window_id == context.read().window.id() => {
+                self.focused = focused;
+
+                context.read().window.set_cursor_visible(!focused);
+            }
+            Event::WindowEvent {
+                event: WindowEvent::CursorLeft { .. },
+                window_id,
+            } if window_id == context.read().window.id() => {
+                self.focused = false;
+            }
+            Event::WindowEvent {
+                event: WindowEvent::CursorEntered { .. },
+                window_id,
+            } if window_id == context.read().window.id() => {
+                self.focused = context.read().window.has_focus();
+            }
+            Event::WindowEvent {
+                event: WindowEvent::RedrawRequested,
+                window_id,
+            } if window_id == context.read().window.id() => {
+                let (Some(player), Some(camera)) = (self.player, self.camera) else {
+                    return Ok(());
+                };
+                let world = world.read();
+
+                if !self.focused {
+                    Self::set_scale(&world, self.crosshair, Vector2::default());
+                    Self::set_scale(&world, self.outline, Vector2::default());
+
+                    self.tooltip.hide(&world.em.read());
+
+                    return Ok(());
+                }
+
+                let (cursor, rotation, pixel, tooltip) = {
+                    let em = world.em.read();
+                    let camera_transform = em.get_component::<Trans>(camera).unwrap();
+                    let camera_transform = camera_transform.read();
+                    let camera = em.get_component::<Camera>(camera).unwrap();
+                    let camera = camera.read();
+                    let mouse = em.get_component::<Player>(player).unwrap().read().cursor;
+                    let Some(cursor) = util::screen_to_world(
+                        &camera,
+                        &camera_transform,
+                        context.read().window.inner_size(),
+                        PhysicalPosition::new(mouse.x as f64, mouse.y as f64),
+                    ) else {
+                        return Ok(());
+                    };
+                    let tile = TileMap::tile_pos(cursor);
+                    let tooltip = tile.and_then(|tile| {
+                        self.tile_map.read().get(tile).map(|id| {
+                            let progress = em
+                                .get_component::<Miner>(player)
+                                .and_then(|m| m.read().progress_at(tile));
+
+                            match progress {
+                                Some(p) => format!("{id}\n{:.0}%", p * 100.0),
+                                None => id.clone(),
+                            }
+                        })
+                    });
+
+                    if let Some(t) = self.crosshair.and_then(|e| em.get_component::<Trans>(e)) {
+                        let mut t = t.write();
+
+                        t.set_position(cursor);
+                        t.set_rotation(camera_transform.rotation());
+                        t.set_scale(Vector2::new(CROSSHAIR_SCALE, CROSSHAIR_SCALE));
+                    }
+
+                    if let Some(t) = self.outline.and_then(|e| em.get_component::<Trans>(e)) {
+                        let mut t = t.write();
+
+                        match tile.filter(|_| tooltip.is_some()) {
+                            Some(tile) => {
+                                t.set_position(TileMap::tile_position(tile));
+                                t.set_scale(Vector2::new(1.0, 1.0));
+                            }
+                            None => t.set_scale(Vector2::default()),
+                        }
+                    }
+
+                    (
+                        cursor,
+                        camera_transform.rotation(),
+                        camera.dimensions().y * TOOLTIP_PIXEL_MUL,
+                        tooltip,
+                    )
+                };
+                let mut em = world.em.write();
+
+                match tooltip {
+                    Some(tooltip) => {
+                        self.tooltip.set(&mut em, &tooltip);
+                        self.tooltip.layout(
+                            &em,
+                            cursor
+                                + Matrix2::new_rotation(rotation)
+                                    * Vector2::new(TOOLTIP_OFFSET, -TOOLTIP_OFFSET),
+                            pixel,
+                            rotation,
+                        );
+                    }
+                    None => self.tooltip.clear(&mut em),
+                }
+            }
+            _ => {}
+        }
+
+        Ok(())
+    }
+}
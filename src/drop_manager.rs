@@ -0,0 +1,305 @@
+use crate::{
+    chunk_manager::{Tile, TileMap},
+    events::{EventBus, GameEvent, Subscriber},
+    gravity_manager::GravityField,
+    inventory::{Inventory, ItemStack},
+    prefab::{ComponentRegistry, EntityData, Sprite, TransData},
+    spatial_manager::SpatialIndex,
+    tag::TagIndex,
+};
+use hex::{
+    anyhow,
+    components::Trans,
+    nalgebra::Vector2,
+    parking_lot::RwLock,
+    winit::event::{Event, WindowEvent},
+    world::{system_manager::System, EntityManager, World},
+    Context, Control, Id,
+};
+use rand::prelude::*;
+use serde_derive::{Deserialize, Serialize};
+use std::{collections::HashSet, f32::consts::TAU, sync::Arc, time::Instant};
+
+pub const DROP_TAG: &str = "drop";
+pub const DROP_SCALE: f32 = 0.4;
+pub const DROP_SPEED: f32 = 1.5;
+pub const DROP_DRAG: f32 = 1.5;
+pub const DROP_LIFETIME: f32 = 300.0;
+pub const DROP_PICKUP_RADIUS: f32 = 3.0;
+pub const DROP_COLLECT_RADIUS: f32 = 0.5;
+pub const DROP_MAGNET_ACCEL: f32 = 30.0;
+pub const DROP_MAGNET_SPEED: f32 = 12.0;
+pub const DROP_MERGE_RADIUS: f32 = 0.75;
+pub const DROP_FALLBACK_TEXTURE: &str = "art/player_projectile.png";
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ItemDrop {
+    pub id: String,
+    pub count: u32,
+    pub velocity: [f32; 2],
+    pub age: f32,
+}
+
+impl ItemDrop {
+    pub fn new(stack: ItemStack, velocity: Vector2<f32>) -> Self {
+        Self {
+            id: stack.id,
+            count: stack.count,
+            velocity: velocity.into(),
+            age: 0.0,
+        }
+    }
+
+    pub fn entity_data(self, position: Vector2<f32>) -> anyhow::Result<EntityData> {
+        let texture = Tile::file_map(&self.id).unwrap_or_else(|| DROP_FALLBACK_TEXTURE.into());
+
+        EntityData::default()
+            .with("tag", &DROP_TAG)?
+            .with(
+                "trans",
+                &TransData {
+                    position: position.into(),
+                    rotation: 0.0,
+                    scale: [DROP_SCALE; 2],
+                },
+            )?
+            .with(
+                "sprite",
+                &Sprite {
+                    texture,
+                    color: Tile::tint(&self.id),
+                    size: [1.0; 2],
+                    layer: 1,
+                },
+            )?
+            .with("drop", &self)?
+            .with("persistent", &())
+    }
+}
+
+pub struct DropManager {
+    pub player: Option<Id>,
+    pub tags: Arc<RwLock<TagIndex>>,
+    pub registry: Arc<RwLock<ComponentRegistry>>,
+    pub events: Arc<RwLock<EventBus>>,
+    pub gravity: Arc<RwLock<GravityField>>,
+    pub spatial: Arc<RwLock<SpatialIndex>>,
+    pub subscriber: Subscriber,
+    pub rng: StdRng,
+    last_frame: Instant,
+}
+
+impl DropManager {
+    pub fn new(
+        tags: Arc<RwLock<TagIndex>>,
+        registry: Arc<RwLock<ComponentRegistry>>,
+        events: Arc<RwLock<EventBus>>,
+        gravity: Arc<RwLock<GravityField>>,
+        spatial: Arc<RwLock<SpatialIndex>>,
+    ) -> Self {
+        let subscriber = events.write().subscribe();
+
+        Self {
+            player: None,
+            tags,
+            registry,
+            events,
+            gravity,
+            spatial,
+            subscriber,
+            rng: StdRng::from_entropy(),
+            last_frame: Instant::now(),
+        }
+    }
+
+    pub fn spawn(
+        &mut self,
+        em: &mut EntityManager,
+        stack: ItemStack,
+        position: Vector2<f32>,
+    ) -> anyhow::Result<Id> {
+        let angle = self.rng.gen_range(0.0..TAU);
+        let speed = self.rng.gen_range(0.5..1.0) * DROP_SPEED;
+        let velocity = Vector2::new(angle.cos(), angle.sin()) * speed;
+        let data = ItemDrop::new(stack, velocity).entity_data(position)?;
+
+        self.registry.read().spawn(em, &data)
+    }
+
+    fn collect(&self, em: &EntityManager, player: Id, drop: &mut ItemDrop) {
+        let Some(inventory) = em.get_component::<Inventory>(player) else {
+            return;
+        };
+        let left = inventory.write().add(&drop.id, drop.count);
+        let picked = drop.count - left;
+
+        if picked > 0 {
+            drop.count = left;
+            self.events.write().publish(GameEvent::ItemPicked {
+                entity: player,
+                id: drop.id.clone(),
+                count: picked,
+            });
+        }
+    }
+
+    fn merge(&self, em: &EntityManager, drops: &[(Id, Vector2<f32>)]) -> Vec<Id> {
+        let spatial = self.spatial.read();
+        let live: HashSet<_> = drops.iter().map(|(e, _)| *e).collect();
+        let mut merged = HashSet::new();
+
+        for (a, pa) in drops {
+            if merged.contains(a) {
+                continue;
+            }
+
+            let Some(da) = em.get_component::<ItemDrop>(*a) else {
+                continue;
+            };
+
+            for b in spatial.radius(*pa, DROP_MERGE_RADIUS) {
+                if b == *a || !live.contains(&b) || merged.contains(&b) {
+                    continue;
+                }
+
+                let Some(db) = em.get_component::<ItemDrop>(b) else {
+                    continue;
+                };
+                let mut da = da.write();
+                let db = db.read();
+
+                if da.id == db.id {
+                    da.count += db.count;
+                    da.age = da.age.min(db.age);
+                    merged.insert(b);
+                }
+            }
+        }
+
+        merged.into_iter().collect()
+    }
+}
+
+impl System for DropManager {
+    fn init(&mut self, _: Arc<RwLock<Context>>, _: Arc<RwLock<World>>) -> anyhow::Result<()> {
+        self.player = self.tags.read().first("player");
+
+        Ok(())
+    }
+
+    fn update(
+        &mut self,
+        control: Arc<RwLock<Control>>,
+        context: Arc<RwLock<Context>>,
+        world: Arc<RwLock<World>>,
+    ) -> anyhow::Result<()> {
+        let event = control.read().event.clone();
+
+        if let Event::WindowEvent {
+            event: WindowEvent::RedrawRequested,
+            window_id,
+        } = event
+        {
+            if window_id != context.read().window.id() {
+                return Ok(());
+            }
+
+            let now = Instant::now();
+            let delta = now.duration_since(self.last_frame).as_secs_f32();
+
+            self.last_frame = now;
+
+            let Some(player) = self.player else {
+                return Ok(());
+            };
+            let em = world.read().em.clone();
+            let mut em = em.write();
+            let events = self.events.write().poll(self.subscriber);
+
+            for event in events {
+                match event {
+                    GameEvent::TileMined { tile, id, .. } => {
+                        self.spawn(
+                            &mut em,
+                            ItemStack { id, count: 1 },
+                            TileMap::tile_position(tile),
+                        )?;
+                    }
+                    GameEvent::ItemsDropped { position, items } => {
+                        for stack in items {
+                            self.spawn(&mut em, stack, position)?;
+                        }
+                    }
+                    _ => {}
+                }
+            }
+
+            let Some(target) = em
+                .get_component::<Trans>(player)
+                .map(|t| t.read().position())
+            else {
+                return Ok(());
+            };
+            let ids: Vec<_> = self.tags.read().all(DROP_TAG).collect();
+            let gravity = self.gravity.read();
+            let mut drops = Vec::new();
+            let mut rm = Vec::new();
+
+            for e in ids {
+                let (Some(drop), Some(trans)) = (
+                    em.get_component::<ItemDrop>(e),
+                    em.get_component::<Trans>(e),
+                ) else {
+                    continue;
+                };
+                let mut drop = drop.write();
+                let mut trans = trans.write();
+                let offset = target - trans.position();
+                let distance = offset.magnitude();
+                let space = em
+                    .get_component::<Inventory>(player)
+                    .is_some_and(|i| i.read().space_for(&drop.id) > 0);
+                let mut velocity = Vector2::from(drop.velocity);
+
+                drop.age += delta;
+
+                if space && distance <= DROP_COLLECT_RADIUS {
+                    self.collect(&em, player, &mut drop);
+                }
+
+                if drop.count == 0 || drop.age >= DROP_LIFETIME {
+                    rm.push(e);
+
+                    continue;
+                }
+
+                if space && distance <= DROP_PICKUP_RADIUS && distance > 0.0 {
+                    velocity += offset / distance * DROP_MAGNET_ACCEL * delta;
+
+                    if velocity.magnitude() > DROP_MAGNET_SPEED {
+                        velocity = velocity.normalize() * DROP_MAGNET_SPEED;
+                    }
+                } else if !gravity.in_field(trans.position()) {
+                    velocity *= (1.0 - DROP_DRAG * delta).max(0.0);
+                }
+
+                drop.velocity = velocity.into();
+                trans.set_position(trans.position() + velocity * delta);
+                drops.push((e, trans.position()));
+            }
+
+            rm.extend(self.merge(&em, &drops));
+
+            let mut tags = self.tags.write();
+
+            let mut spatial = self.spatial.write();
+
+            for e in rm {
+                spatial.remove(e);
+                tags.rm(&mut em, e);
+            }
+        }
+
+        Ok(())
+    }
+}
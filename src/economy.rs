@@ -0,0 +1,256 @@
+use crate::{chunk_manager::SAVE_DIR, station_manager::StationData};
+use hex::{anyhow, parking_lot::RwLock};
+use serde_derive::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+pub const ECONOMY_FILE: &str = "economy.json";
+pub const ECONOMY_TICK: f32 = 1.0;
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug)]
+pub struct Quote {
+    pub buy: u64,
+    pub sell: u64,
+    pub stock: u32,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct StationQuotes {
+    pub chunk: [u32; 2],
+    pub name: String,
+    pub tick: u64,
+    pub prices: BTreeMap<String, Quote>,
+}
+
+#[derive(Clone, Debug)]
+pub struct Route {
+    pub id: String,
+    pub from: (u32, u32),
+    pub to: (u32, u32),
+    pub buy: u64,
+    pub sell: u64,
+}
+
+impl Route {
+    pub fn profit(&self) -> u64 {
+        self.sell.saturating_sub(self.buy)
+    }
+}
+
+#[derive(Serialize, Deserialize, Default, Debug)]
+#[serde(default)]
+pub struct Economy {
+    pub tick: u64,
+    pub quotes: BTreeMap<String, StationQuotes>,
+    #[serde(skip)]
+    pub accumulator: f32,
+}
+
+impl Economy {
+    pub fn path() -> PathBuf {
+        PathBuf::from(SAVE_DIR).join(ECONOMY_FILE)
+    }
+
+    pub fn load() -> anyhow::Result<Arc<RwLock<Self>>> {
+        let path = Self::path();
+        let economy = if Path::exists(&path) {
+            serde_json::from_str(fs::read_to_string(path)?.as_str())?
+        } else {
+            Self::default()
+        };
+
+        Ok(Arc::new(RwLock::new(economy)))
+    }
+
+    pub fn save(&self) -> anyhow::Result<()> {
+        fs::create_dir_all(SAVE_DIR)?;
+        fs::write(Self::path(), serde_json::to_string(self)?)?;
+
+        Ok(())
+    }
+
+    pub fn key((x, y): (u32, u32)) -> String {
+        format!("{x},{y}")
+    }
+
+    pub fn advance(&mut self, delta: f32) -> u64 {
+        self.accumulator += delta;
+
+        let ticks = (self.accumulator / ECONOMY_TICK) as u64;
+
+        self.accumulator -= ticks as f32 * ECONOMY_TICK;
+        self.tick += ticks;
+
+        ticks
+    }
+
+    pub fn observe(&mut self, chunk: (u32, u32), station: &StationData) {
+        self.quotes.insert(
+            Self::key(chunk),
+            StationQuotes {
+                chunk: [chunk.0, chunk.1],
+                name: station.name.clone(),
+                tick: station.market.tick,
+                prices: station
+                    .market
+                    .entries
+                    .iter()
+                    .map(|(id, e)| {
+                        (
+                            id.clone(),
+                            Quote {
+                                buy: e.price(),
+                                sell: e.sell_price(),
+                                stock: e.stock,
+                            },
+                        )
+                    })
+                    .collect(),
+            },
+        );
+    }
+
+    pub fn quote(&self, chunk: (u32, u32), id: &str) -> Option<Quote> {
+        self.quotes
+            .get(&Self::key(chunk))
+            .and_then(|q| q.prices.get(id))
+            .copied()
+    }
+
+    pub fn routes(&self, from: Option<(u32, u32)>) -> Vec<Route> {
+        let mut routes = Vec::new();
+
+        for a in self.quotes.values() {
+            let a_chunk = (a.chunk[0], a.chunk[1]);
+
+            if from.is_some_and(|f| f != a_chunk) {
+                continue;
+            }
+
+            for b in self.quotes.values().filter(|b| b.chunk != a.chunk) {
+                for (id, buy) in a.prices.iter().filter(|(_, q)| q.stock > 0) {
+                    if let Some(sell) = b.prices.get(id).filter(|s| s.sell > buy.buy) {
+                        routes.push(Route {
+                            id: id.clone(),
+                            from: a_chunk,
+                            to: (b.chunk[0], b.chunk[1]),
+                            buy: buy.buy,
+                            sell: sell.sell,
+                        });
+                    }
+                }
+            }
+        }
+
+        routes.sort_by(|a, b| {
+            b.profit()
+                .cmp(&a.profit())
+                .then_with(|| a.id.cmp(&b.id))
+                .then_with(|| a.to.cmp(&b.to))
+        });
+
+        routes
+    }
+
+    pub fn name(&self, chunk: (u32, u32)) -> Option<&str> {
+        self.quotes.get(&Self::key(chunk)).map(|q| q.name.as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn station(chunk: (u32, u32), prices: &[(&str, u64, u64, u32)]) -> (String, StationQuotes) {
+        (
+            Economy::key(chunk),
+            StationQuotes {
+                chunk: [chunk.0, chunk.1],
+                name: Economy::key(chunk),
+                tick: 0,
+                prices: prices
+                    .iter()
+                    .map(|(id, buy, sell, stock)| {
+                        (
+                            id.to_string(),
+                            Quote {
+                                buy: *buy,
+                                sell: *sell,
+                                stock: *stock,
+                            },
+                        )
+                    })
+                    .collect(),
+            },
+        )
+    }
+
+    fn summary(routes: &[Route]) -> Vec<(&str, (u32, u32), (u32, u32), u64)> {
+        routes
+            .iter()
+            .map(|r| (r.id.as_str(), r.from, r.to, r.profit()))
+            .collect()
+    }
+
+    fn economy() -> Economy {
+        Economy {
+            quotes: [
+                station((2, 2), &[("metal", 10, 8, 5), ("circuit", 40, 32, 0)]),
+                station((3, 3), &[("metal", 20, 16, 5), ("circuit", 60, 50, 3)]),
+                station((4, 4), &[("metal", 30, 25, 0), ("circuit", 30, 24, 2)]),
+            ]
+            .into_iter()
+            .collect(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn routes_sorted_by_profit() {
+        assert_eq!(
+            summary(&economy().routes(None)),
+            vec![
+                ("circuit", (4, 4), (3, 3), 20),
+                ("metal", (2, 2), (4, 4), 15),
+                ("metal", (2, 2), (3, 3), 6),
+                ("metal", (3, 3), (4, 4), 5),
+                ("circuit", (4, 4), (2, 2), 2),
+            ]
+        );
+    }
+
+    #[test]
+    fn routes_from_station() {
+        assert_eq!(
+            summary(&economy().routes(Some((3, 3)))),
+            vec![("metal", (3, 3), (4, 4), 5)]
+        );
+    }
+
+    #[test]
+    fn routes_break_ties_by_id_then_destination() {
+        let economy = Economy {
+            quotes: [
+                station((2, 2), &[("metal", 10, 8, 5), ("asteroid_1", 5, 4, 5)]),
+                station((4, 4), &[("metal", 25, 20, 0)]),
+                station((3, 3), &[("metal", 25, 20, 0), ("asteroid_1", 20, 15, 0)]),
+            ]
+            .into_iter()
+            .collect(),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            summary(&economy.routes(None)),
+            vec![
+                ("asteroid_1", (2, 2), (3, 3), 10),
+                ("metal", (2, 2), (3, 3), 10),
+                ("metal", (2, 2), (4, 4), 10),
+            ]
+        );
+    }
+}
@@ -0,0 +1,295 @@
+use crate::{game_manager::Thrust, health::DamageSource, inventory::ItemStack};
+use hex::{
+    anyhow,
+    nalgebra::Vector2,
+    parking_lot::RwLock,
+    winit::event::{Event, WindowEvent},
+    world::{system_manager::System, World},
+    Context, Control, Id,
+};
+use std::{collections::VecDeque, sync::Arc};
+
+pub const MAX_EVENTS: usize = 4096;
+
+#[derive(Clone, Debug)]
+pub enum GameEvent {
+    TileMined {
+        tile: (u32, u32),
+        id: String,
+        by: Id,
+    },
+    TileChanged {
+        tile: (u32, u32),
+        old: Option<String>,
+        new: Option<String>,
+    },
+    ChunkLoaded {
+        chunk: (u32, u32),
+    },
+    ChunkUnloaded {
+        chunk: (u32, u32),
+    },
+    EntityDamaged {
+        entity: Id,
+        amount: f32,
+        source: DamageSource,
+    },
+    ItemPicked {
+        entity: Id,
+        id: String,
+        count: u32,
+    },
+    PlayerDied {
+        entity: Id,
+        position: Vector2<f32>,
+    },
+    EntityDestroyed {
+        entity: Id,
+        kind: Option<String>,
+        position: Vector2<f32>,
+    },
+    ItemsDropped {
+        position: Vector2<f32>,
+        items: Vec<ItemStack>,
+    },
+    ThrustChanged {
+        entity: Id,
+        thrust: Thrust,
+        active: bool,
+    },
+    TriggerChanged {
+        entity: Id,
+        active: bool,
+    },
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Subscriber(usize);
+
+/// Events published during a tick are buffered and become visible together
+/// when `EventManager` swaps the buffer at the start of the next tick. Every
+/// subscriber then sees the same batch in publish order, and systems receive
+/// it in the order the system manager runs them, i.e. by priority. A
+/// subscriber that falls more than `MAX_EVENTS` behind skips the oldest events.
+#[derive(Default)]
+pub struct EventBus {
+    pub events: VecDeque<GameEvent>,
+    pub pending: Vec<GameEvent>,
+    pub offset: u64,
+    pub cursors: Vec<Option<u64>>,
+}
+
+impl EventBus {
+    pub fn new() -> Arc<RwLock<Self>> {
+        Arc::new(RwLock::new(Self::default()))
+    }
+
+    pub fn end(&self) -> u64 {
+        self.offset + self.events.len() as u64
+    }
+
+    pub fn subscribe(&mut self) -> Subscriber {
+        self.cursors.push(Some(self.end()));
+
+        Subscriber(self.cursors.len() - 1)
+    }
+
+    pub fn unsubscribe(&mut self, subscriber: Subscriber) {
+        if let Some(cursor) = self.cursors.get_mut(subscriber.0) {
+            *cursor = None;
+        }
+
+        self.trim();
+    }
+
+    pub fn publish(&mut self, event: GameEvent) {
+        if self.cursors.iter().all(Option::is_none) {
+            return;
+        }
+
+        self.pending.push(event);
+    }
+
+    pub fn swap(&mut self) {
+        self.events.extend(self.pending.drain(..));
+        self.trim();
+    }
+
+    pub fn poll(&mut self, subscriber: Subscriber) -> Vec<GameEvent> {
+        let end = self.end();
+        let offset = self.offset;
+        let Some(Some(cursor)) = self.cursors.get_mut(subscriber.0) else {
+            return Vec::new();
+        };
+        let start = cursor.saturating_sub(offset) as usize;
+
+        *cursor = end;
+
+        let events = self.events.range(start..).cloned().collect();
+
+        self.trim();
+
+        events
+    }
+
+    fn trim(&mut self) {
+        let seen = self
+            .cursors
+            .iter()
+            .flatten()
+            .copied()
+            .min()
+            .unwrap_or(self.end())
+            .max(self.end().saturating_sub(MAX_EVENTS as u64));
+
+        while self.offset < seen && self.events.pop_front().is_some() {
+            self.offset += 1;
+        }
+    }
+}
+
+pub struct EventManager {
+    pub events: Arc<RwLock<EventBus>>,
+}
+
+impl EventManager {
+    pub fn new(events: Arc<RwLock<EventBus>>) -> Self {
+        Self { events }
+    }
+}
+
+impl System for EventManager {
+    fn update(
+        &mut self,
+        control: Arc<RwLock<Control>>,
+        context: Arc<RwLock<Context>>,
+        _: Arc<RwLock<World>>,
+    ) -> anyhow::Result<()> {
+        let event = control.read().event.clone();
+
+        if let Event::WindowEvent {
+            event: WindowEvent::RedrawRequested,
+            window_id,
+        } = event
+        {
+            if window_id == context.read().window.id() {
+                self.events.write().swap();
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn loaded(chunk: (u32, u32)) -> GameEvent {
+        GameEvent::ChunkLoaded { chunk }
+    }
+
+    fn chunks(events: Vec<GameEvent>) -> Vec<(u32, u32)> {
+        events
+            .into_iter()
+            .filter_map(|e| match e {
+                GameEvent::ChunkLoaded { chunk } => Some(chunk),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn delivered_in_publish_order() {
+        let mut bus = EventBus::default();
+        let s = bus.subscribe();
+
+        for x in 0..4 {
+            bus.publish(loaded((x, 0)));
+        }
+
+        bus.swap();
+
+        assert_eq!(chunks(bus.poll(s)), vec![(0, 0), (1, 0), (2, 0), (3, 0)]);
+        assert!(bus.poll(s).is_empty());
+    }
+
+    #[test]
+    fn held_until_next_tick() {
+        let mut bus = EventBus::default();
+        let early = bus.subscribe();
+        let late = bus.subscribe();
+
+        bus.publish(loaded((0, 0)));
+        bus.swap();
+
+        assert_eq!(chunks(bus.poll(early)), vec![(0, 0)]);
+
+        bus.publish(loaded((1, 0)));
+
+        assert_eq!(chunks(bus.poll(late)), vec![(0, 0)]);
+
+        bus.swap();
+
+        assert_eq!(chunks(bus.poll(early)), vec![(1, 0)]);
+        assert_eq!(chunks(bus.poll(late)), vec![(1, 0)]);
+        assert!(bus.events.is_empty());
+    }
+
+    #[test]
+    fn subscribers_miss_earlier_events() {
+        let mut bus = EventBus::default();
+
+        bus.publish(loaded((0, 0)));
+        bus.swap();
+
+        let first = bus.subscribe();
+
+        bus.publish(loaded((1, 0)));
+        bus.swap();
+
+        let second = bus.subscribe();
+
+        bus.publish(loaded((2, 0)));
+        bus.swap();
+
+        assert_eq!(chunks(bus.poll(first)), vec![(1, 0), (2, 0)]);
+        assert_eq!(chunks(bus.poll(second)), vec![(2, 0)]);
+    }
+
+    #[test]
+    fn unsubscribed_stops_receiving() {
+        let mut bus = EventBus::default();
+        let s = bus.subscribe();
+        let idle = bus.subscribe();
+
+        bus.publish(loaded((0, 0)));
+        bus.swap();
+        bus.unsubscribe(idle);
+
+        assert!(bus.poll(idle).is_empty());
+        assert_eq!(chunks(bus.poll(s)), vec![(0, 0)]);
+        assert!(bus.events.is_empty());
+
+        bus.unsubscribe(s);
+        bus.publish(loaded((1, 0)));
+
+        assert!(bus.pending.is_empty());
+    }
+
+    #[test]
+    fn idle_subscriber_is_bounded() {
+        let mut bus = EventBus::default();
+        let s = bus.subscribe();
+        let idle = bus.subscribe();
+
+        for x in 0..MAX_EVENTS as u32 * 2 {
+            bus.publish(loaded((x, 0)));
+            bus.swap();
+            bus.poll(s);
+        }
+
+        assert_eq!(bus.events.len(), MAX_EVENTS);
+        assert_eq!(bus.poll(idle).len(), MAX_EVENTS);
+    }
+}
@@ -0,0 +1,169 @@
+use crate::chunk_manager::{MAX_CHUNK, MIN_CHUNK, SAVE_DIR};
+use hex::{anyhow, parking_lot::RwLock};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+pub const EXPLORED_FILE: &str = "explored.bin";
+pub const SENSOR_RANGE: u32 = 2;
+pub const SECTOR_SIZE: u32 = 32;
+pub const EXPLORATION_SAVE_TIME: f32 = 5.0;
+
+const MAGIC: &[u8; 4] = b"EXPL";
+
+pub struct Exploration {
+    pub size: u32,
+    pub bits: Vec<u64>,
+    pub count: u32,
+    pub dirty: bool,
+}
+
+impl Exploration {
+    pub fn new(size: u32) -> Self {
+        let len = (size as usize * size as usize).div_ceil(64);
+
+        Self {
+            size,
+            bits: vec![0; len],
+            count: 0,
+            dirty: false,
+        }
+    }
+
+    pub fn path() -> PathBuf {
+        PathBuf::from(SAVE_DIR).join(EXPLORED_FILE)
+    }
+
+    pub fn load() -> anyhow::Result<Arc<RwLock<Self>>> {
+        let path = Self::path();
+        let exploration = if Path::exists(&path) {
+            Self::from_bytes(&fs::read(path)?)?
+        } else {
+            Self::new(MAX_CHUNK)
+        };
+
+        Ok(Arc::new(RwLock::new(exploration)))
+    }
+
+    pub fn save(&mut self) -> anyhow::Result<()> {
+        fs::create_dir_all(SAVE_DIR)?;
+        fs::write(Self::path(), self.to_bytes())?;
+
+        self.dirty = false;
+
+        Ok(())
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(8 + self.bits.len() * 8);
+
+        bytes.extend_from_slice(MAGIC);
+        bytes.extend_from_slice(&self.size.to_le_bytes());
+
+        for word in &self.bits {
+            bytes.extend_from_slice(&word.to_le_bytes());
+        }
+
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> anyhow::Result<Self> {
+        if bytes.len() < 8 || &bytes[..4] != MAGIC {
+            anyhow::bail!("invalid exploration data");
+        }
+
+        let size = u32::from_le_bytes(bytes[4..8].try_into()?);
+        let mut exploration = Self::new(size);
+
+        for (word, chunk) in exploration.bits.iter_mut().zip(bytes[8..].chunks_exact(8)) {
+            *word = u64::from_le_bytes(chunk.try_into()?);
+        }
+
+        exploration.count = exploration.bits.iter().map(|w| w.count_ones()).sum();
+
+        Ok(exploration)
+    }
+
+    fn index(&self, (x, y): (u32, u32)) -> Option<usize> {
+        (x < self.size && y < self.size).then(|| y as usize * self.size as usize + x as usize)
+    }
+
+    pub fn get(&self, chunk: (u32, u32)) -> bool {
+        self.index(chunk)
+            .map(|i| (self.bits[i / 64] >> (i % 64)) & 1 == 1)
+            .unwrap_or(false)
+    }
+
+    pub fn set(&mut self, chunk: (u32, u32)) -> bool {
+        let Some(i) = self.index(chunk) else {
+            return false;
+        };
+        let mask = 1 << (i % 64);
+
+        if self.bits[i / 64] & mask != 0 {
+            return false;
+        }
+
+        self.bits[i / 64] |= mask;
+        self.count += 1;
+        self.dirty = true;
+
+        true
+    }
+
+    pub fn explore(&mut self, (x, y): (u32, u32), range: u32) {
+        for i in x.saturating_sub(range).max(MIN_CHUNK)..=(x + range).min(self.size - 1) {
+            for j in y.saturating_sub(range).max(MIN_CHUNK)..=(y + range).min(self.size - 1) {
+                self.set((i, j));
+            }
+        }
+    }
+
+    pub fn sector(chunk: (u32, u32)) -> (u32, u32) {
+        (chunk.0 / SECTOR_SIZE, chunk.1 / SECTOR_SIZE)
+    }
+
+    pub fn explored_in(&self, min: (u32, u32), max: (u32, u32)) -> (u32, u32) {
+        let mut explored = 0;
+        let mut total = 0;
+
+        for x in min.0..max.0.min(self.size) {
+            for y in min.1..max.1.min(self.size) {
+                total += 1;
+
+                if self.get((x, y)) {
+                    explored += 1;
+                }
+            }
+        }
+
+        (explored, total)
+    }
+
+    pub fn sector_percent(&self, (sx, sy): (u32, u32)) -> f32 {
+        let min = (
+            (sx * SECTOR_SIZE).max(MIN_CHUNK),
+            (sy * SECTOR_SIZE).max(MIN_CHUNK),
+        );
+        let max = ((sx + 1) * SECTOR_SIZE, (sy + 1) * SECTOR_SIZE);
+        let (explored, total) = self.explored_in(min, max);
+
+        if total == 0 {
+            0.0
+        } else {
+            explored as f32 / total as f32 * 100.0
+        }
+    }
+
+    pub fn percent(&self) -> f32 {
+        let side = self.size.saturating_sub(MIN_CHUNK);
+
+        if side == 0 {
+            0.0
+        } else {
+            self.count as f32 / (side * side) as f32 * 100.0
+        }
+    }
+}
@@ -0,0 +1,232 @@
+use crate::util;
+use hex::{
+    anyhow,
+    assets::Shape,
+    components::Trans,
+    nalgebra::{Matrix2, Vector2, Vector4},
+    parking_lot::RwLock,
+    world::EntityManager,
+    Context, Id,
+};
+use hex_instance::components::Instance;
+use std::{collections::HashMap, sync::Arc};
+
+pub const GLYPH_WIDTH: u32 = 3;
+pub const GLYPH_HEIGHT: u32 = 5;
+pub const GLYPH_ADVANCE: u32 = GLYPH_WIDTH + 1;
+pub const LINE_ADVANCE: u32 = GLYPH_HEIGHT + 1;
+
+const GLYPHS: &[(char, [u8; 5])] = &[
+    ('0', [0b111, 0b101, 0b101, 0b101, 0b111]),
+    ('1', [0b010, 0b110, 0b010, 0b010, 0b111]),
+    ('2', [0b111, 0b001, 0b111, 0b100, 0b111]),
+    ('3', [0b111, 0b001, 0b111, 0b001, 0b111]),
+    ('4', [0b101, 0b101, 0b111, 0b001, 0b001]),
+    ('5', [0b111, 0b100, 0b111, 0b001, 0b111]),
+    ('6', [0b111, 0b100, 0b111, 0b101, 0b111]),
+    ('7', [0b111, 0b001, 0b001, 0b001, 0b001]),
+    ('8', [0b111, 0b101, 0b111, 0b101, 0b111]),
+    ('9', [0b111, 0b101, 0b111, 0b001, 0b111]),
+    ('A', [0b010, 0b101, 0b111, 0b101, 0b101]),
+    ('B', [0b110, 0b101, 0b110, 0b101, 0b110]),
+    ('C', [0b011, 0b100, 0b100, 0b100, 0b011]),
+    ('D', [0b110, 0b101, 0b101, 0b101, 0b110]),
+    ('E', [0b111, 0b100, 0b110, 0b100, 0b111]),
+    ('F', [0b111, 0b100, 0b110, 0b100, 0b100]),
+    ('G', [0b011, 0b100, 0b101, 0b101, 0b011]),
+    ('H', [0b101, 0b101, 0b111, 0b101, 0b101]),
+    ('I', [0b111, 0b010, 0b010, 0b010, 0b111]),
+    ('J', [0b001, 0b001, 0b001, 0b101, 0b010]),
+    ('K', [0b101, 0b101, 0b110, 0b101, 0b101]),
+    ('L', [0b100, 0b100, 0b100, 0b100, 0b111]),
+    ('M', [0b101, 0b111, 0b111, 0b101, 0b101]),
+    ('N', [0b110, 0b101, 0b101, 0b101, 0b101]),
+    ('O', [0b010, 0b101, 0b101, 0b101, 0b010]),
+    ('P', [0b110, 0b101, 0b110, 0b100, 0b100]),
+    ('Q', [0b010, 0b101, 0b101, 0b110, 0b011]),
+    ('R', [0b110, 0b101, 0b110, 0b101, 0b101]),
+    ('S', [0b011, 0b100, 0b010, 0b001, 0b110]),
+    ('T', [0b111, 0b010, 0b010, 0b010, 0b010]),
+    ('U', [0b101, 0b101, 0b101, 0b101, 0b111]),
+    ('V', [0b101, 0b101, 0b101, 0b101, 0b010]),
+    ('W', [0b101, 0b101, 0b111, 0b111, 0b101]),
+    ('X', [0b101, 0b101, 0b010, 0b101, 0b101]),
+    ('Y', [0b101, 0b101, 0b010, 0b010, 0b010]),
+    ('Z', [0b111, 0b001, 0b010, 0b100, 0b111]),
+    ('.', [0b000, 0b000, 0b000, 0b000, 0b010]),
+    (',', [0b000, 0b000, 0b000, 0b010, 0b100]),
+    (':', [0b000, 0b010, 0b000, 0b010, 0b000]),
+    ('-', [0b000, 0b000, 0b111, 0b000, 0b000]),
+    ('+', [0b000, 0b010, 0b111, 0b010, 0b000]),
+    ('=', [0b000, 0b111, 0b000, 0b111, 0b000]),
+    ('/', [0b001, 0b001, 0b010, 0b100, 0b100]),
+    ('%', [0b101, 0b001, 0b010, 0b100, 0b101]),
+    ('(', [0b010, 0b100, 0b100, 0b100, 0b010]),
+    (')', [0b010, 0b001, 0b001, 0b001, 0b010]),
+    ('[', [0b110, 0b100, 0b100, 0b100, 0b110]),
+    (']', [0b011, 0b001, 0b001, 0b001, 0b011]),
+    ('<', [0b001, 0b010, 0b100, 0b010, 0b001]),
+    ('>', [0b100, 0b010, 0b001, 0b010, 0b100]),
+    ('_', [0b000, 0b000, 0b000, 0b000, 0b111]),
+    ('!', [0b010, 0b010, 0b010, 0b000, 0b010]),
+    ('?', [0b110, 0b001, 0b010, 0b000, 0b010]),
+    ('#', [0b101, 0b111, 0b101, 0b111, 0b101]),
+    ('*', [0b101, 0b010, 0b101, 0b000, 0b000]),
+    ('|', [0b010, 0b010, 0b010, 0b010, 0b010]),
+    ('\'', [0b010, 0b010, 0b000, 0b000, 0b000]),
+];
+
+pub struct Font {
+    pub glyphs: HashMap<char, Arc<RwLock<Instance>>>,
+}
+
+impl Font {
+    pub fn new(context: &Context, color: Vector4<f32>, layer: i32) -> anyhow::Result<Arc<Self>> {
+        let shape = Arc::new(Shape::rect(context, Vector2::new(1.0, 1.0))?);
+        let mut glyphs = HashMap::new();
+
+        for (c, rows) in GLYPHS {
+            let mut img = Vec::with_capacity((GLYPH_WIDTH * GLYPH_HEIGHT * 4) as usize);
+
+            for row in rows {
+                for x in (0..GLYPH_WIDTH).rev() {
+                    let a = if (row >> x) & 1 == 1 { 255 } else { 0 };
+
+                    img.extend_from_slice(&[255, 255, 255, a]);
+                }
+            }
+
+            let texture = util::pixel_texture(context, &img, GLYPH_WIDTH, GLYPH_HEIGHT);
+
+            glyphs.insert(
+                *c,
+                Instance::new(context, shape.clone(), Arc::new(texture), color, layer)?,
+            );
+        }
+
+        Ok(Arc::new(Self { glyphs }))
+    }
+
+    pub fn glyph(&self, c: char) -> Option<Arc<RwLock<Instance>>> {
+        if c.is_whitespace() {
+            None
+        } else {
+            self.glyphs
+                .get(&c.to_ascii_uppercase())
+                .or_else(|| self.glyphs.get(&'?'))
+                .cloned()
+        }
+    }
+
+    pub fn measure(text: &str, pixel: f32) -> Vector2<f32> {
+        let lines = text.lines().count().max(1) as u32;
+        let columns = text
+            .lines()
+            .map(|l| l.chars().count())
+            .max()
+            .unwrap_or_default() as u32;
+
+        Vector2::new(
+            (columns * GLYPH_ADVANCE).saturating_sub(1) as f32,
+            (lines * LINE_ADVANCE).saturating_sub(1) as f32,
+        ) * pixel
+    }
+}
+
+pub struct Text {
+    pub font: Arc<Font>,
+    pub value: String,
+    pub glyphs: Vec<(Id, u32, u32)>,
+}
+
+impl Text {
+    pub fn new(font: Arc<Font>) -> Self {
+        Self {
+            font,
+            value: String::new(),
+            glyphs: Vec::new(),
+        }
+    }
+
+    pub fn set(&mut self, em: &mut EntityManager, value: &str) {
+        if self.value == value {
+            return;
+        }
+
+        let glyphs: Vec<_> = value
+            .lines()
+            .enumerate()
+            .flat_map(|(row, line)| {
+                line.chars()
+                    .enumerate()
+                    .map(move |(col, c)| (c, col as u32, row as u32))
+            })
+            .filter_map(|(c, col, row)| self.font.glyph(c).map(|instance| (instance, col, row)))
+            .collect();
+
+        if self.glyphs.len() > glyphs.len() {
+            for (e, _, _) in self.glyphs.drain(glyphs.len()..) {
+                em.rm(e);
+            }
+        }
+
+        for (i, (instance, col, row)) in glyphs.into_iter().enumerate() {
+            match self.glyphs.get_mut(i) {
+                Some((e, c, r)) => {
+                    if !em
+                        .get_component::<Instance>(*e)
+                        .is_some_and(|current| Arc::ptr_eq(&current, &instance))
+                    {
+                        em.add_component(*e, instance);
+                    }
+
+                    (*c, *r) = (col, row);
+                }
+                None => {
+                    let e = em.add(true);
+
+                    em.add_component(e, instance);
+                    em.add_component(e, Trans::new(Vector2::default(), 0.0, Vector2::default()));
+
+                    self.glyphs.push((e, col, row));
+                }
+            }
+        }
+
+        self.value = value.to_string();
+    }
+
+    pub fn clear(&mut self, em: &mut EntityManager) {
+        for (e, _, _) in self.glyphs.drain(..) {
+            em.rm(e);
+        }
+
+        self.value.clear();
+    }
+
+    pub fn layout(&self, em: &EntityManager, origin: Vector2<f32>, pixel: f32, rotation: f32) {
+        let rot = Matrix2::new_rotation(rotation);
+
+        for (e, col, row) in &self.glyphs {
+            if let Some(t) = em.get_component::<Trans>(*e) {
+                let offset = Vector2::new(
+                    (col * GLYPH_ADVANCE) as f32 + GLYPH_WIDTH as f32 / 2.0,
+                    -((row * LINE_ADVANCE) as f32 + GLYPH_HEIGHT as f32 / 2.0),
+                ) * pixel;
+                let mut t = t.write();
+
+                t.set_position(origin + rot * offset);
+                t.set_rotation(rotation);
+                t.set_scale(Vector2::new(GLYPH_WIDTH as f32, GLYPH_HEIGHT as f32) * pixel);
+            }
+        }
+    }
+
+    pub fn hide(&self, em: &EntityManager) {
+        for (e, _, _) in &self.glyphs {
+            if let Some(t) = em.get_component::<Trans>(*e) {
+                t.write().set_scale(Vector2::default());
+            }
+        }
+    }
+}
@@ -1,4 +1,22 @@
-use crate::{tag::Tag, util};
+use crate::{
+    build_manager::Builder,
+    camera_manager::CameraController,
+    chunk_manager::SAVE_DIR,
+    crafting_manager::Crafter,
+    events::{EventBus, GameEvent, Subscriber},
+    gravity_manager::GravityField,
+    hazard_manager::Environment,
+    health::{Health, PLAYER_HEALTH, PLAYER_INVULNERABILITY},
+    inventory::Inventory,
+    map_manager::MapMarker,
+    mining_manager::Miner,
+    module_manager::{Loadout, ShipStats},
+    ship_manager::{ShipConfig, ShipSystems, SHIP_SYSTEMS_FILE},
+    station_manager::{Docking, Wallet, STARTING_CREDITS},
+    tag::TagIndex,
+    util,
+    waypoint_manager::SPAWN_POSITION,
+};
 use hex::{
     anyhow,
     assets::Shape,
@@ -6,7 +24,7 @@ use hex::{
     nalgebra::{Matrix3, Vector2, Vector4},
     parking_lot::RwLock,
     winit::{
-        dpi::PhysicalSize,
+        dpi::PhysicalPosition,
         event::{Event, WindowEvent},
     },
     world::{system_manager::System, EntityManager, World},
@@ -14,18 +32,78 @@ use hex::{
 };
 use hex_instance::components::Instance;
 use hex_physics::components::Collider;
-use std::{sync::Arc, time::Instant};
+use serde_derive::{Deserialize, Serialize};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::Instant,
+};
 
 pub const PLAYER_ACCEL: f32 = 0.05;
 pub const PLAYER_MAX_SPEED: f32 = 10.0;
 pub const PLAYER_DECCEL_MUL: f32 = 0.1;
+pub const PLAYER_FILE: &str = "player.json";
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct PlayerData {
+    pub position: [f32; 2],
+    pub inventory: Inventory,
+    pub loadout: Loadout,
+    pub credits: u64,
+}
+
+impl Default for PlayerData {
+    fn default() -> Self {
+        Self {
+            position: SPAWN_POSITION,
+            inventory: Inventory::default(),
+            loadout: Loadout::starter(),
+            credits: STARTING_CREDITS,
+        }
+    }
+}
+
+impl PlayerData {
+    pub fn path() -> PathBuf {
+        PathBuf::from(SAVE_DIR).join(PLAYER_FILE)
+    }
+
+    pub fn load() -> anyhow::Result<Self> {
+        let path = Self::path();
+
+        if Path::exists(&path) {
+            Ok(serde_json::from_str(fs::read_to_string(path)?.as_str())?)
+        } else {
+            Ok(Self::default())
+        }
+    }
+
+    pub fn save(&self) -> anyhow::Result<()> {
+        fs::create_dir_all(SAVE_DIR)?;
+        fs::write(Self::path(), serde_json::to_string_pretty(self)?)?;
+
+        Ok(())
+    }
+
+    pub fn collect(em: &EntityManager, player: Id) -> Option<Self> {
+        Some(Self {
+            position: em.get_component::<Trans>(player)?.read().position().into(),
+            inventory: em.get_component::<Inventory>(player)?.read().clone(),
+            loadout: em.get_component::<Loadout>(player)?.read().clone(),
+            credits: em.get_component::<Wallet>(player)?.read().credits,
+        })
+    }
+}
 
 pub struct GameManager {
     pub player: Id,
     pub camera: Id,
-    pub last_fs: bool,
+    pub gravity: Arc<RwLock<GravityField>>,
+    pub events: Arc<RwLock<EventBus>>,
+    pub subscriber: Subscriber,
     pub mouse_position: Vector2<f32>,
-    pub dims: (u32, u32),
     pub last_frame: Instant,
 }
 
@@ -33,15 +111,38 @@ impl GameManager {
     pub fn new(
         context: Arc<RwLock<Context>>,
         em: Arc<RwLock<EntityManager>>,
+        tags: Arc<RwLock<TagIndex>>,
+        gravity: Arc<RwLock<GravityField>>,
+        events: Arc<RwLock<EventBus>>,
     ) -> anyhow::Result<Self> {
+        let data = PlayerData::load()?;
         let mut em = em.write();
+        let mut tags = tags.write();
         let player = em.add(true);
 
         em.add_component(player, Arc::new(RwLock::new(Player::default())));
-        em.add_component(player, Tag::new("player"));
+        tags.tag(&mut em, player, "player");
+        em.add_component(player, Miner::new());
+        em.add_component(player, Arc::new(RwLock::new(data.inventory)));
+        em.add_component(player, Loadout::new(data.loadout));
+        em.add_component(player, ShipStats::new());
+        em.add_component(player, Wallet::new(data.credits));
+        em.add_component(player, Docking::new());
+        em.add_component(player, Crafter::new());
+        em.add_component(player, Builder::new());
+        em.add_component(player, Environment::new());
+        em.add_component(
+            player,
+            Health::with_invulnerability(PLAYER_HEALTH, PLAYER_INVULNERABILITY),
+        );
+        em.add_component(
+            player,
+            ShipSystems::new(ShipConfig::load(SHIP_SYSTEMS_FILE)?),
+        );
+        em.add_component(player, MapMarker::new(Vector4::new(0.2, 1.0, 0.2, 1.0)));
         em.add_component(
             player,
-            Trans::new(Vector2::new(0.0, 100.0), 0.0, Vector2::new(1.0, 1.0)),
+            Trans::new(data.position.into(), 0.0, Vector2::new(1.0, 1.0)),
         );
         em.add_component(
             player,
@@ -67,19 +168,26 @@ impl GameManager {
 
         let camera = em.add(true);
 
-        em.add_component(camera, Tag::new("camera"));
+        tags.tag(&mut em, camera, "camera");
+        em.add_component(
+            camera,
+            CameraController::new(player, data.position.into(), rand::random()),
+        );
         em.add_component(camera, Camera::new(Vector2::new(25.0, 25.0), 1000));
         em.add_component(
             camera,
-            Trans::new(Vector2::new(0.0, 0.0), 0.0, Vector2::new(1.0, 1.0)),
+            Trans::new(data.position.into(), 0.0, Vector2::new(1.0, 1.0)),
         );
 
+        let subscriber = events.write().subscribe();
+
         Ok(Self {
             player,
             camera,
-            last_fs: Default::default(),
+            gravity,
+            events,
+            subscriber,
             mouse_position: Default::default(),
-            dims: Default::default(),
             last_frame: Instant::now(),
         })
     }
@@ -96,18 +204,22 @@ impl System for GameManager {
 
         match event {
             Event::WindowEvent {
-                event: WindowEvent::Resized(PhysicalSize { width, height }, ..),
+                event: WindowEvent::CursorMoved { position, .. },
                 window_id,
             } if window_id == context.read().window.id() => {
-                self.dims = (width, height);
+                let pos = Vector2::new(position.x as f32, position.y as f32);
+
+                self.mouse_position = pos;
             }
             Event::WindowEvent {
-                event: WindowEvent::CursorMoved { position, .. },
+                event: WindowEvent::CloseRequested,
                 window_id,
             } if window_id == context.read().window.id() => {
-                let pos = Vector2::new(position.x as f32, position.y as f32);
+                let em = world.read().em.clone();
 
-                self.mouse_position = pos;
+                if let Some(data) = PlayerData::collect(&em.read(), self.player) {
+                    data.save()?;
+                }
             }
             Event::WindowEvent {
                 event: WindowEvent::RedrawRequested,
@@ -116,16 +228,18 @@ impl System for GameManager {
                 let em = world.read().em.clone();
                 let em = em.read();
                 let camera = em.get_component::<Camera>(self.camera).unwrap();
+                let window = context.read().window.inner_size();
                 {
                     let mut camera = camera.write();
+                    let scale_factor = context.read().window.scale_factor() as f32;
                     let (window_x, window_y) = {
-                        let window_dims_x = self.dims.0 as i32;
-                        let window_dims_y = self.dims.1 as i32;
-                        let asp_ratio = self.dims.0 as f32 / self.dims.1 as f32;
+                        let window_dims_x = window.width as i32;
+                        let window_dims_y = window.height as i32;
+                        let asp_ratio = window.width as f32 / window.height as f32;
 
                         (
-                            window_dims_x as f32 / asp_ratio / 50.0,
-                            window_dims_y as f32 / asp_ratio / 50.0,
+                            window_dims_x as f32 / asp_ratio / 50.0 / scale_factor,
+                            window_dims_y as f32 / asp_ratio / 50.0 / scale_factor,
                         )
                     };
 
@@ -133,16 +247,20 @@ impl System for GameManager {
                 }
                 let camera = camera.read();
                 let camera_transform = em.get_component::<Trans>(self.camera).unwrap();
-                let mut camera_transform = camera_transform.write();
-                let pos = util::mouse_pos_world(
-                    camera.dimensions(),
-                    camera_transform.scale(),
-                    self.dims,
-                    (self.mouse_position.x as f64, self.mouse_position.y as f64),
-                )
-                .unwrap_or_default();
+                let camera_transform = camera_transform.read();
                 let player_transform = em.get_component::<Trans>(self.player).unwrap();
                 let player_transform = &mut *player_transform.write();
+                let aim = util::screen_to_world(
+                    &camera,
+                    &camera_transform,
+                    window,
+                    PhysicalPosition::new(
+                        self.mouse_position.x as f64,
+                        self.mouse_position.y as f64,
+                    ),
+                )
+                .unwrap_or(player_transform.position());
+                let pos = aim - player_transform.position();
                 let cross = Vector2::new(0.0, 1.0).perp(&pos);
                 let angle = Vector2::new(0.0, 1.0).angle(&pos);
                 let angle = if cross < 0.0 { angle } else { -angle };
@@ -155,28 +273,80 @@ impl System for GameManager {
 
                 let player = em.get_component::<Player>(self.player).unwrap();
                 let player = &mut *player.write();
-                let f = player.force();
+
+                player.aim = aim;
+                player.cursor = self.mouse_position;
+
+                for event in self.events.write().poll(self.subscriber) {
+                    if let GameEvent::ThrustChanged {
+                        entity,
+                        thrust,
+                        active,
+                    } = event
+                    {
+                        if entity == self.player {
+                            player.states.set(thrust, active);
+                        }
+                    }
+                }
+
+                if em
+                    .get_component::<Docking>(self.player)
+                    .is_some_and(|d| d.read().is_docked())
+                {
+                    player.velocity = Vector2::default();
+
+                    return Ok(());
+                }
+
+                let (thrust, boost) = em
+                    .get_component::<ShipSystems>(self.player)
+                    .map(|s| {
+                        let s = s.read();
+
+                        (s.can_thrust(), s.boost_multiplier())
+                    })
+                    .unwrap_or((true, 1.0));
+                let stats = em
+                    .get_component::<ShipStats>(self.player)
+                    .map(|s| s.read().clone())
+                    .unwrap_or_default();
+                let env = em
+                    .get_component::<Environment>(self.player)
+                    .map(|e| e.read().clone())
+                    .unwrap_or_default();
+                let drag = if self.gravity.read().in_field(player_transform.position()) {
+                    0.0
+                } else {
+                    env.drag
+                };
+                let f = if thrust {
+                    player.force()
+                } else {
+                    Vector2::default()
+                };
                 let f = player.velocity
                     + if f.magnitude() != 0.0 {
                         (Matrix3::new_rotation(player_transform.rotation())
                             * util::lerp_vec2(f, Vector2::default(), 1.0).push(1.0))
                         .xy()
-                            * PLAYER_ACCEL
+                            * stats.accel
+                            * boost
                     } else {
                         -util::lerp_vec2(player.velocity, Vector2::default(), 1.0)
-                            * PLAYER_ACCEL
-                            * PLAYER_DECCEL_MUL
+                            * stats.accel
+                            * stats.deccel_mul
+                            * drag
                     };
                 player.velocity = if f.magnitude() != 0.0 {
-                    f.normalize() * f.magnitude().min(PLAYER_MAX_SPEED)
+                    f.normalize() * f.magnitude().min(stats.max_speed * boost)
                 } else {
                     Vector2::default()
-                };
+                } + env.pull * delta.as_secs_f32();
 
                 player_transform.set_position(
                     player_transform.position() + player.velocity * delta.as_secs_f32(),
                 );
-                camera_transform.set_position(player_transform.position());
             }
             _ => {}
         }
@@ -185,6 +355,14 @@ impl System for GameManager {
     }
 }
 
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Thrust {
+    Forward,
+    Backward,
+    Left,
+    Right,
+}
+
 #[derive(Default)]
 pub struct ButtonStates {
     pub forward: bool,
@@ -193,10 +371,23 @@ pub struct ButtonStates {
     pub right: bool,
 }
 
+impl ButtonStates {
+    pub fn set(&mut self, thrust: Thrust, active: bool) {
+        match thrust {
+            Thrust::Forward => self.forward = active,
+            Thrust::Backward => self.backward = active,
+            Thrust::Left => self.left = active,
+            Thrust::Right => self.right = active,
+        }
+    }
+}
+
 #[derive(Default)]
 pub struct Player {
     pub states: ButtonStates,
     pub velocity: Vector2<f32>,
+    pub aim: Vector2<f32>,
+    pub cursor: Vector2<f32>,
 }
 
 impl Player {
@@ -1,6 +1,24 @@
-use crate::{game_manager::Player, Tag};
+use crate::{
+    build_manager::Builder,
+    camera_manager::{CameraController, CameraMode},
+    crafting_manager::{Crafter, Recipes},
+    events::{EventBus, GameEvent},
+    game_manager::Thrust,
+    inventory::{Inventory, HOTBAR_SIZE},
+    map_manager::MapView,
+    mining_manager::Miner,
+    mission_manager::Missions,
+    module_manager::{Loadout, Modules},
+    settings::{Settings, SETTINGS_FILE},
+    ship_manager::ShipSystems,
+    station_manager::{Docking, StationAction},
+    tag::TagIndex,
+    waypoint_manager::{Waypoints, WAYPOINT_REMOVE_RADIUS},
+};
 use hex::{
     anyhow,
+    components::Trans,
+    nalgebra::Vector2,
     parking_lot::RwLock,
     winit::{
         event::{ElementState, Event, KeyEvent, MouseButton, WindowEvent},
@@ -30,13 +48,27 @@ pub type Binds = HashMap<
 
 pub struct GameUiManager {
     player: Option<Id>,
+    camera: Option<Id>,
+    state: Option<Id>,
+    tags: Arc<RwLock<TagIndex>>,
+    settings: Arc<RwLock<Settings>>,
+    events: Arc<RwLock<EventBus>>,
     kp_cb: Binds,
 }
 
 impl GameUiManager {
-    pub fn new() -> anyhow::Result<Self> {
+    pub fn new(
+        settings: Arc<RwLock<Settings>>,
+        tags: Arc<RwLock<TagIndex>>,
+        events: Arc<RwLock<EventBus>>,
+    ) -> anyhow::Result<Self> {
         Ok(Self {
             player: Default::default(),
+            tags,
+            camera: Default::default(),
+            state: Default::default(),
+            settings,
+            events,
             kp_cb: Default::default(),
         })
     }
@@ -60,44 +92,336 @@ impl GameUiManager {
 
     fn init_default_keybinds(&mut self, _: Arc<RwLock<World>>) {
         let player = self.player.unwrap();
+        let camera = self.camera.unwrap();
+        let state_id = self.state.unwrap();
+        let settings = self.settings.clone();
+
+        for (code, thrust) in [
+            (KeyCode::KeyW, Thrust::Forward),
+            (KeyCode::KeyS, Thrust::Backward),
+            (KeyCode::KeyA, Thrust::Left),
+            (KeyCode::KeyD, Thrust::Right),
+        ] {
+            let events = self.events.clone();
+
+            self.add_keybind(Input::Keyboard(code), move |state, _, _| {
+                events.write().publish(GameEvent::ThrustChanged {
+                    entity: player,
+                    thrust,
+                    active: Self::convert_state(state),
+                });
+
+                Ok(())
+            });
+        }
+        self.add_keybind(Input::Keyboard(KeyCode::Space), {
+            let events = self.events.clone();
+
+            move |state, _, _| {
+                events.write().publish(GameEvent::TriggerChanged {
+                    entity: player,
+                    active: Self::convert_state(state),
+                });
+
+                Ok(())
+            }
+        });
+        self.add_keybind(Input::Mouse(MouseButton::Left), move |state, _, world| {
+            let em = world.read().em.clone();
+            let em = em.read();
+
+            match em.get_component::<Builder>(player) {
+                Some(b) if b.read().active => {
+                    b.write().placing = Self::convert_state(state);
+                }
+                _ => {
+                    if let Some(m) = em.get_component::<Miner>(player) {
+                        m.write().active = Self::convert_state(state);
+                    }
+                }
+            }
+
+            Ok(())
+        });
+        self.add_keybind(Input::Keyboard(KeyCode::KeyB), move |state, _, world| {
+            let em = world.read().em.clone();
+            let em = em.read();
+
+            if let (Some(b), true) = (
+                em.get_component::<Builder>(player),
+                Self::convert_state(state),
+            ) {
+                b.write().toggle();
+
+                if let Some(m) = em.get_component::<Miner>(player) {
+                    m.write().active = false;
+                }
+            }
+
+            Ok(())
+        });
+
+        for (i, code) in [
+            KeyCode::Digit1,
+            KeyCode::Digit2,
+            KeyCode::Digit3,
+            KeyCode::Digit4,
+            KeyCode::Digit5,
+            KeyCode::Digit6,
+            KeyCode::Digit7,
+            KeyCode::Digit8,
+            KeyCode::Digit9,
+        ]
+        .into_iter()
+        .enumerate()
+        .take(HOTBAR_SIZE)
+        {
+            self.add_keybind(Input::Keyboard(code), move |state, _, world| {
+                if let Some(inventory) = world.read().em.read().get_component::<Inventory>(player) {
+                    if Self::convert_state(state) {
+                        inventory.write().selected = i;
+                    }
+                }
+
+                Ok(())
+            });
+        }
+
+        self.add_keybind(
+            Input::Keyboard(KeyCode::ShiftLeft),
+            move |state, _, world| {
+                if let Some(s) = world.read().em.read().get_component::<ShipSystems>(player) {
+                    s.write().boosting = Self::convert_state(state);
+                }
+
+                Ok(())
+            },
+        );
+        self.add_keybind(Input::Keyboard(KeyCode::KeyR), move |state, _, world| {
+            let em = world.read().em.clone();
+            let em = em.read();
+
+            if let (Some(s), Some(i)) = (
+                em.get_component::<ShipSystems>(player),
+                em.get_component::<Inventory>(player),
+            ) {
+                if Self::convert_state(state) {
+                    s.write().refine(&mut i.write());
+                }
+            }
+
+            Ok(())
+        });
+        self.add_keybind(Input::Keyboard(KeyCode::KeyV), move |state, _, world| {
+            if let Some(c) = world.read().em.read().get_component::<Crafter>(player) {
+                if Self::convert_state(state) {
+                    let mut c = c.write();
+
+                    c.selected = c.selected.wrapping_add(1);
+                }
+            }
+
+            Ok(())
+        });
+        self.add_keybind(Input::Keyboard(KeyCode::KeyC), move |state, _, world| {
+            let em = world.read().em.clone();
+            let em = em.read();
+
+            if let (Some(c), Some(i), Some(r)) = (
+                em.get_component::<Crafter>(player),
+                em.get_component::<Inventory>(player),
+                em.get_component::<Recipes>(state_id),
+            ) {
+                if Self::convert_state(state) {
+                    let r = r.read();
+                    let mut c = c.write();
 
-        self.add_keybind(Input::Keyboard(KeyCode::KeyW), move |state, _, world| {
-            if let Some(p) = world.read().em.read().get_component::<Player>(player) {
-                p.write().states.forward = Self::convert_state(state);
+                    if let Some(id) = c.selected(&r).map(|r| r.id.clone()) {
+                        let _ = c.enqueue(&r, &id, &mut i.write());
+                    }
+                }
+            }
+
+            Ok(())
+        });
+        self.add_keybind(
+            Input::Keyboard(KeyCode::Backspace),
+            move |state, _, world| {
+                let em = world.read().em.clone();
+                let em = em.read();
+
+                if let (Some(c), Some(i), Some(r)) = (
+                    em.get_component::<Crafter>(player),
+                    em.get_component::<Inventory>(player),
+                    em.get_component::<Recipes>(state_id),
+                ) {
+                    if Self::convert_state(state) {
+                        c.write().cancel(&r.read(), &mut i.write());
+                    }
+                }
+
+                Ok(())
+            },
+        );
+        self.add_keybind(Input::Keyboard(KeyCode::KeyU), move |state, _, world| {
+            let em = world.read().em.clone();
+            let em = em.read();
+
+            if let (Some(l), Some(i), Some(m)) = (
+                em.get_component::<Loadout>(player),
+                em.get_component::<Inventory>(player),
+                em.get_component::<Modules>(state_id),
+            ) {
+                if Self::convert_state(state) {
+                    let mut i = i.write();
+                    let m = m.read();
+
+                    if let Some(module) = i.selected().and_then(|s| m.get(&s.id)).cloned() {
+                        l.write().install(&module, &mut i);
+                    }
+                }
             }
 
             Ok(())
         });
-        self.add_keybind(Input::Keyboard(KeyCode::KeyS), move |state, _, world| {
-            if let Some(p) = world.read().em.read().get_component::<Player>(player) {
-                p.write().states.backward = Self::convert_state(state);
+        for (code, action) in [
+            (KeyCode::KeyE, StationAction::Undock),
+            (KeyCode::KeyF, StationAction::Service),
+            (KeyCode::KeyG, StationAction::Store),
+            (KeyCode::KeyT, StationAction::Retrieve),
+            (KeyCode::KeyK, StationAction::Buy),
+            (KeyCode::KeyL, StationAction::Sell),
+            (KeyCode::Period, StationAction::Next),
+            (KeyCode::Comma, StationAction::Previous),
+        ] {
+            self.add_keybind(Input::Keyboard(code), move |state, _, world| {
+                if let Some(d) = world.read().em.read().get_component::<Docking>(player) {
+                    if Self::convert_state(state) {
+                        d.write().request(action);
+                    }
+                }
+
+                Ok(())
+            });
+        }
+
+        self.add_keybind(Input::Keyboard(KeyCode::KeyJ), move |state, _, world| {
+            if let Some(m) = world.read().em.read().get_component::<Missions>(state_id) {
+                if Self::convert_state(state) {
+                    m.write().accept = true;
+                }
             }
 
             Ok(())
         });
-        self.add_keybind(Input::Keyboard(KeyCode::KeyA), move |state, _, world| {
-            if let Some(p) = world.read().em.read().get_component::<Player>(player) {
-                p.write().states.left = Self::convert_state(state);
+        self.add_keybind(Input::Keyboard(KeyCode::KeyM), move |state, _, world| {
+            if let Some(m) = world.read().em.read().get_component::<MapView>(state_id) {
+                if Self::convert_state(state) {
+                    m.write().toggle();
+                }
             }
 
             Ok(())
         });
-        self.add_keybind(Input::Keyboard(KeyCode::KeyD), move |state, _, world| {
-            if let Some(p) = world.read().em.read().get_component::<Player>(player) {
-                p.write().states.right = Self::convert_state(state);
+        self.add_keybind(Input::Keyboard(KeyCode::KeyN), move |state, _, world| {
+            let em = world.read().em.clone();
+            let em = em.read();
+
+            if let (Some(w), Some(t)) = (
+                em.get_component::<Waypoints>(state_id),
+                em.get_component::<Trans>(player),
+            ) {
+                if Self::convert_state(state) {
+                    w.write().add_numbered(t.read().position());
+                }
             }
 
             Ok(())
         });
+        self.add_keybind(Input::Keyboard(KeyCode::KeyX), move |state, _, world| {
+            let em = world.read().em.clone();
+            let em = em.read();
+
+            if let (Some(w), Some(t)) = (
+                em.get_component::<Waypoints>(state_id),
+                em.get_component::<Trans>(player),
+            ) {
+                if Self::convert_state(state) {
+                    w.write()
+                        .remove_near(t.read().position(), WAYPOINT_REMOVE_RADIUS);
+                }
+            }
+
+            Ok(())
+        });
+        self.add_keybind(Input::Keyboard(KeyCode::F11), move |state, context, _| {
+            if Self::convert_state(state) {
+                let mut settings = settings.write();
+
+                settings.toggle_fullscreen(&context.read().window);
+                settings.save(SETTINGS_FILE)?;
+            }
+
+            Ok(())
+        });
+        self.add_keybind(Input::Keyboard(KeyCode::F3), move |state, _, world| {
+            if let Some(c) = world
+                .read()
+                .em
+                .read()
+                .get_component::<CameraController>(camera)
+            {
+                if Self::convert_state(state) {
+                    let mut c = c.write();
+
+                    c.mode = c.mode.next();
+                }
+            }
+
+            Ok(())
+        });
+
+        for (code, dir) in [
+            (KeyCode::ArrowUp, Vector2::new(0.0, 1.0)),
+            (KeyCode::ArrowDown, Vector2::new(0.0, -1.0)),
+            (KeyCode::ArrowLeft, Vector2::new(-1.0, 0.0)),
+            (KeyCode::ArrowRight, Vector2::new(1.0, 0.0)),
+        ] {
+            self.add_keybind(Input::Keyboard(code), move |state, _, world| {
+                if let Some(c) = world
+                    .read()
+                    .em
+                    .read()
+                    .get_component::<CameraController>(camera)
+                {
+                    let mut c = c.write();
+
+                    if c.mode == CameraMode::FreeLook {
+                        let pressed = Self::convert_state(state);
+
+                        for i in 0..2 {
+                            if dir[i] != 0.0 {
+                                if pressed {
+                                    c.pan[i] = dir[i];
+                                } else if c.pan[i] == dir[i] {
+                                    c.pan[i] = 0.0;
+                                }
+                            }
+                        }
+                    }
+                }
+
+                Ok(())
+            });
+        }
     }
 }
 
 impl System for GameUiManager {
     fn init(&mut self, _: Arc<RwLock<Context>>, world: Arc<RwLock<World>>) -> anyhow::Result<()> {
-        let em = world.read().em.clone();
-        let em = em.read();
-
-        self.player = Tag("player".to_string()).find(&em);
+        self.player = self.tags.read().first("player");
+        self.camera = self.tags.read().first("camera");
+        self.state = self.tags.read().first("state");
 
         self.init_default_keybinds(world);
 
@@ -0,0 +1,246 @@
+use crate::{
+    chunk_manager::{TileMap, CHUNK_SIZE, MIN_CHUNK},
+    drop_manager::{ItemDrop, DROP_TAG},
+    events::{EventBus, GameEvent, Subscriber},
+    game_manager::Player,
+    state::State,
+    tag::TagIndex,
+    util,
+};
+use hex::{
+    anyhow,
+    components::Trans,
+    nalgebra::Vector2,
+    parking_lot::RwLock,
+    winit::event::{Event, WindowEvent},
+    world::{system_manager::System, EntityManager, World},
+    Context, Control, Id,
+};
+use rand::prelude::*;
+use std::{collections::HashMap, sync::Arc, time::Instant};
+
+pub const GRAVITY_TICK: f32 = 1.0 / 60.0;
+pub const MAX_GRAVITY_STEPS: u32 = 5;
+pub const GRAVITY_CONSTANT: f32 = 1.0;
+pub const PLANETOID_RARITY: u64 = 12;
+pub const PLANETOID_SALT: u64 = 0x504c_414e_4554_4f44;
+pub const PLANETOID_RADIUS: [f32; 2] = [2.5, 4.5];
+pub const PLANETOID_DENSITY: f32 = 2.0;
+pub const PLANETOID_INFLUENCE: f32 = 3.0;
+
+#[derive(Clone, Copy, Debug)]
+pub struct Body {
+    pub position: Vector2<f32>,
+    pub radius: f32,
+    pub mass: f32,
+    pub influence: f32,
+}
+
+impl Body {
+    pub fn generate(seed: u32, chunk @ (x, y): (u32, u32)) -> Option<Self> {
+        let seed = util::chunk_hash(chunk, util::seed_hash(seed, PLANETOID_SALT));
+
+        if x < MIN_CHUNK || y < MIN_CHUNK || seed % PLANETOID_RARITY != 0 {
+            return None;
+        }
+
+        let mut rng = StdRng::seed_from_u64(seed);
+        let radius = rng.gen_range(PLANETOID_RADIUS[0]..=PLANETOID_RADIUS[1]);
+        let margin = radius.ceil() + 1.0;
+        let mut offset = || rng.gen_range(margin..CHUNK_SIZE as f32 - margin).floor();
+        let position = Vector2::new(
+            (x * CHUNK_SIZE) as f32 + offset(),
+            (y * CHUNK_SIZE) as f32 + offset(),
+        );
+
+        Some(Self {
+            position,
+            radius,
+            mass: PLANETOID_DENSITY * radius * radius,
+            influence: (radius * PLANETOID_INFLUENCE).min(CHUNK_SIZE as f32),
+        })
+    }
+
+    pub fn accel(&self, position: Vector2<f32>) -> Option<Vector2<f32>> {
+        let offset = self.position - position;
+        let distance = offset.magnitude();
+
+        if distance >= self.influence || distance == 0.0 {
+            return None;
+        }
+
+        let r = distance.max(self.radius);
+
+        Some(offset / distance * GRAVITY_CONSTANT * self.mass / (r * r))
+    }
+
+    pub fn orbital_speed(&self, distance: f32) -> f32 {
+        (GRAVITY_CONSTANT * self.mass / distance.max(self.radius)).sqrt()
+    }
+}
+
+#[derive(Default)]
+pub struct GravityField {
+    pub bodies: HashMap<(u32, u32), Body>,
+}
+
+impl GravityField {
+    pub fn new() -> Arc<RwLock<Self>> {
+        Arc::new(RwLock::new(Self::default()))
+    }
+
+    pub fn nearby(&self, position: Vector2<f32>) -> impl Iterator<Item = &Body> + '_ {
+        let (cx, cy) = TileMap::tile_pos(position)
+            .map(|t| TileMap::tile_chunk(t).0)
+            .unwrap_or_default();
+
+        (-1..=1)
+            .flat_map(move |i| (-1..=1).map(move |j| (cx as i64 + i, cy as i64 + j)))
+            .filter_map(|(x, y)| {
+                let x = u32::try_from(x).ok()?;
+                let y = u32::try_from(y).ok()?;
+
+                self.bodies.get(&(x, y))
+            })
+    }
+
+    pub fn accel(&self, position: Vector2<f32>) -> Option<Vector2<f32>> {
+        self.nearby(position)
+            .filter_map(|b| b.accel(position))
+            .reduce(|a, b| a + b)
+    }
+
+    pub fn in_field(&self, position: Vector2<f32>) -> bool {
+        self.accel(position).is_some()
+    }
+}
+
+pub struct GravityManager {
+    pub player: Option<Id>,
+    pub field: Arc<RwLock<GravityField>>,
+    pub state: Arc<RwLock<State>>,
+    pub tags: Arc<RwLock<TagIndex>>,
+    pub events: Arc<RwLock<EventBus>>,
+    pub subscriber: Subscriber,
+    accumulator: f32,
+    last_frame: Instant,
+}
+
+impl GravityManager {
+    pub fn new(
+        field: Arc<RwLock<GravityField>>,
+        state: Arc<RwLock<State>>,
+        tags: Arc<RwLock<TagIndex>>,
+        events: Arc<RwLock<EventBus>>,
+    ) -> Self {
+        let subscriber = events.write().subscribe();
+
+        Self {
+            player: None,
+            field,
+            state,
+            tags,
+            events,
+            subscriber,
+            accumulator: 0.0,
+            last_frame: Instant::now(),
+        }
+    }
+
+    fn step(&self, em: &EntityManager, dt: f32) {
+        let field = self.field.read();
+
+        if field.bodies.is_empty() {
+            return;
+        }
+
+        if let Some(player) = self.player {
+            if let (Some(p), Some(t)) = (
+                em.get_component::<Player>(player),
+                em.get_component::<Trans>(player),
+            ) {
+                if let Some(a) = field.accel(t.read().position()) {
+                    p.write().velocity += a * dt;
+                }
+            }
+        }
+
+        for (e, drop) in self.tags.read().query::<ItemDrop>(em, DROP_TAG) {
+            let Some(t) = em.get_component::<Trans>(e) else {
+                continue;
+            };
+
+            if let Some(a) = field.accel(t.read().position()) {
+                let mut drop = drop.write();
+
+                drop.velocity = (Vector2::from(drop.velocity) + a * dt).into();
+            }
+        }
+    }
+}
+
+impl System for GravityManager {
+    fn init(&mut self, _: Arc<RwLock<Context>>, _: Arc<RwLock<World>>) -> anyhow::Result<()> {
+        self.player = self.tags.read().first("player");
+
+        Ok(())
+    }
+
+    fn update(
+        &mut self,
+        control: Arc<RwLock<Control>>,
+        context: Arc<RwLock<Context>>,
+        world: Arc<RwLock<World>>,
+    ) -> anyhow::Result<()> {
+        let event = control.read().event.clone();
+
+        if let Event::WindowEvent {
+            event: WindowEvent::RedrawRequested,
+            window_id,
+        } = event
+        {
+            if window_id != context.read().window.id() {
+                return Ok(());
+            }
+
+            let now = Instant::now();
+            let delta = now.duration_since(self.last_frame).as_secs_f32();
+
+            self.last_frame = now;
+
+            let events = self.events.write().poll(self.subscriber);
+
+            {
+                let seed = self.state.read().seed;
+                let mut field = self.field.write();
+
+                for event in events {
+                    match event {
+                        GameEvent::ChunkLoaded { chunk } => {
+                            if let Some(body) = Body::generate(seed, chunk) {
+                                field.bodies.insert(chunk, body);
+                            }
+                        }
+                        GameEvent::ChunkUnloaded { chunk } => {
+                            field.bodies.remove(&chunk);
+                        }
+                        _ => {}
+                    }
+                }
+            }
+
+            let em = world.read().em.clone();
+            let em = em.read();
+
+            self.accumulator =
+                (self.accumulator + delta).min(GRAVITY_TICK * MAX_GRAVITY_STEPS as f32);
+
+            while self.accumulator >= GRAVITY_TICK {
+                self.accumulator -= GRAVITY_TICK;
+                self.step(&em, GRAVITY_TICK);
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,581 @@
+use crate::{
+    chunk_manager::{TileMap, CHUNK_SIZE, ICE, MIN_CHUNK, RADIOACTIVE},
+    events::{EventBus, GameEvent, Subscriber},
+    health::{DamageSource, Health},
+    health_manager::HOSTILE_TAGS,
+    hud_manager::Notifications,
+    station_manager::Docking,
+    tag::TagIndex,
+    util,
+};
+use hex::{
+    anyhow,
+    assets::Shape,
+    components::Trans,
+    nalgebra::{Vector2, Vector4},
+    parking_lot::RwLock,
+    winit::event::{Event, WindowEvent},
+    world::{system_manager::System, EntityManager, World},
+    Context, Control, Id,
+};
+use hex_instance::components::Instance;
+use rand::prelude::*;
+use serde_derive::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs,
+    path::Path,
+    sync::Arc,
+    time::Instant,
+};
+
+pub const HAZARDS_FILE: &str = "data/hazards.json";
+pub const HAZARD_SALT: u64 = 0x4841_5a41_5244_5a53;
+pub const HAZARD_MARGIN: f32 = 2.0;
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum HazardKind {
+    Radiation,
+    GravityWell,
+    IceField,
+}
+
+impl HazardKind {
+    pub fn color(self) -> [u8; 3] {
+        match self {
+            Self::Radiation => [120, 255, 80],
+            Self::GravityWell => [170, 90, 255],
+            Self::IceField => [150, 220, 255],
+        }
+    }
+
+    pub fn tile(self) -> Option<&'static str> {
+        match self {
+            Self::Radiation => Some(RADIOACTIVE),
+            Self::GravityWell => None,
+            Self::IceField => Some(ICE),
+        }
+    }
+
+    fn salt(self) -> u64 {
+        HAZARD_SALT.wrapping_add(self as u64)
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ZoneConfig {
+    pub rarity: u64,
+    pub count: [u32; 2],
+    pub radius: [f32; 2],
+    pub strength: [f32; 2],
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct FlareConfig {
+    pub interval: [f32; 2],
+    pub warning: f32,
+    pub duration: f32,
+    pub damage: f32,
+}
+
+impl Default for FlareConfig {
+    fn default() -> Self {
+        Self {
+            interval: [180.0, 420.0],
+            warning: 10.0,
+            duration: 15.0,
+            damage: 3.0,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Default, Debug)]
+#[serde(default)]
+pub struct HazardConfig {
+    pub zones: BTreeMap<HazardKind, ZoneConfig>,
+    pub flare: FlareConfig,
+}
+
+impl HazardConfig {
+    pub fn load<P>(path: P) -> anyhow::Result<Self>
+    where
+        P: AsRef<Path>,
+    {
+        let config: Self = if Path::exists(path.as_ref()) {
+            serde_json::from_str(fs::read_to_string(path)?.as_str())?
+        } else {
+            Self::default()
+        };
+
+        config.validate()?;
+
+        Ok(config)
+    }
+
+    pub fn validate(&self) -> anyhow::Result<()> {
+        for (kind, zone) in &self.zones {
+            if zone.count[0] > zone.count[1]
+                || !Self::range_ok(zone.radius)
+                || !Self::range_ok(zone.strength)
+            {
+                anyhow::bail!("invalid {kind:?} zone config");
+            }
+        }
+
+        let flare = &self.flare;
+
+        if !Self::range_ok(flare.interval)
+            || !(0.0..f32::INFINITY).contains(&flare.warning)
+            || !(0.0..f32::INFINITY).contains(&flare.duration)
+            || !(0.0..f32::INFINITY).contains(&flare.damage)
+        {
+            anyhow::bail!("invalid flare config");
+        }
+
+        Ok(())
+    }
+
+    fn range_ok([min, max]: [f32; 2]) -> bool {
+        min.is_finite() && max.is_finite() && min > 0.0 && min <= max
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Hazard {
+    pub kind: HazardKind,
+    pub position: [f32; 2],
+    pub radius: f32,
+    pub strength: f32,
+}
+
+impl Hazard {
+    pub fn generate(config: &HazardConfig, seed: u32, chunk @ (x, y): (u32, u32)) -> Vec<Self> {
+        let mut hazards = Vec::new();
+
+        if x < MIN_CHUNK || y < MIN_CHUNK {
+            return hazards;
+        }
+
+        for (kind, zone) in &config.zones {
+            let seed = util::chunk_hash(chunk, util::seed_hash(seed, kind.salt()));
+
+            if zone.rarity == 0 || seed % zone.rarity != 0 {
+                continue;
+            }
+
+            let mut rng = StdRng::seed_from_u64(seed);
+            let count = rng.gen_range(zone.count[0]..=zone.count[1]);
+
+            for _ in 0..count {
+                let span = CHUNK_SIZE as f32 - HAZARD_MARGIN;
+                let position = [
+                    (x * CHUNK_SIZE) as f32 + rng.gen_range(HAZARD_MARGIN..span),
+                    (y * CHUNK_SIZE) as f32 + rng.gen_range(HAZARD_MARGIN..span),
+                ];
+
+                hazards.push(Self {
+                    kind: *kind,
+                    position,
+                    radius: rng.gen_range(zone.radius[0]..=zone.radius[1]),
+                    strength: rng.gen_range(zone.strength[0]..=zone.strength[1]),
+                });
+            }
+        }
+
+        hazards
+    }
+
+    pub fn position(&self) -> Vector2<f32> {
+        self.position.into()
+    }
+
+    pub fn influence(&self, position: Vector2<f32>) -> Option<f32> {
+        let distance = (self.position() - position).magnitude();
+
+        (distance < self.radius).then(|| 1.0 - distance / self.radius)
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Environment {
+    pub radiation: f32,
+    pub pull: Vector2<f32>,
+    pub drag: f32,
+    pub flare: bool,
+}
+
+impl Default for Environment {
+    fn default() -> Self {
+        Self {
+            radiation: 0.0,
+            pull: Vector2::default(),
+            drag: 1.0,
+            flare: false,
+        }
+    }
+}
+
+impl Environment {
+    pub fn new() -> Arc<RwLock<Self>> {
+        Arc::new(RwLock::new(Self::default()))
+    }
+
+    pub fn sample<'a, I>(hazards: I, position: Vector2<f32>, flare: bool) -> Self
+    where
+        I: IntoIterator<Item = &'a Hazard>,
+    {
+        let mut env = Self {
+            flare,
+            ..Default::default()
+        };
+
+        for hazard in hazards {
+            let Some(f) = hazard.influence(position) else {
+                continue;
+            };
+
+            match hazard.kind {
+                HazardKind::Radiation => env.radiation += hazard.strength * f,
+                HazardKind::GravityWell => {
+                    let offset = hazard.position() - position;
+
+                    if offset.magnitude() > 0.0 {
+                        env.pull += offset.normalize() * hazard.strength * f;
+                    }
+                }
+                HazardKind::IceField => {
+                    env.drag = env.drag.min(1.0 + (hazard.strength - 1.0) * f);
+                }
+            }
+        }
+
+        env
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Flare {
+    Calm(f32),
+    Warning(f32),
+    Active(f32),
+}
+
+impl Flare {
+    pub fn is_active(self) -> bool {
+        matches!(self, Self::Active(_))
+    }
+}
+
+pub struct HazardManager {
+    pub tags: Arc<RwLock<TagIndex>>,
+    pub tile_map: Arc<RwLock<TileMap>>,
+    pub events: Arc<RwLock<EventBus>>,
+    pub notifications: Arc<RwLock<Notifications>>,
+    pub subscriber: Subscriber,
+    pub config: HazardConfig,
+    pub zones: HashMap<(u32, u32), Vec<(Id, Hazard)>>,
+    pub flare: Flare,
+    instances: BTreeMap<HazardKind, Arc<RwLock<Instance>>>,
+    rng: StdRng,
+    last_frame: Instant,
+}
+
+impl HazardManager {
+    pub fn new(
+        context: &Context,
+        config: HazardConfig,
+        tile_map: Arc<RwLock<TileMap>>,
+        tags: Arc<RwLock<TagIndex>>,
+        events: Arc<RwLock<EventBus>>,
+        notifications: Arc<RwLock<Notifications>>,
+    ) -> anyhow::Result<Self> {
+        let shape = Arc::new(Shape::rect(context, Vector2::new(1.0, 1.0))?);
+        let mut instances = BTreeMap::new();
+
+        for kind in [
+            HazardKind::Radiation,
+            HazardKind::GravityWell,
+            HazardKind::IceField,
+        ] {
+            instances.insert(
+                kind,
+                Instance::new(
+                    context,
+                    shape.clone(),
+                    Arc::new(Self::zone_texture(context, kind)),
+                    Vector4::new(1.0, 1.0, 1.0, 1.0),
+                    1,
+                )?,
+            );
+        }
+
+        let mut rng = StdRng::from_entropy();
+        let flare = Flare::Calm(rng.gen_range(config.flare.interval[0]..=config.flare.interval[1]));
+        let subscriber = events.write().subscribe();
+
+        Ok(Self {
+            tags,
+            tile_map,
+            events,
+            notifications,
+            subscriber,
+            config,
+            zones: HashMap::new(),
+            flare,
+            instances,
+            rng,
+            last_frame: Instant::now(),
+        })
+    }
+
+    fn zone_texture(context: &Context, kind: HazardKind) -> hex::assets::Texture {
+        let size = 32u32;
+        let [r, g, b] = kind.color();
+        let mut img = Vec::with_capacity((size * size * 4) as usize);
+
+        for y in 0..size {
+            for x in 0..size {
+                let d = Vector2::new(x as f32 + 0.5, y as f32 + 0.5)
+                    - Vector2::new(size as f32 / 2.0, size as f32 / 2.0);
+                let r2 = d.magnitude() / (size as f32 / 2.0);
+                let alpha = if r2 < 1.0 {
+                    (40.0 + 60.0 * r2 * r2) as u8
+                } else {
+                    0
+                };
+
+                img.extend_from_slice(&[r, g, b, alpha]);
+            }
+        }
+
+        util::pixel_texture(context, &img, size, size)
+    }
+
+    fn load_zones(&mut self, em: &mut EntityManager, chunk: (u32, u32)) {
+        if self.zones.contains_key(&chunk) {
+            return;
+        }
+
+        let hazards = self
+            .tile_map
+            .read()
+            .chunks
+            .get(&chunk)
+            .map(|data| data.hazards.clone())
+            .unwrap_or_default();
+        let zones = hazards
+            .into_iter()
+            .map(|hazard| {
+                let e = em.add(true);
+
+                em.add_component(e, self.instances[&hazard.kind].clone());
+                em.add_component(
+                    e,
+                    Trans::new(
+                        hazard.position(),
+                        0.0,
+                        Vector2::new(hazard.radius, hazard.radius) * 2.0,
+                    ),
+                );
+                em.add_component(e, Arc::new(RwLock::new(hazard.clone())));
+
+                (e, hazard)
+            })
+            .collect();
+
+        self.zones.insert(chunk, zones);
+    }
+
+    fn unload_zones(&mut self, em: &mut EntityManager, chunk: (u32, u32)) {
+        for (e, _) in self.zones.remove(&chunk).unwrap_or_default() {
+            em.rm(e);
+        }
+    }
+
+    fn update_flare(&mut self, delta: f32) {
+        let config = &self.config.flare;
+        let next = match self.flare {
+            Flare::Calm(t) if t <= delta => {
+                self.notifications
+                    .write()
+                    .push("SOLAR FLARE INBOUND - DOCK FOR SHELTER");
+
+                Flare::Warning(config.warning)
+            }
+            Flare::Warning(t) if t <= delta => {
+                self.notifications.write().push("SOLAR FLARE");
+
+                Flare::Active(config.duration)
+            }
+            Flare::Active(t) if t <= delta => {
+                self.notifications.write().push("SOLAR FLARE PASSED");
+
+                Flare::Calm(self.rng.gen_range(config.interval[0]..=config.interval[1]))
+            }
+            Flare::Calm(t) => Flare::Calm(t - delta),
+            Flare::Warning(t) => Flare::Warning(t - delta),
+            Flare::Active(t) => Flare::Active(t - delta),
+        };
+
+        self.flare = next;
+    }
+
+    fn apply(&self, em: &EntityManager, delta: f32) {
+        let tags = self.tags.read();
+        let flare = self.flare.is_active();
+
+        for tag in std::iter::once("player").chain(HOSTILE_TAGS.iter().copied()) {
+            for (e, env) in tags.query::<Environment>(em, tag) {
+                let Some(position) = em.get_component::<Trans>(e).map(|t| t.read().position())
+                else {
+                    continue;
+                };
+                let sheltered = em
+                    .get_component::<Docking>(e)
+                    .is_some_and(|d| d.read().is_docked());
+                let sample = Environment::sample(
+                    self.zones.values().flatten().map(|(_, h)| h),
+                    position,
+                    flare && !sheltered,
+                );
+
+                if let Some(health) = em.get_component::<Health>(e) {
+                    let mut health = health.write();
+                    let damage = sample.radiation
+                        + if sample.flare {
+                            self.config.flare.damage
+                        } else {
+                            0.0
+                        };
+
+                    if damage > 0.0 {
+                        health.damage(damage * delta, DamageSource::Hazard);
+                    }
+                }
+
+                *env.write() = sample;
+            }
+        }
+    }
+}
+
+impl System for HazardManager {
+    fn update(
+        &mut self,
+        control: Arc<RwLock<Control>>,
+        context: Arc<RwLock<Context>>,
+        world: Arc<RwLock<World>>,
+    ) -> anyhow::Result<()> {
+        let event = control.read().event.clone();
+
+        if let Event::WindowEvent {
+            event: WindowEvent::RedrawRequested,
+            window_id,
+        } = event
+        {
+            if window_id != context.read().window.id() {
+                return Ok(());
+            }
+
+            let now = Instant::now();
+            let delta = now.duration_since(self.last_frame).as_secs_f32();
+
+            self.last_frame = now;
+
+            let em = world.read().em.clone();
+            let mut em = em.write();
+
+            let events = self.events.write().poll(self.subscriber);
+
+            for event in events {
+                match event {
+                    GameEvent::ChunkLoaded { chunk } => self.load_zones(&mut em, chunk),
+                    GameEvent::ChunkUnloaded { chunk } => self.unload_zones(&mut em, chunk),
+                    _ => {}
+                }
+            }
+
+            self.update_flare(delta);
+            self.apply(&em, delta);
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(radius: [f32; 2], strength: [f32; 2], interval: [f32; 2]) -> HazardConfig {
+        HazardConfig {
+            zones: [(
+                HazardKind::Radiation,
+                ZoneConfig {
+                    rarity: 1,
+                    count: [1, 2],
+                    radius,
+                    strength,
+                },
+            )]
+            .into_iter()
+            .collect(),
+            flare: FlareConfig {
+                interval,
+                ..Default::default()
+            },
+        }
+    }
+
+    #[test]
+    fn default_config_is_valid() {
+        assert!(HazardConfig::default().validate().is_ok());
+    }
+
+    #[test]
+    fn accepts_good_ranges() {
+        assert!(config([2.0, 4.0], [1.0, 1.0], [10.0, 20.0])
+            .validate()
+            .is_ok());
+    }
+
+    #[test]
+    fn rejects_bad_ranges() {
+        assert!(config([4.0, 2.0], [1.0, 2.0], [10.0, 20.0])
+            .validate()
+            .is_err());
+        assert!(config([0.0, 2.0], [1.0, 2.0], [10.0, 20.0])
+            .validate()
+            .is_err());
+        assert!(config([2.0, 4.0], [f32::NAN, 2.0], [10.0, 20.0])
+            .validate()
+            .is_err());
+        assert!(config([2.0, 4.0], [1.0, 2.0], [20.0, 10.0])
+            .validate()
+            .is_err());
+    }
+
+    #[test]
+    fn rejects_bad_flare_damage() {
+        let mut config = config([2.0, 4.0], [1.0, 2.0], [10.0, 20.0]);
+
+        config.flare.damage = -1.0;
+        assert!(config.validate().is_err());
+
+        config.flare.damage = f32::NAN;
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn valid_config_generates() {
+        let config = config([2.0, 2.0], [1.0, 1.0], [10.0, 10.0]);
+
+        for x in MIN_CHUNK..MIN_CHUNK + 4 {
+            for hazard in Hazard::generate(&config, 7, (x, MIN_CHUNK)) {
+                assert_eq!(hazard.radius, 2.0);
+                assert_eq!(hazard.strength, 1.0);
+            }
+        }
+    }
+}
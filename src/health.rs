@@ -0,0 +1,106 @@
+use hex::parking_lot::RwLock;
+use std::sync::Arc;
+
+pub const PLAYER_HEALTH: f32 = 100.0;
+pub const PLAYER_INVULNERABILITY: f32 = 0.75;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum DamageSource {
+    Impact,
+    Projectile,
+    Hazard,
+    Suffocation,
+}
+
+impl DamageSource {
+    pub fn is_continuous(self) -> bool {
+        matches!(self, Self::Hazard | Self::Suffocation)
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Damage {
+    pub amount: f32,
+    pub source: DamageSource,
+}
+
+pub struct Health {
+    pub current: f32,
+    pub max: f32,
+    pub invulnerability: f32,
+    pub invulnerable: f32,
+    pub dead: bool,
+    pub pending: Vec<Damage>,
+}
+
+impl Health {
+    pub fn new(max: f32) -> Arc<RwLock<Self>> {
+        Self::with_invulnerability(max, 0.0)
+    }
+
+    pub fn with_invulnerability(max: f32, invulnerability: f32) -> Arc<RwLock<Self>> {
+        Arc::new(RwLock::new(Self {
+            current: max,
+            max,
+            invulnerability,
+            invulnerable: 0.0,
+            dead: false,
+            pending: Vec::new(),
+        }))
+    }
+
+    pub fn fraction(&self) -> f32 {
+        if self.max > 0.0 {
+            (self.current / self.max).clamp(0.0, 1.0)
+        } else {
+            0.0
+        }
+    }
+
+    pub fn damage(&mut self, amount: f32, source: DamageSource) {
+        self.pending.push(Damage { amount, source });
+    }
+
+    pub fn heal(&mut self, amount: f32) {
+        if !self.dead {
+            self.current = (self.current + amount).min(self.max);
+        }
+    }
+
+    pub fn is_invulnerable(&self) -> bool {
+        self.invulnerable > 0.0
+    }
+
+    pub fn apply(&mut self, delta: f32) -> Vec<Damage> {
+        self.invulnerable = (self.invulnerable - delta).max(0.0);
+
+        let mut applied = Vec::new();
+
+        for damage in std::mem::take(&mut self.pending) {
+            let continuous = damage.source.is_continuous();
+
+            if self.dead || (self.is_invulnerable() && !continuous) || damage.amount <= 0.0 {
+                continue;
+            }
+
+            self.current = (self.current - damage.amount).max(0.0);
+
+            if !continuous {
+                self.invulnerable = self.invulnerability;
+            }
+
+            self.dead = self.current <= 0.0;
+
+            applied.push(damage);
+        }
+
+        applied
+    }
+
+    pub fn revive(&mut self, invulnerable: f32) {
+        self.current = self.max;
+        self.dead = false;
+        self.invulnerable = invulnerable;
+        self.pending.clear();
+    }
+}
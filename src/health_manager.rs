@@ -0,0 +1,296 @@
+use crate::{
+    camera_manager::CameraController,
+    events::{EventBus, GameEvent},
+    game_manager::Player,
+    health::{DamageSource, Health},
+    hud_manager::Notifications,
+    inventory::Inventory,
+    ship_manager::ShipSystems,
+    station_manager::Docking,
+    tag::TagIndex,
+    waypoint_manager::Waypoints,
+};
+use hex::{
+    anyhow,
+    components::Trans,
+    nalgebra::Vector2,
+    parking_lot::RwLock,
+    winit::event::{Event, WindowEvent},
+    world::{system_manager::System, EntityManager, World},
+    Context, Control, Id,
+};
+use std::{sync::Arc, time::Instant};
+
+pub const IMPACT_THRESHOLD: f32 = 2.0;
+pub const IMPACT_DAMAGE: f32 = 4.0;
+pub const IMPACT_TRAUMA: f32 = 0.15;
+pub const DEATH_DROP_FRACTION: f32 = 0.5;
+pub const RESPAWN_INVULNERABILITY: f32 = 3.0;
+pub const HOSTILE_TAGS: &[&str] = &["miner"];
+
+pub struct HealthManager {
+    pub player: Option<Id>,
+    pub camera: Option<Id>,
+    pub tags: Arc<RwLock<TagIndex>>,
+    pub waypoints: Arc<RwLock<Waypoints>>,
+    pub notifications: Arc<RwLock<Notifications>>,
+    pub events: Arc<RwLock<EventBus>>,
+    pub last_position: Option<Vector2<f32>>,
+    pub last_frame: Instant,
+}
+
+impl HealthManager {
+    pub fn new(
+        waypoints: Arc<RwLock<Waypoints>>,
+        notifications: Arc<RwLock<Notifications>>,
+        events: Arc<RwLock<EventBus>>,
+        tags: Arc<RwLock<TagIndex>>,
+    ) -> Self {
+        Self {
+            player: None,
+            tags,
+            camera: None,
+            waypoints,
+            notifications,
+            events,
+            last_position: None,
+            last_frame: Instant::now(),
+        }
+    }
+
+    fn impact(&self, em: &EntityManager, player: Id, position: Vector2<f32>, delta: f32) {
+        let (Some(last), Some(p), Some(health)) = (
+            self.last_position,
+            em.get_component::<Player>(player),
+            em.get_component::<Health>(player),
+        ) else {
+            return;
+        };
+
+        if delta <= 0.0 {
+            return;
+        }
+
+        let mut p = p.write();
+        let actual = (position - last) / delta;
+        let lost = (p.velocity - actual).magnitude();
+
+        if lost > IMPACT_THRESHOLD {
+            health.write().damage(
+                (lost - IMPACT_THRESHOLD) * IMPACT_DAMAGE,
+                DamageSource::Impact,
+            );
+
+            if let Some(c) = self
+                .camera
+                .and_then(|c| em.get_component::<CameraController>(c))
+            {
+                c.write()
+                    .add_trauma((lost - IMPACT_THRESHOLD) * IMPACT_TRAUMA);
+            }
+
+            p.velocity = actual;
+        }
+    }
+
+    fn die(&mut self, em: &EntityManager, player: Id, position: Vector2<f32>) {
+        let spawn = {
+            let mut waypoints = self.waypoints.write();
+
+            waypoints.set_last_death(position);
+            waypoints.spawn.position()
+        };
+        let items = em
+            .get_component::<Inventory>(player)
+            .map(|i| i.write().drop_fraction(DEATH_DROP_FRACTION))
+            .unwrap_or_default();
+        let dropped: u32 = items.iter().map(|s| s.count).sum();
+
+        {
+            let mut events = self.events.write();
+
+            events.publish(GameEvent::PlayerDied {
+                entity: player,
+                position,
+            });
+            events.publish(GameEvent::ItemsDropped { position, items });
+        }
+
+        {
+            let mut notifications = self.notifications.write();
+
+            notifications.push("HULL DESTROYED");
+
+            if dropped > 0 {
+                notifications.push(format!("DROPPED {dropped} ITEMS"));
+            }
+        }
+
+        if let Some(t) = em.get_component::<Trans>(player) {
+            t.write().set_position(spawn);
+        }
+
+        if let Some(p) = em.get_component::<Player>(player) {
+            p.write().velocity = Vector2::default();
+        }
+
+        if let Some(health) = em.get_component::<Health>(player) {
+            health.write().revive(RESPAWN_INVULNERABILITY);
+        }
+
+        if let Some(systems) = em.get_component::<ShipSystems>(player) {
+            systems.write().refill();
+        }
+
+        if let Some(c) = self
+            .camera
+            .and_then(|c| em.get_component::<CameraController>(c))
+        {
+            c.write().snap(spawn);
+        }
+
+        self.last_position = Some(spawn);
+    }
+
+    fn hostiles(&self, em: &EntityManager, delta: f32) -> Vec<(Id, String)> {
+        let tags = self.tags.read();
+        let mut events = self.events.write();
+        let mut dead = Vec::new();
+
+        for tag in HOSTILE_TAGS {
+            for (e, health) in tags.query::<Health>(em, tag) {
+                let mut health = health.write();
+
+                for damage in health.apply(delta) {
+                    events.publish(GameEvent::EntityDamaged {
+                        entity: e,
+                        amount: damage.amount,
+                        source: damage.source,
+                    });
+                }
+
+                if health.dead {
+                    dead.push((e, tag.to_string()));
+                }
+            }
+        }
+
+        dead
+    }
+
+    fn destroy(&self, em: &mut EntityManager, e: Id, kind: String) {
+        let position = em
+            .get_component::<Trans>(e)
+            .map(|t| t.read().position())
+            .unwrap_or_default();
+        let items = em
+            .get_component::<Inventory>(e)
+            .map(|i| i.write().drop_fraction(1.0))
+            .unwrap_or_default();
+
+        {
+            let mut events = self.events.write();
+
+            events.publish(GameEvent::EntityDestroyed {
+                entity: e,
+                kind: Some(kind),
+                position,
+            });
+            events.publish(GameEvent::ItemsDropped { position, items });
+        }
+
+        self.tags.write().rm(em, e);
+    }
+}
+
+impl System for HealthManager {
+    fn init(&mut self, _: Arc<RwLock<Context>>, _: Arc<RwLock<World>>) -> anyhow::Result<()> {
+        self.player = self.tags.read().first("player");
+        self.camera = self.tags.read().first("camera");
+
+        Ok(())
+    }
+
+    fn update(
+        &mut self,
+        control: Arc<RwLock<Control>>,
+        context: Arc<RwLock<Context>>,
+        world: Arc<RwLock<World>>,
+    ) -> anyhow::Result<()> {
+        let event = control.read().event.clone();
+
+        if let Event::WindowEvent {
+            event: WindowEvent::RedrawRequested,
+            window_id,
+        } = event
+        {
+            if window_id != context.read().window.id() {
+                return Ok(());
+            }
+
+            let now = Instant::now();
+            let delta = now.duration_since(self.last_frame).as_secs_f32();
+
+            self.last_frame = now;
+
+            let Some(player) = self.player else {
+                return Ok(());
+            };
+            let em = world.read().em.clone();
+            let em = em.read();
+            let Some(position) = em
+                .get_component::<Trans>(player)
+                .map(|t| t.read().position())
+            else {
+                return Ok(());
+            };
+
+            let docked = em
+                .get_component::<Docking>(player)
+                .is_some_and(|d| d.read().is_docked());
+
+            if !docked {
+                self.impact(&em, player, position, delta);
+            }
+
+            self.last_position = Some(position);
+
+            let dead = em
+                .get_component::<Health>(player)
+                .map(|h| {
+                    let mut h = h.write();
+                    let mut events = self.events.write();
+
+                    for damage in h.apply(delta) {
+                        events.publish(GameEvent::EntityDamaged {
+                            entity: player,
+                            amount: damage.amount,
+                            source: damage.source,
+                        });
+                    }
+
+                    h.dead
+                })
+                .unwrap_or(false);
+
+            if dead {
+                self.die(&em, player, position);
+            }
+
+            let hostiles = self.hostiles(&em, delta);
+
+            drop(em);
+
+            if !hostiles.is_empty() {
+                let em = world.read().em.clone();
+                let mut em = em.write();
+
+                for (e, kind) in hostiles {
+                    self.destroy(&mut em, e, kind);
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,508 @@
+use crate::{
+    chunk_manager::{ChunkManager, TileMap},
+    crafting_manager::{Crafter, Recipes},
+    exploration::Exploration,
+    font::{Font, Text, GLYPH_HEIGHT, LINE_ADVANCE},
+    game_manager::Player,
+    health::Health,
+    inventory::Inventory,
+    ship_manager::ShipSystems,
+    tag::TagIndex,
+    util,
+};
+use hex::{
+    anyhow,
+    assets::{Shape, Texture},
+    components::{Camera, Trans},
+    nalgebra::{Matrix2, Vector2, Vector4},
+    parking_lot::RwLock,
+    winit::{
+        dpi::{PhysicalPosition, PhysicalSize},
+        event::{Event, WindowEvent},
+    },
+    world::{system_manager::System, EntityManager, World},
+    Context, Control, Id,
+};
+use hex_instance::components::Instance;
+use std::{sync::Arc, time::Instant};
+
+pub const HUD_PIXEL_MUL: f32 = 1.0 / 160.0;
+pub const HUD_MARGIN: f32 = 4.0;
+pub const BAR_WIDTH: f32 = 40.0;
+pub const BAR_LABEL_WIDTH: f32 = 20.0;
+pub const NOTIFICATION_TIME: f32 = 4.0;
+pub const MAX_NOTIFICATIONS: usize = 5;
+
+pub type BarValue = Arc<dyn Fn(&EntityManager, Id) -> Option<f32> + Send + Sync>;
+
+#[derive(Default)]
+pub struct Notifications {
+    pub messages: Vec<(String, f32)>,
+}
+
+impl Notifications {
+    pub fn new() -> Arc<RwLock<Self>> {
+        Arc::new(RwLock::new(Self::default()))
+    }
+
+    pub fn push<S>(&mut self, message: S)
+    where
+        S: Into<String>,
+    {
+        self.messages.push((message.into(), NOTIFICATION_TIME));
+
+        if self.messages.len() > MAX_NOTIFICATIONS {
+            self.messages.remove(0);
+        }
+    }
+
+    pub fn tick(&mut self, delta: f32) {
+        for (_, time) in self.messages.iter_mut() {
+            *time -= delta;
+        }
+
+        self.messages.retain(|(_, time)| *time > 0.0);
+    }
+}
+
+pub struct Bar {
+    pub value: BarValue,
+    pub label: Text,
+    pub label_value: String,
+    pub background: Option<Id>,
+    pub fill: Option<Id>,
+    pub fill_instance: Arc<RwLock<Instance>>,
+}
+
+pub struct Screen {
+    pub top_left: Vector2<f32>,
+    pub right: Vector2<f32>,
+    pub down: Vector2<f32>,
+    pub size: Vector2<f32>,
+    pub rotation: f32,
+    pub pixel: f32,
+    pub window: PhysicalSize<u32>,
+    pub camera: Arc<RwLock<Camera>>,
+    pub camera_transform: Arc<RwLock<Trans>>,
+}
+
+impl Screen {
+    pub fn new(context: &Context, em: &EntityManager, camera: Id) -> Option<Self> {
+        let camera_transform = em.get_component::<Trans>(camera)?;
+        let camera = em.get_component::<Camera>(camera)?;
+        let window = context.window.inner_size();
+        let (top_left, bottom_right, rotation, pixel) = {
+            let camera_transform = camera_transform.read();
+            let camera = camera.read();
+            let top_left = util::screen_to_world(
+                &camera,
+                &camera_transform,
+                window,
+                PhysicalPosition::new(0.0, 0.0),
+            )?;
+            let bottom_right = util::screen_to_world(
+                &camera,
+                &camera_transform,
+                window,
+                PhysicalPosition::new(window.width as f64, window.height as f64),
+            )?;
+
+            (
+                top_left,
+                bottom_right,
+                camera_transform.rotation(),
+                camera.dimensions().y * HUD_PIXEL_MUL,
+            )
+        };
+        let rot = Matrix2::new_rotation(rotation);
+        let right = rot * Vector2::new(1.0, 0.0);
+        let down = rot * Vector2::new(0.0, -1.0);
+        let diagonal = bottom_right - top_left;
+
+        Some(Self {
+            top_left,
+            right,
+            down,
+            size: Vector2::new(diagonal.dot(&right), diagonal.dot(&down)) / pixel,
+            rotation,
+            pixel,
+            window,
+            camera,
+            camera_transform,
+        })
+    }
+
+    pub fn at(&self, x: f32, y: f32) -> Vector2<f32> {
+        self.top_left + (self.right * x + self.down * y) * self.pixel
+    }
+
+    pub fn to_screen(&self, world: Vector2<f32>) -> Vector2<f32> {
+        let physical = util::world_to_screen(
+            &self.camera.read(),
+            &self.camera_transform.read(),
+            self.window,
+            world,
+        );
+
+        Vector2::new(
+            physical.x as f32 / self.window.width as f32 * self.size.x,
+            physical.y as f32 / self.window.height as f32 * self.size.y,
+        )
+    }
+
+    pub fn place(&self, em: &EntityManager, e: Id, pos: Vector2<f32>, size: Vector2<f32>) {
+        if let Some(t) = em.get_component::<Trans>(e) {
+            let mut t = t.write();
+
+            t.set_position(self.at(pos.x + size.x / 2.0, pos.y + size.y / 2.0));
+            t.set_rotation(self.rotation);
+            t.set_scale(size * self.pixel);
+        }
+    }
+
+    pub fn place_text(&self, text: &Text, em: &EntityManager, pos: Vector2<f32>) {
+        text.layout(em, self.at(pos.x, pos.y), self.pixel, self.rotation);
+    }
+}
+
+pub struct HudManager {
+    pub player: Option<Id>,
+    pub camera: Option<Id>,
+    pub tags: Arc<RwLock<TagIndex>>,
+    pub notifications: Arc<RwLock<Notifications>>,
+    pub exploration: Arc<RwLock<Exploration>>,
+    pub recipes: Arc<RwLock<Recipes>>,
+    pub font: Arc<Font>,
+    pub shape: Arc<Shape>,
+    pub white: Arc<Texture>,
+    pub background_instance: Arc<RwLock<Instance>>,
+    pub stats: Text,
+    pub crafting: Text,
+    pub hotbar: Text,
+    pub messages: Text,
+    pub bars: Vec<Bar>,
+    pub last_frame: Instant,
+}
+
+impl HudManager {
+    pub fn new(
+        context: &Context,
+        notifications: Arc<RwLock<Notifications>>,
+        exploration: Arc<RwLock<Exploration>>,
+        recipes: Arc<RwLock<Recipes>>,
+        tags: Arc<RwLock<TagIndex>>,
+    ) -> anyhow::Result<Self> {
+        let font = Font::new(context, Vector4::new(1.0, 1.0, 1.0, 1.0), 0)?;
+        let shape = Arc::new(Shape::rect(context, Vector2::new(1.0, 1.0))?);
+        let white = Arc::new(util::pixel_texture(context, &[255; 4], 1, 1));
+        let background_instance = Instance::new(
+            context,
+            shape.clone(),
+            white.clone(),
+            Vector4::new(0.1, 0.1, 0.1, 0.75),
+            0,
+        )?;
+        let mut hud = Self {
+            player: None,
+            tags,
+            camera: None,
+            notifications,
+            exploration,
+            recipes,
+            stats: Text::new(font.clone()),
+            crafting: Text::new(font.clone()),
+            hotbar: Text::new(font.clone()),
+            messages: Text::new(font.clone()),
+            font,
+            shape,
+            white,
+            background_instance,
+            bars: Vec::new(),
+            last_frame: Instant::now(),
+        };
+
+        hud.add_bar(
+            context,
+            "HULL",
+            Vector4::new(0.8, 0.2, 0.2, 1.0),
+            |em, player| {
+                em.get_component::<Health>(player)
+                    .map(|h| h.read().fraction())
+            },
+        )?;
+        hud.add_bar(
+            context,
+            "FUEL",
+            Vector4::new(0.9, 0.6, 0.1, 1.0),
+            |em, player| {
+                em.get_component::<ShipSystems>(player)
+                    .map(|s| s.read().fuel_fraction())
+            },
+        )?;
+        hud.add_bar(
+            context,
+            "NRG",
+            Vector4::new(0.3, 0.6, 1.0, 1.0),
+            |em, player| {
+                em.get_component::<ShipSystems>(player)
+                    .map(|s| s.read().energy_fraction())
+            },
+        )?;
+        hud.add_bar(
+            context,
+            "O2",
+            Vector4::new(0.6, 0.9, 0.9, 1.0),
+            |em, player| {
+                em.get_component::<ShipSystems>(player)
+                    .map(|s| s.read().oxygen_fraction())
+            },
+        )?;
+
+        Ok(hud)
+    }
+
+    pub fn add_bar<F>(
+        &mut self,
+        context: &Context,
+        label: &str,
+        color: Vector4<f32>,
+        f: F,
+    ) -> anyhow::Result<()>
+    where
+        F: Fn(&EntityManager, Id) -> Option<f32> + Send + Sync + 'static,
+    {
+        self.bars.push(Bar {
+            value: Arc::new(f),
+            label: Text::new(self.font.clone()),
+            label_value: label.to_string(),
+            background: None,
+            fill: None,
+            fill_instance: Instance::new(
+                context,
+                self.shape.clone(),
+                self.white.clone(),
+                color,
+                0,
+            )?,
+        });
+
+        Ok(())
+    }
+
+    pub fn rect(em: &mut EntityManager, instance: Arc<RwLock<Instance>>) -> Id {
+        let e = em.add(true);
+
+        em.add_component(e, instance);
+        em.add_component(e, Trans::new(Vector2::default(), 0.0, Vector2::default()));
+
+        e
+    }
+
+    fn crafting_text(em: &EntityManager, player: Id, recipes: &Recipes) -> String {
+        let (Some(crafter), Some(inventory)) = (
+            em.get_component::<Crafter>(player),
+            em.get_component::<Inventory>(player),
+        ) else {
+            return String::new();
+        };
+        let crafter = crafter.read();
+        let Some(recipe) = crafter.selected(recipes) else {
+            return String::new();
+        };
+        let ready =
+            recipe.station_ok(crafter.station.as_deref()) && recipe.has_inputs(&inventory.read());
+        let mut text = format!("CRAFT {} {}", recipe.id, if ready { "OK" } else { "--" });
+
+        if let Some((id, progress)) = crafter.progress(recipes) {
+            text += &format!(
+                "\nQUEUE {} {id} {:.0}%{}",
+                crafter.queue.len(),
+                progress * 100.0,
+                if crafter.blocked { " FULL" } else { "" }
+            );
+        }
+
+        text
+    }
+
+    fn stats_text(em: &EntityManager, player: Id, exploration: &Exploration) -> String {
+        let velocity = em
+            .get_component::<Player>(player)
+            .map(|p| p.read().velocity.magnitude())
+            .unwrap_or_default();
+        let position = em
+            .get_component::<Trans>(player)
+            .map(|t| t.read().position())
+            .unwrap_or_default();
+        let (cx, cy) = ChunkManager::chunk_pos(position);
+        let (sx, sy) = TileMap::tile_pos(position)
+            .map(|t| Exploration::sector(TileMap::tile_chunk(t).0))
+            .unwrap_or_default();
+
+        format!(
+            "SPD {velocity:.1}\nPOS {:.0},{:.0}\nCHUNK {cx},{cy}\nSECTOR {sx},{sy} {:.1}%\nEXPLORED {:.2}%",
+            position.x,
+            position.y,
+            exploration.sector_percent((sx, sy)),
+            exploration.percent(),
+        )
+    }
+
+    fn hotbar_text(em: &EntityManager, player: Id) -> String {
+        let Some(inventory) = em.get_component::<Inventory>(player) else {
+            return String::new();
+        };
+        let inventory = inventory.read();
+
+        inventory
+            .hotbar()
+            .iter()
+            .enumerate()
+            .map(|(i, s)| {
+                let slot = match s {
+                    Some(s) => format!("{}:{} {}", i + 1, s.id, s.count),
+                    None => format!("{}:-", i + 1),
+                };
+
+                if i == inventory.selected {
+                    format!("[{slot}]")
+                } else {
+                    slot
+                }
+            })
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+}
+
+impl System for HudManager {
+    fn init(&mut self, _: Arc<RwLock<Context>>, world: Arc<RwLock<World>>) -> anyhow::Result<()> {
+        let em = world.read().em.clone();
+        let mut em = em.write();
+
+        self.player = self.tags.read().first("player");
+        self.camera = self.tags.read().first("camera");
+
+        for bar in &mut self.bars {
+            bar.background = Some(Self::rect(&mut em, self.background_instance.clone()));
+            bar.fill = Some(Self::rect(&mut em, bar.fill_instance.clone()));
+            bar.label.set(&mut em, &bar.label_value);
+        }
+
+        Ok(())
+    }
+
+    fn update(
+        &mut self,
+        control: Arc<RwLock<Control>>,
+        context: Arc<RwLock<Context>>,
+        world: Arc<RwLock<World>>,
+    ) -> anyhow::Result<()> {
+        let event = control.read().event.clone();
+
+        if let Event::WindowEvent {
+            event: WindowEvent::RedrawRequested,
+            window_id,
+        } = event
+        {
+            if window_id != context.read().window.id() {
+                return Ok(());
+            }
+
+            let now = Instant::now();
+            let delta = now.duration_since(self.last_frame).as_secs_f32();
+
+            self.last_frame = now;
+
+            let Some(player) = self.player else {
+                return Ok(());
+            };
+            let em = world.read().em.clone();
+            let (stats, crafting, hotbar, messages) = {
+                let em = em.read();
+                let mut notifications = self.notifications.write();
+
+                notifications.tick(delta);
+
+                (
+                    Self::stats_text(&em, player, &self.exploration.read()),
+                    Self::crafting_text(&em, player, &self.recipes.read()),
+                    Self::hotbar_text(&em, player),
+                    notifications
+                        .messages
+                        .iter()
+                        .map(|(m, _)| m.as_str())
+                        .collect::<Vec<_>>()
+                        .join("\n"),
+                )
+            };
+            let mut em = em.write();
+
+            self.stats.set(&mut em, &stats);
+            self.crafting.set(&mut em, &crafting);
+            self.hotbar.set(&mut em, &hotbar);
+            self.messages.set(&mut em, &messages);
+
+            let Some(screen) = self
+                .camera
+                .and_then(|camera| Screen::new(&context.read(), &em, camera))
+            else {
+                return Ok(());
+            };
+            let line = LINE_ADVANCE as f32;
+
+            screen.place_text(&self.stats, &em, Vector2::new(HUD_MARGIN, HUD_MARGIN));
+            screen.place_text(
+                &self.crafting,
+                &em,
+                Vector2::new(HUD_MARGIN, HUD_MARGIN + Font::measure(&stats, 1.0).y + line),
+            );
+
+            let size = Font::measure(&messages, 1.0);
+
+            screen.place_text(
+                &self.messages,
+                &em,
+                Vector2::new(screen.size.x - HUD_MARGIN - size.x, HUD_MARGIN),
+            );
+
+            let size = Font::measure(&hotbar, 1.0);
+
+            screen.place_text(
+                &self.hotbar,
+                &em,
+                Vector2::new(
+                    (screen.size.x - size.x) / 2.0,
+                    screen.size.y - HUD_MARGIN - size.y,
+                ),
+            );
+
+            for (i, bar) in self.bars.iter().enumerate() {
+                let y = screen.size.y - HUD_MARGIN - (i + 2) as f32 * line;
+                let value = (bar.value)(&em, player).unwrap_or_default().clamp(0.0, 1.0);
+
+                screen.place_text(&bar.label, &em, Vector2::new(HUD_MARGIN, y));
+
+                if let Some(background) = bar.background {
+                    screen.place(
+                        &em,
+                        background,
+                        Vector2::new(HUD_MARGIN + BAR_LABEL_WIDTH + BAR_WIDTH * value, y),
+                        Vector2::new(BAR_WIDTH * (1.0 - value), GLYPH_HEIGHT as f32),
+                    );
+                }
+
+                if let Some(fill) = bar.fill {
+                    screen.place(
+                        &em,
+                        fill,
+                        Vector2::new(HUD_MARGIN + BAR_LABEL_WIDTH, y),
+                        Vector2::new(BAR_WIDTH * value, GLYPH_HEIGHT as f32),
+                    );
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
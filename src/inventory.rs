@@ -0,0 +1,166 @@
+use hex::parking_lot::RwLock;
+use serde_derive::{Deserialize, Serialize};
+use std::sync::Arc;
+
+pub const INVENTORY_SIZE: usize = 27;
+pub const HOTBAR_SIZE: usize = 9;
+pub const MAX_STACK: u32 = 99;
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, Debug)]
+pub struct ItemStack {
+    pub id: String,
+    pub count: u32,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Inventory {
+    pub slots: Vec<Option<ItemStack>>,
+    pub selected: usize,
+}
+
+impl Default for Inventory {
+    fn default() -> Self {
+        Self::with_size(INVENTORY_SIZE)
+    }
+}
+
+impl Inventory {
+    pub fn new() -> Arc<RwLock<Self>> {
+        Arc::new(RwLock::new(Self::default()))
+    }
+
+    pub fn with_size(size: usize) -> Self {
+        Self {
+            slots: vec![None; size],
+            selected: 0,
+        }
+    }
+
+    pub fn resize(&mut self, size: usize) {
+        while self.slots.len() > size && self.slots.last() == Some(&None) {
+            self.slots.pop();
+        }
+
+        if self.slots.len() < size {
+            self.slots.resize(size, None);
+        }
+
+        self.selected = self.selected.min(self.slots.len().saturating_sub(1));
+    }
+
+    pub fn hotbar(&self) -> &[Option<ItemStack>] {
+        &self.slots[..HOTBAR_SIZE.min(self.slots.len())]
+    }
+
+    pub fn selected(&self) -> Option<&ItemStack> {
+        self.slots.get(self.selected).and_then(|s| s.as_ref())
+    }
+
+    pub fn count(&self, id: &str) -> u32 {
+        self.slots
+            .iter()
+            .flatten()
+            .filter(|s| s.id == id)
+            .map(|s| s.count)
+            .sum()
+    }
+
+    pub fn has(&self, id: &str, count: u32) -> bool {
+        self.count(id) >= count
+    }
+
+    pub fn space_for(&self, id: &str) -> u32 {
+        self.slots
+            .iter()
+            .map(|s| match s {
+                Some(s) if s.id == id => MAX_STACK.saturating_sub(s.count),
+                Some(_) => 0,
+                None => MAX_STACK,
+            })
+            .sum()
+    }
+
+    pub fn add(&mut self, id: &str, mut count: u32) -> u32 {
+        for stack in self.slots.iter_mut().flatten() {
+            if count == 0 {
+                break;
+            }
+
+            if stack.id == id {
+                let n = MAX_STACK.saturating_sub(stack.count).min(count);
+
+                stack.count += n;
+                count -= n;
+            }
+        }
+
+        for slot in self.slots.iter_mut() {
+            if count == 0 {
+                break;
+            }
+
+            if slot.is_none() {
+                let n = MAX_STACK.min(count);
+
+                *slot = Some(ItemStack {
+                    id: id.to_string(),
+                    count: n,
+                });
+                count -= n;
+            }
+        }
+
+        count
+    }
+
+    pub fn remove(&mut self, id: &str, count: u32) -> bool {
+        if !self.has(id, count) {
+            return false;
+        }
+
+        let mut count = count;
+
+        for slot in self.slots.iter_mut().rev() {
+            if count == 0 {
+                break;
+            }
+
+            if let Some(stack) = slot.as_mut().filter(|s| s.id == id) {
+                let n = stack.count.min(count);
+
+                stack.count -= n;
+                count -= n;
+
+                if stack.count == 0 {
+                    *slot = None;
+                }
+            }
+        }
+
+        true
+    }
+
+    pub fn drop_fraction(&mut self, fraction: f32) -> Vec<ItemStack> {
+        let mut dropped = Vec::new();
+
+        for slot in self.slots.iter_mut() {
+            if let Some(stack) = slot.as_mut() {
+                let n = ((stack.count as f32 * fraction).ceil() as u32).min(stack.count);
+
+                if n > 0 {
+                    stack.count -= n;
+                    dropped.push(ItemStack {
+                        id: stack.id.clone(),
+                        count: n,
+                    });
+                }
+
+                if stack.count == 0 {
+                    *slot = None;
+                }
+            }
+        }
+
+        dropped
+    }
+}
@@ -1,61 +1,318 @@
+pub mod build_manager;
+pub mod camera_manager;
 pub mod chunk_manager;
+pub mod crafting_manager;
+pub mod cursor_manager;
+pub mod drop_manager;
+pub mod economy;
+pub mod events;
+pub mod exploration;
+pub mod font;
 pub mod game_manager;
 pub mod game_ui_manager;
+pub mod gravity_manager;
+pub mod hazard_manager;
+pub mod health;
+pub mod health_manager;
+pub mod hud_manager;
+pub mod inventory;
+pub mod map_manager;
+pub mod mining_manager;
+pub mod mission_manager;
+pub mod module_manager;
+pub mod path_manager;
+pub mod prefab;
+pub mod projectile_manager;
+pub mod settings;
+pub mod ship_manager;
+pub mod spatial_manager;
 pub mod state;
+pub mod station_manager;
 pub mod tag;
 pub mod util;
+pub mod waypoint_manager;
 
-use chunk_manager::ChunkManager;
+use build_manager::BuildManager;
+use camera_manager::CameraManager;
+use chunk_manager::{ChunkManager, TileMap};
+use crafting_manager::{CraftingManager, Recipes, RECIPES_FILE};
+use cursor_manager::CursorManager;
+use drop_manager::DropManager;
+use economy::Economy;
+use events::{EventBus, EventManager};
+use exploration::Exploration;
 use game_manager::GameManager;
 use game_ui_manager::GameUiManager;
+use gravity_manager::{GravityField, GravityManager};
+use hazard_manager::{HazardConfig, HazardManager, HAZARDS_FILE};
+use health_manager::HealthManager;
 use hex::{
     nalgebra::*,
     threadpool::ThreadPool,
-    vulkano::swapchain::PresentMode,
-    winit::{event_loop::EventLoop, window::WindowBuilder},
+    winit::{dpi::PhysicalSize, event_loop::EventLoop, window::WindowBuilder},
     world::{entity_manager::*, renderer_manager::*, system_manager::*},
     *,
 };
 use hex_instance::renderers::InstanceRenderer;
 use hex_physics::systems::PhysicsManager;
-use rand::prelude::*;
+use hud_manager::{HudManager, Notifications};
+use map_manager::{MapManager, MapView};
+use mining_manager::MiningManager;
+use mission_manager::{MissionManager, Missions, MISSION_TEMPLATES_FILE};
+use module_manager::{ModuleManager, Modules, MODULES_FILE};
+use path_manager::{PathConfig, PathManager, Pathfinder};
+use prefab::{ComponentRegistry, Prefabs, PREFABS_FILE};
+use projectile_manager::ProjectileManager;
+use settings::{Settings, SETTINGS_FILE};
+use ship_manager::ShipManager;
+use spatial_manager::{SpatialIndex, SpatialManager};
 use state::State;
+use station_manager::StationManager;
 use std::sync::Arc;
-use tag::Tag;
+use tag::TagIndex;
+use waypoint_manager::{WaypointManager, Waypoints};
 
 fn main() {
+    let settings = Settings::load(SETTINGS_FILE).unwrap();
     let ev = EventLoop::new().unwrap();
-    let wb = Arc::new(
-        WindowBuilder::new()
-            .with_title("Paraselene Reimagined")
-            .build(&ev)
-            .unwrap(),
-    );
-    let context = Context::new(
-        &ev,
-        wb,
-        PresentMode::Immediate,
-        ThreadPool::new(num_cpus::get() / 2),
-        Vector4::new(0.5, 0.5, 0.5, 1.0),
-    )
-    .unwrap();
-    let state = State::new(rand::thread_rng().gen::<u32>());
+    let context = {
+        let settings = settings.read();
+        let wb = Arc::new(
+            WindowBuilder::new()
+                .with_title(settings.title.clone())
+                .with_inner_size(PhysicalSize::new(settings.size[0], settings.size[1]))
+                .with_fullscreen(settings.window_mode.fullscreen(ev.primary_monitor()))
+                .build(&ev)
+                .unwrap(),
+        );
+
+        Context::new(
+            &ev,
+            wb,
+            settings.present_mode.into(),
+            ThreadPool::new(settings.threads()),
+            settings.clear_color(),
+        )
+        .unwrap()
+    };
+    let state = State::load().unwrap();
+    let tile_map = TileMap::new();
+    let notifications = Notifications::new();
+    let map_view = MapView::new();
+    let exploration = Exploration::load().unwrap();
+    let waypoints = Waypoints::load().unwrap();
+    let recipes = Recipes::load(RECIPES_FILE).unwrap();
+    let modules = Modules::load(MODULES_FILE).unwrap();
+    let economy = Economy::load().unwrap();
+    let missions = Missions::load(MISSION_TEMPLATES_FILE).unwrap();
+    let events = EventBus::new();
+    let tags = TagIndex::new();
+    let registry = ComponentRegistry::new(context.clone(), tags.clone());
+    let gravity = GravityField::new();
+    let spatial = SpatialIndex::new();
+    let pathfinder = Pathfinder::new(PathConfig::default());
+    let prefabs = Prefabs::load(PREFABS_FILE).unwrap();
+    let hazards = HazardConfig::load(HAZARDS_FILE).unwrap();
+
+    {
+        let mut recipes = recipes.write();
+
+        for recipe in modules.read().recipes() {
+            recipes.insert(recipe);
+        }
+    }
+
     let em = EntityManager::new();
 
     {
         let mut em = em.write();
         let s = em.add(true);
 
-        em.add_component(s, Tag::new("state"));
+        tags.write().tag(&mut em, s, "state");
         em.add_component(s, state.clone());
+        em.add_component(s, settings.clone());
+        em.add_component(s, tile_map.clone());
+        em.add_component(s, notifications.clone());
+        em.add_component(s, map_view.clone());
+        em.add_component(s, exploration.clone());
+        em.add_component(s, waypoints.clone());
+        em.add_component(s, recipes.clone());
+        em.add_component(s, modules.clone());
+        em.add_component(s, economy.clone());
+        em.add_component(s, missions.clone());
+        em.add_component(s, events.clone());
+        em.add_component(s, tags.clone());
+        em.add_component(s, registry.clone());
+        em.add_component(s, prefabs.clone());
+        em.add_component(s, gravity.clone());
+        em.add_component(s, spatial.clone());
+        em.add_component(s, pathfinder.clone());
     }
 
     let mut sm = SystemManager::new();
 
-    sm.add(0, GameManager::new(context.clone(), em.clone()).unwrap());
-    sm.add(0, ChunkManager::new(&context.read(), state).unwrap());
+    sm.add(0, EventManager::new(events.clone()));
+    sm.add(
+        0,
+        GameManager::new(
+            context.clone(),
+            em.clone(),
+            tags.clone(),
+            gravity.clone(),
+            events.clone(),
+        )
+        .unwrap(),
+    );
+    sm.add(
+        0,
+        ChunkManager::new(
+            &context.read(),
+            state.clone(),
+            tile_map.clone(),
+            exploration.clone(),
+            events.clone(),
+            tags.clone(),
+            registry.clone(),
+            spatial.clone(),
+            prefabs.clone(),
+            hazards.clone(),
+        )
+        .unwrap(),
+    );
+    sm.add(
+        0,
+        MiningManager::new(tile_map.clone(), events.clone(), tags.clone()),
+    );
+    sm.add(
+        0,
+        PathManager::new(pathfinder, tile_map.clone(), events.clone()),
+    );
+    sm.add(
+        0,
+        GravityManager::new(gravity, state.clone(), tags.clone(), events.clone()),
+    );
+    sm.add(0, ModuleManager::new(modules, tags.clone()));
     sm.add(1, PhysicsManager);
-    sm.add(2, GameUiManager::new().unwrap());
+    sm.add(
+        1,
+        SpatialManager::new(tags.clone(), spatial.clone(), events.clone()),
+    );
+    sm.add(
+        2,
+        StationManager::new(
+            &context.read(),
+            state.clone(),
+            tile_map.clone(),
+            notifications.clone(),
+            economy.clone(),
+            events.clone(),
+            tags.clone(),
+        )
+        .unwrap(),
+    );
+    sm.add(
+        2,
+        MissionManager::new(
+            &context.read(),
+            state.clone(),
+            missions,
+            economy,
+            notifications.clone(),
+            events.clone(),
+            tags.clone(),
+        )
+        .unwrap(),
+    );
+    sm.add(
+        2,
+        BuildManager::new(
+            &context.read(),
+            tile_map.clone(),
+            events.clone(),
+            tags.clone(),
+        )
+        .unwrap(),
+    );
+    sm.add(
+        2,
+        HazardManager::new(
+            &context.read(),
+            hazards,
+            tile_map.clone(),
+            tags.clone(),
+            events.clone(),
+            notifications.clone(),
+        )
+        .unwrap(),
+    );
+    sm.add(
+        2,
+        DropManager::new(
+            tags.clone(),
+            registry.clone(),
+            events.clone(),
+            gravity.clone(),
+            spatial.clone(),
+        ),
+    );
+    sm.add(
+        2,
+        ProjectileManager::new(
+            tags.clone(),
+            registry.clone(),
+            prefabs.clone(),
+            tile_map.clone(),
+            spatial.clone(),
+            events.clone(),
+        ),
+    );
+    sm.add(2, ShipManager::new(notifications.clone(), tags.clone()));
+    sm.add(
+        2,
+        CraftingManager::new(recipes.clone(), notifications.clone(), tags.clone()),
+    );
+    sm.add(
+        2,
+        HealthManager::new(
+            waypoints.clone(),
+            notifications.clone(),
+            events.clone(),
+            tags.clone(),
+        ),
+    );
+    sm.add(2, CameraManager::new(events.clone(), tags.clone()));
+    sm.add(
+        2,
+        MapManager::new(
+            &context.read(),
+            tile_map.clone(),
+            map_view,
+            exploration.clone(),
+            waypoints.clone(),
+            tags.clone(),
+        )
+        .unwrap(),
+    );
+    sm.add(
+        2,
+        CursorManager::new(&context.read(), tile_map, tags.clone()).unwrap(),
+    );
+    sm.add(
+        2,
+        HudManager::new(
+            &context.read(),
+            notifications,
+            exploration,
+            recipes,
+            tags.clone(),
+        )
+        .unwrap(),
+    );
+    sm.add(
+        2,
+        WaypointManager::new(&context.read(), waypoints, tags.clone()).unwrap(),
+    );
+    sm.add(2, GameUiManager::new(settings, tags, events).unwrap());
 
     let mut rm = RendererManager::default();
 
@@ -0,0 +1,666 @@
+use crate::{
+    chunk_manager::{ChunkData, ChunkManager, Tile, TileMap, CHUNK_SIZE, MAX_CHUNK, SAVE_DIR},
+    exploration::Exploration,
+    health_manager::HOSTILE_TAGS,
+    hud_manager::{HudManager, Screen, HUD_MARGIN},
+    station_manager::STATION_TAG,
+    tag::TagIndex,
+    util,
+    waypoint_manager::Waypoints,
+};
+use hex::{
+    anyhow,
+    assets::{Shape, Texture},
+    components::Trans,
+    nalgebra::{Vector2, Vector4},
+    parking_lot::RwLock,
+    winit::event::{ElementState, Event, MouseButton, MouseScrollDelta, WindowEvent},
+    world::{system_manager::System, EntityManager, World},
+    Context, Control, Id,
+};
+use hex_instance::components::Instance;
+use image::RgbaImage;
+use std::{
+    collections::{HashMap, HashSet},
+    fs,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+pub const MAP_DIR: &str = "map";
+pub const MINIMAP_SIZE: f32 = 48.0;
+pub const MINIMAP_ZOOM: f32 = 0.5;
+pub const MAP_MARGIN: f32 = 16.0;
+pub const MAP_ZOOM: f32 = 0.25;
+pub const MIN_MAP_ZOOM: f32 = 1.0 / 32.0;
+pub const MAX_MAP_ZOOM: f32 = 4.0;
+pub const MAP_ZOOM_STEP: f32 = 1.25;
+pub const MAP_LOAD_AMOUNT: usize = 8;
+pub const MARKER_SIZE: f32 = 3.0;
+pub const MAP_CLIP_SAMPLES: u32 = 4;
+pub const MARKER_TAGS: &[&str] = &["player", STATION_TAG];
+
+pub struct MapView {
+    pub open: bool,
+    pub center: Option<Vector2<f32>>,
+    pub zoom: f32,
+}
+
+impl MapView {
+    pub fn new() -> Arc<RwLock<Self>> {
+        Arc::new(RwLock::new(Self {
+            open: false,
+            center: None,
+            zoom: MAP_ZOOM,
+        }))
+    }
+
+    pub fn toggle(&mut self) {
+        self.open = !self.open;
+        self.center = None;
+    }
+}
+
+pub struct MapMarker {
+    pub color: Vector4<f32>,
+}
+
+impl MapMarker {
+    pub fn new(color: Vector4<f32>) -> Arc<RwLock<Self>> {
+        Arc::new(RwLock::new(Self { color }))
+    }
+}
+
+#[derive(Clone)]
+struct MapTile {
+    revision: u64,
+    image: Arc<RgbaImage>,
+    instance: Arc<RwLock<Instance>>,
+}
+
+struct MapLayer {
+    center: Vector2<f32>,
+    zoom: f32,
+    pos: Vector2<f32>,
+    size: Vector2<f32>,
+}
+
+impl MapLayer {
+    fn to_screen(&self, world: Vector2<f32>) -> Vector2<f32> {
+        self.pos
+            + self.size / 2.0
+            + Vector2::new(world.x - self.center.x, self.center.y - world.y) * self.zoom
+    }
+
+    fn to_world(&self, screen: Vector2<f32>) -> Vector2<f32> {
+        let offset = (screen - self.pos - self.size / 2.0) / self.zoom;
+
+        self.center + Vector2::new(offset.x, -offset.y)
+    }
+
+    fn contains(&self, pos: Vector2<f32>, size: Vector2<f32>) -> bool {
+        pos.x >= self.pos.x
+            && pos.y >= self.pos.y
+            && pos.x + size.x <= self.pos.x + self.size.x
+            && pos.y + size.y <= self.pos.y + self.size.y
+    }
+
+    fn clip(&self, pos: Vector2<f32>, size: Vector2<f32>) -> Option<(Vector2<f32>, Vector2<f32>)> {
+        let min = pos.sup(&self.pos);
+        let max = (pos + size).inf(&(self.pos + self.size));
+
+        (max.x > min.x && max.y > min.y).then(|| (min, max - min))
+    }
+
+    fn chunks(&self) -> impl Iterator<Item = (u32, u32)> {
+        let half = self.size / 2.0 / self.zoom;
+        let chunk = |v: f32| {
+            ((v + 0.5) / CHUNK_SIZE as f32)
+                .floor()
+                .clamp(0.0, MAX_CHUNK as f32) as u32
+        };
+        let (x0, x1) = (chunk(self.center.x - half.x), chunk(self.center.x + half.x));
+        let (y0, y1) = (chunk(self.center.y - half.y), chunk(self.center.y + half.y));
+
+        (x0..=x1).flat_map(move |x| (y0..=y1).map(move |y| (x, y)))
+    }
+
+    fn chunk_rect(&self, (x, y): (u32, u32)) -> (Vector2<f32>, Vector2<f32>) {
+        let top_left = Vector2::new(
+            (x * CHUNK_SIZE) as f32 - 0.5,
+            ((y + 1) * CHUNK_SIZE) as f32 - 0.5,
+        );
+
+        (
+            self.to_screen(top_left),
+            Vector2::new(CHUNK_SIZE as f32, CHUNK_SIZE as f32) * self.zoom,
+        )
+    }
+}
+
+pub struct MapManager {
+    pub player: Option<Id>,
+    pub camera: Option<Id>,
+    pub tags: Arc<RwLock<TagIndex>>,
+    pub tile_map: Arc<RwLock<TileMap>>,
+    pub view: Arc<RwLock<MapView>>,
+    pub exploration: Arc<RwLock<Exploration>>,
+    pub waypoints: Arc<RwLock<Waypoints>>,
+    pub shape: Arc<Shape>,
+    pub white: Arc<Texture>,
+    cache: HashMap<(u32, u32), MapTile>,
+    clipped: HashMap<((u32, u32), bool), ([u32; 4], MapTile)>,
+    missing: HashSet<(u32, u32)>,
+    minimap: HashMap<(u32, u32), (Id, Arc<RwLock<Instance>>)>,
+    full: HashMap<(u32, u32), (Id, Arc<RwLock<Instance>>)>,
+    markers: HashMap<[u32; 4], Vec<Id>>,
+    marker_instances: HashMap<[u32; 4], Arc<RwLock<Instance>>>,
+    mouse: Vector2<f32>,
+    drag: bool,
+    hud_per_px: f32,
+    layer: Option<MapLayer>,
+}
+
+impl MapManager {
+    pub fn new(
+        context: &Context,
+        tile_map: Arc<RwLock<TileMap>>,
+        view: Arc<RwLock<MapView>>,
+        exploration: Arc<RwLock<Exploration>>,
+        waypoints: Arc<RwLock<Waypoints>>,
+        tags: Arc<RwLock<TagIndex>>,
+    ) -> anyhow::Result<Self> {
+        Ok(Self {
+            player: None,
+            tags,
+            camera: None,
+            tile_map,
+            view,
+            exploration,
+            waypoints,
+            shape: Arc::new(Shape::rect(context, Vector2::new(1.0, 1.0))?),
+            white: Arc::new(util::pixel_texture(context, &[255; 4], 1, 1)),
+            cache: HashMap::new(),
+            clipped: HashMap::new(),
+            missing: HashSet::new(),
+            minimap: HashMap::new(),
+            full: HashMap::new(),
+            markers: HashMap::new(),
+            marker_instances: HashMap::new(),
+            mouse: Vector2::default(),
+            drag: false,
+            hud_per_px: 1.0,
+            layer: None,
+        })
+    }
+
+    pub fn map_path(chunk: (u32, u32)) -> PathBuf {
+        let (x, y) = chunk;
+
+        PathBuf::from(SAVE_DIR)
+            .join(MAP_DIR)
+            .join(format!("{x},{y}.png"))
+    }
+
+    pub fn render_chunk(data: &ChunkData) -> RgbaImage {
+        RgbaImage::from_fn(CHUNK_SIZE, CHUNK_SIZE, |x, y| {
+            let id = data
+                .grid
+                .get(x as usize)
+                .and_then(|c| c.get((CHUNK_SIZE - 1 - y) as usize))
+                .and_then(|t| t.as_deref());
+
+            image::Rgba(Tile::map_color(id))
+        })
+    }
+
+    fn store(
+        &mut self,
+        context: &Context,
+        chunk: (u32, u32),
+        revision: u64,
+        img: &RgbaImage,
+    ) -> anyhow::Result<MapTile> {
+        let tile = self.tile(context, revision, img.clone())?;
+
+        self.cache.insert(chunk, tile.clone());
+
+        Ok(tile)
+    }
+
+    fn tile(&self, context: &Context, revision: u64, img: RgbaImage) -> anyhow::Result<MapTile> {
+        let texture = util::pixel_texture(context, img.as_raw(), img.width(), img.height());
+
+        Ok(MapTile {
+            revision,
+            image: Arc::new(img),
+            instance: Instance::new(
+                context,
+                self.shape.clone(),
+                Arc::new(texture),
+                Vector4::new(1.0, 1.0, 1.0, 1.0),
+                0,
+            )?,
+        })
+    }
+
+    fn clip_tile(
+        &mut self,
+        context: &Context,
+        key: ((u32, u32), bool),
+        tile: &MapTile,
+        offset: Vector2<f32>,
+        span: Vector2<f32>,
+    ) -> anyhow::Result<MapTile> {
+        let (width, height) = tile.image.dimensions();
+        let (w, h) = (width * MAP_CLIP_SAMPLES, height * MAP_CLIP_SAMPLES);
+        let x0 = ((offset.x * w as f32).floor() as u32).min(w - 1);
+        let y0 = ((offset.y * h as f32).floor() as u32).min(h - 1);
+        let x1 = (((offset.x + span.x) * w as f32).ceil() as u32).clamp(x0 + 1, w);
+        let y1 = (((offset.y + span.y) * h as f32).ceil() as u32).clamp(y0 + 1, h);
+        let rect = [x0, y0, x1, y1];
+
+        if let Some((_, clipped)) = self
+            .clipped
+            .get(&key)
+            .filter(|(r, t)| *r == rect && t.revision == tile.revision)
+        {
+            return Ok(clipped.clone());
+        }
+
+        let img = RgbaImage::from_fn(x1 - x0, y1 - y0, |x, y| {
+            *tile
+                .image
+                .get_pixel((x0 + x) / MAP_CLIP_SAMPLES, (y0 + y) / MAP_CLIP_SAMPLES)
+        });
+        let clipped = self.tile(context, tile.revision, img)?;
+
+        self.clipped.insert(key, (rect, clipped.clone()));
+
+        Ok(clipped)
+    }
+
+    fn map_tile(
+        &mut self,
+        context: &Context,
+        chunk: (u32, u32),
+        budget: &mut usize,
+    ) -> anyhow::Result<Option<MapTile>> {
+        if !self.exploration.read().get(chunk) {
+            return Ok(None);
+        }
+
+        let loaded = {
+            let tile_map = self.tile_map.read();
+
+            tile_map
+                .chunks
+                .contains_key(&chunk)
+                .then(|| tile_map.revision(chunk))
+        };
+
+        if let Some(revision) = loaded {
+            self.missing.remove(&chunk);
+
+            if let Some(tile) = self.cache.get(&chunk).filter(|t| t.revision == revision) {
+                return Ok(Some(tile.clone()));
+            }
+
+            let img = self
+                .tile_map
+                .read()
+                .chunks
+                .get(&chunk)
+                .map(Self::render_chunk);
+
+            if let Some(img) = img {
+                fs::create_dir_all(PathBuf::from(SAVE_DIR).join(MAP_DIR))?;
+                img.save(Self::map_path(chunk))?;
+
+                return self.store(context, chunk, revision, &img).map(Some);
+            }
+        }
+
+        if let Some(tile) = self.cache.get(&chunk) {
+            return Ok(Some(tile.clone()));
+        }
+
+        if self.missing.contains(&chunk) || *budget == 0 {
+            return Ok(None);
+        }
+
+        *budget -= 1;
+
+        let path = Self::map_path(chunk);
+        let img = if Path::exists(&path) {
+            Some(image::open(path)?.to_rgba8())
+        } else {
+            match ChunkManager::read_saved(chunk)? {
+                Some(data) => {
+                    let img = Self::render_chunk(&data);
+
+                    fs::create_dir_all(PathBuf::from(SAVE_DIR).join(MAP_DIR))?;
+                    img.save(Self::map_path(chunk))?;
+
+                    Some(img)
+                }
+                None => None,
+            }
+        };
+
+        match img {
+            Some(img) => self.store(context, chunk, 0, &img).map(Some),
+            None => {
+                self.missing.insert(chunk);
+
+                Ok(None)
+            }
+        }
+    }
+
+    fn draw_layer(
+        &mut self,
+        context: &Context,
+        em: &mut EntityManager,
+        screen: &Screen,
+        layer: &MapLayer,
+        full: bool,
+        budget: &mut usize,
+    ) -> anyhow::Result<()> {
+        let mut visible = HashMap::new();
+
+        for chunk in layer.chunks() {
+            let (pos, size) = layer.chunk_rect(chunk);
+            let Some((clip_pos, clip_size)) = layer.clip(pos, size) else {
+                continue;
+            };
+
+            if let Some(tile) = self.map_tile(context, chunk, budget)? {
+                let tile = if clip_size == size {
+                    tile
+                } else {
+                    self.clip_tile(
+                        context,
+                        (chunk, full),
+                        &tile,
+                        (clip_pos - pos).component_div(&size),
+                        clip_size.component_div(&size),
+                    )?
+                };
+
+                visible.insert(chunk, (tile, clip_pos, clip_size));
+            }
+        }
+
+        self.clipped
+            .retain(|(chunk, f), _| *f != full || visible.contains_key(chunk));
+
+        let entities = if full {
+            &mut self.full
+        } else {
+            &mut self.minimap
+        };
+
+        entities.retain(|chunk, (e, instance)| {
+            let keep = visible
+                .get(chunk)
+                .map(|(t, _, _)| Arc::ptr_eq(&t.instance, instance))
+                .unwrap_or(false);
+
+            if !keep {
+                em.rm(*e);
+            }
+
+            keep
+        });
+
+        for (chunk, (tile, pos, size)) in visible {
+            let e = entities
+                .entry(chunk)
+                .or_insert_with(|| {
+                    (
+                        HudManager::rect(em, tile.instance.clone()),
+                        tile.instance.clone(),
+                    )
+                })
+                .0;
+
+            screen.place(em, e, pos, size);
+        }
+
+        Ok(())
+    }
+
+    fn clear_layer(
+        em: &mut EntityManager,
+        entities: &mut HashMap<(u32, u32), (Id, Arc<RwLock<Instance>>)>,
+    ) {
+        for (_, (e, _)) in entities.drain() {
+            em.rm(e);
+        }
+    }
+
+    fn marker_key(color: Vector4<f32>) -> [u32; 4] {
+        color.map(|c| c.to_bits()).into()
+    }
+
+    fn marker_instance(
+        &mut self,
+        context: &Context,
+        color: Vector4<f32>,
+    ) -> anyhow::Result<Arc<RwLock<Instance>>> {
+        let key = Self::marker_key(color);
+
+        if let Some(instance) = self.marker_instances.get(&key) {
+            return Ok(instance.clone());
+        }
+
+        let instance = Instance::new(context, self.shape.clone(), self.white.clone(), color, 0)?;
+
+        self.marker_instances.insert(key, instance.clone());
+
+        Ok(instance)
+    }
+
+    fn draw_markers(
+        &mut self,
+        context: &Context,
+        em: &mut EntityManager,
+        screen: &Screen,
+        layers: &[MapLayer],
+    ) -> anyhow::Result<()> {
+        let mut markers: Vec<_> = {
+            let tags = self.tags.read();
+
+            MARKER_TAGS
+                .iter()
+                .chain(HOSTILE_TAGS)
+                .flat_map(|tag| tags.query::<MapMarker>(em, tag))
+                .filter_map(|(e, marker)| {
+                    let position = em.get_component::<Trans>(e)?.read().position();
+                    let color = marker.read().color;
+
+                    Some((position, color))
+                })
+                .collect()
+        };
+
+        markers.extend(
+            self.waypoints
+                .read()
+                .all()
+                .map(|(_, w)| (w.position(), w.color.into())),
+        );
+        let size = Vector2::new(MARKER_SIZE, MARKER_SIZE);
+        let mut used: HashMap<[u32; 4], usize> = HashMap::new();
+
+        for (position, color) in markers {
+            for layer in layers {
+                let pos = layer.to_screen(position) - size / 2.0;
+
+                if !layer.contains(pos, size) {
+                    continue;
+                }
+
+                let key = Self::marker_key(color);
+                let index = *used.get(&key).unwrap_or(&0);
+
+                if self.markers.get(&key).map_or(0, Vec::len) <= index {
+                    let e = HudManager::rect(em, self.marker_instance(context, color)?);
+
+                    self.markers.entry(key).or_default().push(e);
+                }
+
+                screen.place(em, self.markers[&key][index], pos, size);
+                used.insert(key, index + 1);
+            }
+        }
+
+        for (key, entities) in &mut self.markers {
+            for e in entities.drain(used.get(key).copied().unwrap_or_default()..) {
+                em.rm(e);
+            }
+        }
+
+        self.markers.retain(|_, entities| !entities.is_empty());
+
+        Ok(())
+    }
+}
+
+impl System for MapManager {
+    fn init(&mut self, _: Arc<RwLock<Context>>, _: Arc<RwLock<World>>) -> anyhow::Result<()> {
+        self.player = self.tags.read().first("player");
+        self.camera = self.tags.read().first("camera");
+
+        Ok(())
+    }
+
+    fn update(
+        &mut self,
+        control: Arc<RwLock<Control>>,
+        context: Arc<RwLock<Context>>,
+        world: Arc<RwLock<World>>,
+    ) -> anyhow::Result<()> {
+        let event = control.read().event.clone();
+
+        match event {
+            Event::WindowEvent {
+                event: WindowEvent::MouseWheel { delta, .. },
+                window_id,
+            } if window_id == context.read().window.id() => {
+                let mut view = self.view.write();
+
+                if view.open {
+                    let y = match delta {
+                        MouseScrollDelta::LineDelta(_, y) => y,
+                        MouseScrollDelta::PixelDelta(p) => p.y as f32 / 32.0,
+                    };
+
+                    view.zoom =
+                        (view.zoom * MAP_ZOOM_STEP.powf(y)).clamp(MIN_MAP_ZOOM, MAX_MAP_ZOOM);
+                }
+            }
+            Event::WindowEvent {
+                event:
+                    WindowEvent::MouseInput {
+                        state,
+                        button: MouseButton::Right,
+                        ..
+                    },
+                window_id,
+            } if window_id == context.read().window.id() => {
+                self.drag = state == ElementState::Pressed && self.view.read().open;
+            }
+            Event::WindowEvent {
+                event:
+                    WindowEvent::MouseInput {
+                        state: ElementState::Pressed,
+                        button: MouseButton::Middle,
+                        ..
+                    },
+                window_id,
+            } if window_id == context.read().window.id() => {
+                if let Some(layer) = self.layer.as_ref().filter(|_| self.view.read().open) {
+                    self.waypoints
+                        .write()
+                        .add_numbered(layer.to_world(self.mouse * self.hud_per_px));
+                }
+            }
+            Event::WindowEvent {
+                event: WindowEvent::CursorMoved { position, .. },
+                window_id,
+            } if window_id == context.read().window.id() => {
+                let mouse = Vector2::new(position.x as f32, position.y as f32);
+                let delta = (mouse - self.mouse) * self.hud_per_px;
+
+                self.mouse = mouse;
+
+                if self.drag {
+                    let mut view = self.view.write();
+
+                    if let Some(center) = view.center.as_mut() {
+                        *center += Vector2::new(-delta.x, delta.y) / view.zoom;
+                    }
+                }
+            }
+            Event::WindowEvent {
+                event: WindowEvent::RedrawRequested,
+                window_id,
+            } if window_id == context.read().window.id() => {
+                let (Some(player), Some(camera)) = (self.player, self.camera) else {
+                    return Ok(());
+                };
+                let em = world.read().em.clone();
+                let mut em = em.write();
+                let context = context.read();
+                let Some(screen) = Screen::new(&context, &em, camera) else {
+                    return Ok(());
+                };
+                let player_position = em
+                    .get_component::<Trans>(player)
+                    .map(|t| t.read().position())
+                    .unwrap_or_default();
+                let (open, zoom, center) = {
+                    let mut view = self.view.write();
+                    let center = *view.center.get_or_insert(player_position);
+
+                    (view.open, view.zoom, center)
+                };
+                let mut budget = MAP_LOAD_AMOUNT;
+
+                self.hud_per_px = screen.size.x / context.window.inner_size().width.max(1) as f32;
+
+                let layer = if open {
+                    Self::clear_layer(&mut em, &mut self.minimap);
+
+                    MapLayer {
+                        center,
+                        zoom,
+                        pos: Vector2::new(MAP_MARGIN, MAP_MARGIN),
+                        size: screen.size - Vector2::new(MAP_MARGIN, MAP_MARGIN) * 2.0,
+                    }
+                } else {
+                    Self::clear_layer(&mut em, &mut self.full);
+
+                    MapLayer {
+                        center: player_position,
+                        zoom: MINIMAP_ZOOM,
+                        pos: screen.size
+                            - Vector2::new(HUD_MARGIN, HUD_MARGIN * 2.0)
+                            - Vector2::new(MINIMAP_SIZE, MINIMAP_SIZE),
+                        size: Vector2::new(MINIMAP_SIZE, MINIMAP_SIZE),
+                    }
+                };
+
+                self.draw_layer(&context, &mut em, &screen, &layer, open, &mut budget)?;
+                self.draw_markers(&context, &mut em, &screen, std::slice::from_ref(&layer))?;
+
+                self.layer = open.then_some(layer);
+            }
+            _ => {}
+        }
+
+        Ok(())
+    }
+}
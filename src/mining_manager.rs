@@ -0,0 +1,168 @@
+use crate::{
+    chunk_manager::{Tile, TileMap},
+    events::{EventBus, GameEvent},
+    game_manager::Player,
+    tag::TagIndex,
+};
+use hex::{
+    anyhow,
+    components::Trans,
+    parking_lot::RwLock,
+    winit::event::{Event, WindowEvent},
+    world::{system_manager::System, World},
+    Context, Control, Id,
+};
+use std::{sync::Arc, time::Instant};
+
+pub const MINING_RANGE: f32 = 4.0;
+pub const MINING_SPEED: f32 = 1.0;
+
+pub struct Miner {
+    pub active: bool,
+    pub target: Option<(u32, u32)>,
+    pub progress: f32,
+    pub range: f32,
+    pub speed: f32,
+}
+
+impl Miner {
+    pub fn new() -> Arc<RwLock<Self>> {
+        Arc::new(RwLock::new(Self {
+            active: false,
+            target: None,
+            progress: 0.0,
+            range: MINING_RANGE,
+            speed: MINING_SPEED,
+        }))
+    }
+
+    pub fn progress_at(&self, tile: (u32, u32)) -> Option<f32> {
+        (self.target == Some(tile) && self.progress > 0.0).then_some(self.progress)
+    }
+}
+
+pub struct MiningManager {
+    pub player: Option<Id>,
+    pub tags: Arc<RwLock<TagIndex>>,
+    pub tile_map: Arc<RwLock<TileMap>>,
+    pub events: Arc<RwLock<EventBus>>,
+    pub last_frame: Instant,
+}
+
+impl MiningManager {
+    pub fn new(
+        tile_map: Arc<RwLock<TileMap>>,
+        events: Arc<RwLock<EventBus>>,
+        tags: Arc<RwLock<TagIndex>>,
+    ) -> Self {
+        Self {
+            player: None,
+            tags,
+            tile_map,
+            events,
+            last_frame: Instant::now(),
+        }
+    }
+}
+
+impl System for MiningManager {
+    fn init(&mut self, _: Arc<RwLock<Context>>, _: Arc<RwLock<World>>) -> anyhow::Result<()> {
+        self.player = self.tags.read().first("player");
+
+        Ok(())
+    }
+
+    fn update(
+        &mut self,
+        control: Arc<RwLock<Control>>,
+        context: Arc<RwLock<Context>>,
+        world: Arc<RwLock<World>>,
+    ) -> anyhow::Result<()> {
+        let event = control.read().event.clone();
+
+        if let Event::WindowEvent {
+            event: WindowEvent::RedrawRequested,
+            window_id,
+        } = event
+        {
+            if window_id != context.read().window.id() {
+                return Ok(());
+            }
+
+            let now = Instant::now();
+            let delta = now.duration_since(self.last_frame).as_secs_f32();
+
+            self.last_frame = now;
+
+            let Some(player) = self.player else {
+                return Ok(());
+            };
+            let em = world.read().em.clone();
+            let mined = {
+                let em = em.read();
+                let Some(miner) = em.get_component::<Miner>(player) else {
+                    return Ok(());
+                };
+                let mut miner = miner.write();
+                let position = em.get_component::<Trans>(player).unwrap().read().position();
+                let aim = em.get_component::<Player>(player).unwrap().read().aim;
+                let tile_map = self.tile_map.read();
+                let target = TileMap::tile_pos(aim)
+                    .filter(|t| (TileMap::tile_position(*t) - position).magnitude() <= miner.range)
+                    .filter(|t| tile_map.visible(position, TileMap::tile_position(*t)))
+                    .and_then(|t| tile_map.get(t).map(|id| (t, id.clone())));
+
+                match target {
+                    Some((tile, id)) if miner.active => {
+                        if miner.target != Some(tile) {
+                            miner.target = Some(tile);
+                            miner.progress = 0.0;
+                        }
+
+                        miner.progress += delta * miner.speed / Tile::hardness(&id);
+
+                        if miner.progress >= 1.0 {
+                            miner.target = None;
+                            miner.progress = 0.0;
+
+                            Some(tile)
+                        } else {
+                            None
+                        }
+                    }
+                    _ => {
+                        miner.target = None;
+                        miner.progress = 0.0;
+
+                        None
+                    }
+                }
+            };
+
+            if let Some(tile) = mined {
+                let mut tile_map = self.tile_map.write();
+
+                if let Some(id) = tile_map.set(tile, None) {
+                    let mut events = self.events.write();
+
+                    events.publish(GameEvent::TileMined {
+                        tile,
+                        id: id.clone(),
+                        by: player,
+                    });
+                    events.publish(GameEvent::TileChanged {
+                        tile,
+                        old: Some(id),
+                        new: None,
+                    });
+                }
+
+                if let Some(e) = tile_map.entities.remove(&tile) {
+                    em.write().rm(e);
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,875 @@
+use crate::{
+    chunk_manager::SAVE_DIR,
+    economy::Economy,
+    events::{EventBus, GameEvent, Subscriber},
+    font::{Font, Text},
+    hud_manager::{Notifications, Screen, HUD_MARGIN},
+    inventory::{Inventory, ItemStack},
+    state::State,
+    station_manager::{Docking, Wallet},
+    tag::TagIndex,
+    util,
+};
+use hex::{
+    anyhow,
+    components::Trans,
+    nalgebra::{Vector2, Vector4},
+    parking_lot::RwLock,
+    winit::event::{Event, WindowEvent},
+    world::{system_manager::System, EntityManager, World},
+    Context, Control, Id,
+};
+use rand::prelude::*;
+use serde_derive::{Deserialize, Serialize};
+use std::{
+    collections::BTreeSet,
+    f32::consts::TAU,
+    fs,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::Instant,
+};
+
+pub const MISSION_TEMPLATES_FILE: &str = "data/missions.json";
+pub const MISSIONS_FILE: &str = "missions.json";
+pub const MAX_ACTIVE_MISSIONS: usize = 5;
+pub const OFFERS_PER_STATION: usize = 3;
+pub const MISSION_REFRESH_TICKS: u64 = 600;
+pub const MISSION_PANEL_Y: f32 = 48.0;
+pub const MISSION_SALT: u64 = 0x4d49_5353_494f_4e53;
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ObjectiveTemplate {
+    Mine { id: String, count: [u32; 2] },
+    Reach { distance: [f32; 2], radius: f32 },
+    Destroy { kind: String, count: [u32; 2] },
+    Deliver { id: String, count: [u32; 2] },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct MissionTemplate {
+    pub id: String,
+    pub objectives: Vec<ObjectiveTemplate>,
+    pub credits: [u64; 2],
+    #[serde(default)]
+    pub items: Vec<ItemStack>,
+    #[serde(default)]
+    pub time_limit: Option<f32>,
+}
+
+impl MissionTemplate {
+    pub fn validate(&self) -> anyhow::Result<()> {
+        let valid = self.credits[0] <= self.credits[1]
+            && self.time_limit.iter().all(|t| t.is_finite() && *t > 0.0)
+            && self.objectives.iter().all(|o| match o {
+                ObjectiveTemplate::Mine { count, .. }
+                | ObjectiveTemplate::Destroy { count, .. }
+                | ObjectiveTemplate::Deliver { count, .. } => count[0] <= count[1],
+                ObjectiveTemplate::Reach { distance, radius } => {
+                    distance.iter().all(|d| d.is_finite() && *d >= 0.0)
+                        && distance[0] <= distance[1]
+                        && radius.is_finite()
+                        && *radius > 0.0
+                }
+            });
+
+        if !valid {
+            anyhow::bail!("invalid mission template \"{}\"", self.id);
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ObjectiveKind {
+    Mine {
+        id: String,
+        count: u32,
+    },
+    Reach {
+        position: [f32; 2],
+        radius: f32,
+    },
+    Destroy {
+        kind: String,
+        count: u32,
+    },
+    Deliver {
+        id: String,
+        count: u32,
+        station: [u32; 2],
+    },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Objective {
+    pub kind: ObjectiveKind,
+    pub progress: u32,
+}
+
+impl Objective {
+    pub fn new(kind: ObjectiveKind) -> Self {
+        Self { kind, progress: 0 }
+    }
+
+    pub fn required(&self) -> u32 {
+        match &self.kind {
+            ObjectiveKind::Mine { count, .. }
+            | ObjectiveKind::Destroy { count, .. }
+            | ObjectiveKind::Deliver { count, .. } => *count,
+            ObjectiveKind::Reach { .. } => 1,
+        }
+    }
+
+    pub fn remaining(&self) -> u32 {
+        self.required().saturating_sub(self.progress)
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.remaining() == 0
+    }
+
+    pub fn handle(&mut self, event: &MissionEvent) -> bool {
+        if self.is_done() {
+            return false;
+        }
+
+        let amount = match (&self.kind, event) {
+            (ObjectiveKind::Mine { id, .. }, MissionEvent::Mined { id: mined }) if id == mined => 1,
+            (ObjectiveKind::Reach { position, radius }, MissionEvent::Moved { position: p })
+                if (Vector2::from(*position) - p).magnitude() <= *radius =>
+            {
+                1
+            }
+            (ObjectiveKind::Destroy { kind, .. }, MissionEvent::Destroyed { kind: k })
+                if kind == k =>
+            {
+                1
+            }
+            (
+                ObjectiveKind::Deliver { id, station, .. },
+                MissionEvent::Delivered {
+                    station: s,
+                    id: i,
+                    count,
+                },
+            ) if id == i && *station == [s.0, s.1] => *count,
+            _ => 0,
+        };
+
+        self.progress = (self.progress + amount).min(self.required());
+
+        amount > 0
+    }
+
+    pub fn describe(&self) -> String {
+        match &self.kind {
+            ObjectiveKind::Mine { id, count } => format!("MINE {id} {}/{count}", self.progress),
+            ObjectiveKind::Reach { position, .. } => {
+                format!("REACH {:.0},{:.0}", position[0], position[1])
+            }
+            ObjectiveKind::Destroy { kind, count } => {
+                format!("DESTROY {kind} {}/{count}", self.progress)
+            }
+            ObjectiveKind::Deliver {
+                id, count, station, ..
+            } => format!(
+                "DELIVER {id} {}/{count} TO {},{}",
+                self.progress, station[0], station[1]
+            ),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum MissionEvent {
+    Mined {
+        id: String,
+    },
+    Moved {
+        position: Vector2<f32>,
+    },
+    Destroyed {
+        kind: String,
+    },
+    Delivered {
+        station: (u32, u32),
+        id: String,
+        count: u32,
+    },
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum MissionState {
+    Active,
+    Completed,
+    Failed,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Mission {
+    pub key: String,
+    pub template: String,
+    pub origin: [u32; 2],
+    pub objectives: Vec<Objective>,
+    pub credits: u64,
+    pub items: Vec<ItemStack>,
+    pub time_left: Option<f32>,
+    pub state: MissionState,
+}
+
+impl Mission {
+    pub fn generate(
+        template: &MissionTemplate,
+        key: String,
+        origin: (u32, u32),
+        origin_position: Vector2<f32>,
+        destinations: &[(u32, u32)],
+        rng: &mut StdRng,
+    ) -> Self {
+        let objectives = template
+            .objectives
+            .iter()
+            .map(|o| {
+                Objective::new(match o {
+                    ObjectiveTemplate::Mine { id, count } => ObjectiveKind::Mine {
+                        id: id.clone(),
+                        count: rng.gen_range(count[0]..=count[1]),
+                    },
+                    ObjectiveTemplate::Reach { distance, radius } => {
+                        let angle = rng.gen_range(0.0..TAU);
+                        let distance = rng.gen_range(distance[0]..=distance[1]);
+                        let position =
+                            origin_position + Vector2::new(angle.cos(), angle.sin()) * distance;
+
+                        ObjectiveKind::Reach {
+                            position: position.map(|p| p.max(0.0)).into(),
+                            radius: *radius,
+                        }
+                    }
+                    ObjectiveTemplate::Destroy { kind, count } => ObjectiveKind::Destroy {
+                        kind: kind.clone(),
+                        count: rng.gen_range(count[0]..=count[1]),
+                    },
+                    ObjectiveTemplate::Deliver { id, count } => {
+                        let station = destinations.choose(rng).copied().unwrap_or(origin);
+
+                        ObjectiveKind::Deliver {
+                            id: id.clone(),
+                            count: rng.gen_range(count[0]..=count[1]),
+                            station: [station.0, station.1],
+                        }
+                    }
+                })
+            })
+            .collect();
+
+        Self {
+            key,
+            template: template.id.clone(),
+            origin: [origin.0, origin.1],
+            objectives,
+            credits: rng.gen_range(template.credits[0]..=template.credits[1]),
+            items: template.items.clone(),
+            time_left: template.time_limit,
+            state: MissionState::Active,
+        }
+    }
+
+    pub fn handle(&mut self, event: &MissionEvent) -> bool {
+        if self.state != MissionState::Active {
+            return false;
+        }
+
+        let mut changed = false;
+
+        for objective in &mut self.objectives {
+            changed |= objective.handle(event);
+        }
+
+        if self.objectives.iter().all(Objective::is_done) {
+            self.state = MissionState::Completed;
+        }
+
+        changed
+    }
+
+    pub fn tick(&mut self, delta: f32) {
+        if self.state != MissionState::Active {
+            return;
+        }
+
+        if let Some(time_left) = self.time_left.as_mut() {
+            *time_left -= delta;
+
+            if *time_left <= 0.0 {
+                self.state = MissionState::Failed;
+            }
+        }
+    }
+
+    pub fn title(&self) -> String {
+        self.objectives
+            .iter()
+            .map(Objective::describe)
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
+
+#[derive(Serialize, Deserialize, Default, Debug)]
+#[serde(default)]
+pub struct Missions {
+    pub active: Vec<Mission>,
+    pub taken: BTreeSet<String>,
+    pub completed: u32,
+    pub failed: u32,
+    #[serde(skip)]
+    pub templates: Vec<MissionTemplate>,
+    #[serde(skip)]
+    pub events: Vec<MissionEvent>,
+    #[serde(skip)]
+    pub accept: bool,
+    #[serde(skip)]
+    pub dirty: bool,
+}
+
+impl Missions {
+    pub fn path() -> PathBuf {
+        PathBuf::from(SAVE_DIR).join(MISSIONS_FILE)
+    }
+
+    pub fn load<P>(templates: P) -> anyhow::Result<Arc<RwLock<Self>>>
+    where
+        P: AsRef<Path>,
+    {
+        let path = Self::path();
+        let mut missions: Self = if Path::exists(&path) {
+            serde_json::from_str(fs::read_to_string(path)?.as_str())?
+        } else {
+            Self::default()
+        };
+        let templates = templates.as_ref();
+
+        if Path::exists(templates) {
+            missions.templates = serde_json::from_str(fs::read_to_string(templates)?.as_str())?;
+        }
+
+        for template in &missions.templates {
+            template.validate()?;
+        }
+
+        Ok(Arc::new(RwLock::new(missions)))
+    }
+
+    pub fn save(&mut self) -> anyhow::Result<()> {
+        fs::create_dir_all(SAVE_DIR)?;
+        fs::write(Self::path(), serde_json::to_string_pretty(self)?)?;
+
+        self.dirty = false;
+
+        Ok(())
+    }
+
+    pub fn push(&mut self, event: MissionEvent) {
+        self.events.push(event);
+    }
+
+    pub fn offers(
+        &self,
+        seed: u32,
+        station: (u32, u32),
+        position: Vector2<f32>,
+        day: u64,
+        destinations: &[(u32, u32)],
+    ) -> Vec<Mission> {
+        if self.templates.is_empty() {
+            return Vec::new();
+        }
+
+        let salt = util::seed_hash(seed, MISSION_SALT).wrapping_add(day);
+        let mut rng = StdRng::seed_from_u64(util::chunk_hash(station, salt));
+
+        (0..OFFERS_PER_STATION)
+            .map(|i| {
+                let template = &self.templates[rng.gen_range(0..self.templates.len())];
+                let key = format!("{},{}:{day}:{i}", station.0, station.1);
+
+                Mission::generate(template, key, station, position, destinations, &mut rng)
+            })
+            .filter(|m| !self.taken.contains(&m.key))
+            .collect()
+    }
+
+    pub fn accept(&mut self, mission: Mission) -> bool {
+        if self.active.len() >= MAX_ACTIVE_MISSIONS || !self.taken.insert(mission.key.clone()) {
+            return false;
+        }
+
+        self.active.push(mission);
+        self.dirty = true;
+
+        true
+    }
+
+    pub fn handle(&mut self, event: &MissionEvent) -> bool {
+        let mut changed = false;
+
+        for mission in &mut self.active {
+            changed |= mission.handle(event);
+        }
+
+        self.dirty |= changed;
+
+        changed
+    }
+
+    pub fn tick(&mut self, delta: f32) -> Vec<Mission> {
+        for mission in &mut self.active {
+            mission.tick(delta);
+        }
+
+        let (finished, active): (Vec<_>, Vec<_>) = std::mem::take(&mut self.active)
+            .into_iter()
+            .partition(|m| m.state != MissionState::Active);
+
+        self.active = active;
+
+        for mission in &finished {
+            match mission.state {
+                MissionState::Completed => self.completed += 1,
+                _ => self.failed += 1,
+            }
+        }
+
+        self.dirty |= !finished.is_empty();
+
+        finished
+    }
+}
+
+pub struct MissionManager {
+    pub player: Option<Id>,
+    pub camera: Option<Id>,
+    pub tags: Arc<RwLock<TagIndex>>,
+    pub state: Arc<RwLock<State>>,
+    pub missions: Arc<RwLock<Missions>>,
+    pub economy: Arc<RwLock<Economy>>,
+    pub notifications: Arc<RwLock<Notifications>>,
+    pub events: Arc<RwLock<EventBus>>,
+    pub subscriber: Subscriber,
+    pub text: Text,
+    last_frame: Instant,
+}
+
+impl MissionManager {
+    pub fn new(
+        context: &Context,
+        state: Arc<RwLock<State>>,
+        missions: Arc<RwLock<Missions>>,
+        economy: Arc<RwLock<Economy>>,
+        notifications: Arc<RwLock<Notifications>>,
+        events: Arc<RwLock<EventBus>>,
+        tags: Arc<RwLock<TagIndex>>,
+    ) -> anyhow::Result<Self> {
+        let subscriber = events.write().subscribe();
+
+        Ok(Self {
+            player: None,
+            tags,
+            camera: None,
+            state,
+            missions,
+            economy,
+            notifications,
+            events,
+            subscriber,
+            text: Text::new(Font::new(context, Vector4::new(1.0, 0.9, 0.5, 1.0), 0)?),
+            last_frame: Instant::now(),
+        })
+    }
+
+    fn station_offers(&self, station: (u32, u32), position: Vector2<f32>) -> Vec<Mission> {
+        let economy = self.economy.read();
+        let destinations: Vec<_> = economy
+            .quotes
+            .values()
+            .map(|q| (q.chunk[0], q.chunk[1]))
+            .filter(|c| *c != station)
+            .collect();
+
+        self.missions.read().offers(
+            self.state.read().seed,
+            station,
+            position,
+            economy.tick / MISSION_REFRESH_TICKS,
+            &destinations,
+        )
+    }
+
+    fn deliver(&self, em: &EntityManager, player: Id, station: (u32, u32)) {
+        let Some(inventory) = em.get_component::<Inventory>(player) else {
+            return;
+        };
+        let mut inventory = inventory.write();
+        let mut missions = self.missions.write();
+        let wanted: Vec<_> = missions
+            .active
+            .iter()
+            .flat_map(|m| m.objectives.iter())
+            .filter_map(|o| match &o.kind {
+                ObjectiveKind::Deliver { id, station: s, .. }
+                    if *s == [station.0, station.1] && !o.is_done() =>
+                {
+                    Some((id.clone(), o.remaining()))
+                }
+                _ => None,
+            })
+            .collect();
+
+        for (id, remaining) in wanted {
+            let count = inventory.count(&id).min(remaining);
+
+            if count > 0 && inventory.remove(&id, count) {
+                missions.handle(&MissionEvent::Delivered { station, id, count });
+            }
+        }
+    }
+
+    fn reward(&self, em: &EntityManager, player: Id, mission: &Mission) {
+        if let Some(wallet) = em.get_component::<Wallet>(player) {
+            wallet.write().credits += mission.credits;
+        }
+
+        if let Some(inventory) = em.get_component::<Inventory>(player) {
+            let mut inventory = inventory.write();
+
+            for item in &mission.items {
+                inventory.add(&item.id, item.count);
+            }
+        }
+    }
+
+    fn panel_text(&self, docked: Option<&[Mission]>) -> String {
+        let missions = self.missions.read();
+        let mut lines: Vec<_> = missions
+            .active
+            .iter()
+            .map(|m| match m.time_left {
+                Some(t) => format!("{} {:.0}S", m.title(), t.max(0.0)),
+                None => m.title(),
+            })
+            .collect();
+
+        if let Some(offer) = docked.and_then(|o| o.first()) {
+            lines.push(format!("J ACCEPT {} +{}CR", offer.title(), offer.credits));
+        }
+
+        lines.join("\n")
+    }
+}
+
+impl System for MissionManager {
+    fn init(&mut self, _: Arc<RwLock<Context>>, _: Arc<RwLock<World>>) -> anyhow::Result<()> {
+        self.player = self.tags.read().first("player");
+        self.camera = self.tags.read().first("camera");
+
+        Ok(())
+    }
+
+    fn update(
+        &mut self,
+        control: Arc<RwLock<Control>>,
+        context: Arc<RwLock<Context>>,
+        world: Arc<RwLock<World>>,
+    ) -> anyhow::Result<()> {
+        let event = control.read().event.clone();
+
+        match event {
+            Event::WindowEvent {
+                event: WindowEvent::CloseRequested,
+                window_id,
+            } if window_id == context.read().window.id() => {
+                self.missions.write().save()?;
+            }
+            Event::WindowEvent {
+                event: WindowEvent::RedrawRequested,
+                window_id,
+            } if window_id == context.read().window.id() => {
+                let now = Instant::now();
+                let delta = now.duration_since(self.last_frame).as_secs_f32();
+
+                self.last_frame = now;
+
+                let (Some(player), Some(camera)) = (self.player, self.camera) else {
+                    return Ok(());
+                };
+                let em = world.read().em.clone();
+                let mut em = em.write();
+                let Some(position) = em
+                    .get_component::<Trans>(player)
+                    .map(|t| t.read().position())
+                else {
+                    return Ok(());
+                };
+                let docked = em
+                    .get_component::<Docking>(player)
+                    .and_then(|d| d.read().station);
+                let offers = docked.map(|s| self.station_offers(s, position));
+
+                if let Some(station) = docked {
+                    self.deliver(&em, player, station);
+                }
+
+                let finished = {
+                    let mut missions = self.missions.write();
+
+                    for event in self.events.write().poll(self.subscriber) {
+                        match event {
+                            GameEvent::TileMined { id, by, .. } if by == player => {
+                                missions.push(MissionEvent::Mined { id });
+                            }
+                            GameEvent::EntityDestroyed {
+                                kind: Some(kind), ..
+                            } => {
+                                missions.push(MissionEvent::Destroyed { kind });
+                            }
+                            _ => {}
+                        }
+                    }
+
+                    missions.push(MissionEvent::Moved { position });
+
+                    for event in std::mem::take(&mut missions.events) {
+                        missions.handle(&event);
+                    }
+
+                    if std::mem::take(&mut missions.accept) {
+                        if let Some(offer) = offers.as_ref().and_then(|o| o.first()) {
+                            if missions.accept(offer.clone()) {
+                                self.notifications.write().push("MISSION ACCEPTED");
+                            }
+                        }
+                    }
+
+                    let finished = missions.tick(delta);
+
+                    if missions.dirty {
+                        missions.save()?;
+                    }
+
+                    finished
+                };
+
+                for mission in &finished {
+                    if mission.state == MissionState::Completed {
+                        self.reward(&em, player, mission);
+                        self.notifications
+                            .write()
+                            .push(format!("MISSION COMPLETE +{}CR", mission.credits));
+                    } else {
+                        self.notifications.write().push("MISSION FAILED");
+                    }
+                }
+
+                let offers = docked.map(|s| self.station_offers(s, position));
+                let text = self.panel_text(offers.as_deref());
+
+                self.text.set(&mut em, &text);
+
+                if let Some(screen) = Screen::new(&context.read(), &em, camera) {
+                    let size = Font::measure(&text, 1.0);
+
+                    screen.place_text(
+                        &self.text,
+                        &em,
+                        Vector2::new(
+                            screen.size.x - HUD_MARGIN - size.x,
+                            HUD_MARGIN + MISSION_PANEL_Y,
+                        ),
+                    );
+                }
+            }
+            _ => {}
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mission(key: &str, objectives: Vec<ObjectiveKind>, time_left: Option<f32>) -> Mission {
+        Mission {
+            key: key.to_string(),
+            template: key.to_string(),
+            origin: [2, 2],
+            objectives: objectives.into_iter().map(Objective::new).collect(),
+            credits: 100,
+            items: Vec::new(),
+            time_left,
+            state: MissionState::Active,
+        }
+    }
+
+    fn mined(id: &str) -> MissionEvent {
+        MissionEvent::Mined { id: id.to_string() }
+    }
+
+    fn delivered(station: (u32, u32), id: &str, count: u32) -> MissionEvent {
+        MissionEvent::Delivered {
+            station,
+            id: id.to_string(),
+            count,
+        }
+    }
+
+    fn template(objectives: Vec<ObjectiveTemplate>, credits: [u64; 2]) -> MissionTemplate {
+        MissionTemplate {
+            id: "test".to_string(),
+            objectives,
+            credits,
+            items: Vec::new(),
+            time_limit: None,
+        }
+    }
+
+    #[test]
+    fn template_ranges() {
+        let mine = |count| ObjectiveTemplate::Mine {
+            id: "metal".to_string(),
+            count,
+        };
+        let reach = |distance, radius| ObjectiveTemplate::Reach { distance, radius };
+
+        assert!(
+            template(vec![mine([1, 3]), reach([5.0, 10.0], 2.0)], [10, 20])
+                .validate()
+                .is_ok()
+        );
+        assert!(template(vec![mine([3, 1])], [10, 20]).validate().is_err());
+        assert!(template(vec![mine([1, 3])], [20, 10]).validate().is_err());
+        assert!(template(vec![reach([10.0, 5.0], 2.0)], [10, 20])
+            .validate()
+            .is_err());
+        assert!(template(vec![reach([5.0, 10.0], 0.0)], [10, 20])
+            .validate()
+            .is_err());
+    }
+
+    #[test]
+    fn progress_and_completion() {
+        let mut missions = Missions::default();
+
+        assert!(missions.accept(mission(
+            "a",
+            vec![
+                ObjectiveKind::Mine {
+                    id: "metal".to_string(),
+                    count: 2,
+                },
+                ObjectiveKind::Reach {
+                    position: [10.0, 10.0],
+                    radius: 2.0,
+                },
+            ],
+            None,
+        )));
+
+        assert!(!missions.handle(&mined("ice")));
+        assert!(missions.handle(&mined("metal")));
+        assert!(missions.handle(&mined("metal")));
+        assert!(!missions.handle(&mined("metal")));
+        assert_eq!(missions.active[0].objectives[0].progress, 2);
+        assert_eq!(missions.active[0].state, MissionState::Active);
+
+        assert!(!missions.handle(&MissionEvent::Moved {
+            position: Vector2::new(20.0, 20.0),
+        }));
+        assert!(missions.handle(&MissionEvent::Moved {
+            position: Vector2::new(11.0, 10.0),
+        }));
+        assert_eq!(missions.active[0].state, MissionState::Completed);
+
+        let finished = missions.tick(0.0);
+
+        assert_eq!(finished.len(), 1);
+        assert!(missions.active.is_empty());
+        assert_eq!(missions.completed, 1);
+        assert_eq!(missions.failed, 0);
+    }
+
+    #[test]
+    fn time_limit_failure() {
+        let mut missions = Missions::default();
+        let objective = ObjectiveKind::Destroy {
+            kind: "miner".to_string(),
+            count: 1,
+        };
+
+        missions.accept(mission("a", vec![objective.clone()], Some(5.0)));
+        missions.accept(mission("b", vec![objective], None));
+
+        assert!(missions.tick(3.0).is_empty());
+
+        let finished = missions.tick(2.0);
+
+        assert_eq!(finished.len(), 1);
+        assert_eq!(finished[0].key, "a");
+        assert_eq!(finished[0].state, MissionState::Failed);
+        assert_eq!(missions.active.len(), 1);
+        assert_eq!(missions.failed, 1);
+        assert_eq!(missions.completed, 0);
+    }
+
+    #[test]
+    fn finished_missions_ignore_events() {
+        let mut failed = mission(
+            "a",
+            vec![ObjectiveKind::Mine {
+                id: "metal".to_string(),
+                count: 1,
+            }],
+            Some(1.0),
+        );
+
+        failed.tick(1.0);
+
+        assert_eq!(failed.state, MissionState::Failed);
+        assert!(!failed.handle(&mined("metal")));
+        assert_eq!(failed.objectives[0].progress, 0);
+    }
+
+    #[test]
+    fn deliver_matches_station() {
+        let mut missions = Missions::default();
+
+        missions.accept(mission(
+            "a",
+            vec![ObjectiveKind::Deliver {
+                id: "metal".to_string(),
+                count: 10,
+                station: [3, 4],
+            }],
+            None,
+        ));
+
+        assert!(!missions.handle(&delivered((4, 3), "metal", 5)));
+        assert!(!missions.handle(&delivered((3, 4), "ice", 5)));
+        assert_eq!(missions.active[0].objectives[0].progress, 0);
+
+        assert!(missions.handle(&delivered((3, 4), "metal", 6)));
+        assert_eq!(missions.active[0].objectives[0].progress, 6);
+        assert_eq!(missions.active[0].state, MissionState::Active);
+
+        assert!(missions.handle(&delivered((3, 4), "metal", 6)));
+        assert_eq!(missions.active[0].objectives[0].progress, 10);
+        assert_eq!(missions.active[0].state, MissionState::Completed);
+    }
+}
@@ -0,0 +1,323 @@
+use crate::{
+    crafting_manager::Recipe,
+    exploration::SENSOR_RANGE,
+    game_manager::{PLAYER_ACCEL, PLAYER_DECCEL_MUL, PLAYER_MAX_SPEED},
+    health::{Health, PLAYER_HEALTH},
+    inventory::{Inventory, ItemStack, INVENTORY_SIZE},
+    mining_manager::{Miner, MINING_RANGE, MINING_SPEED},
+    tag::TagIndex,
+};
+use hex::{
+    anyhow,
+    parking_lot::RwLock,
+    winit::event::{Event, WindowEvent},
+    world::{system_manager::System, World},
+    Context, Control, Id,
+};
+use serde_derive::{Deserialize, Serialize};
+use std::{collections::BTreeMap, fs, path::Path, sync::Arc};
+
+pub const MODULES_FILE: &str = "data/modules.json";
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum SlotKind {
+    Engine,
+    MiningLaser,
+    CargoHold,
+    Weapon,
+    Shield,
+    Scanner,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum Stat {
+    Accel,
+    MaxSpeed,
+    DeccelMul,
+    MiningSpeed,
+    MiningRange,
+    Cargo,
+    WeaponDamage,
+    FireRate,
+    Shield,
+    ScannerRange,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ModuleDef {
+    pub id: String,
+    pub slot: SlotKind,
+    pub tier: u32,
+    #[serde(default)]
+    pub add: BTreeMap<Stat, f32>,
+    #[serde(default)]
+    pub mul: BTreeMap<Stat, f32>,
+    #[serde(default)]
+    pub cost: Vec<ItemStack>,
+    #[serde(default)]
+    pub time: f32,
+    #[serde(default)]
+    pub station: Option<String>,
+}
+
+#[derive(Default, Debug)]
+pub struct Modules {
+    pub modules: BTreeMap<String, ModuleDef>,
+}
+
+impl Modules {
+    pub fn load<P>(path: P) -> anyhow::Result<Arc<RwLock<Self>>>
+    where
+        P: AsRef<Path>,
+    {
+        let path = path.as_ref();
+        let list: Vec<ModuleDef> = if Path::exists(path) {
+            serde_json::from_str(fs::read_to_string(path)?.as_str())?
+        } else {
+            Vec::new()
+        };
+
+        Ok(Arc::new(RwLock::new(Self {
+            modules: list.into_iter().map(|m| (m.id.clone(), m)).collect(),
+        })))
+    }
+
+    pub fn get(&self, id: &str) -> Option<&ModuleDef> {
+        self.modules.get(id)
+    }
+
+    pub fn recipes(&self) -> impl Iterator<Item = Recipe> + '_ {
+        self.modules
+            .values()
+            .filter(|m| !m.cost.is_empty())
+            .map(|m| Recipe {
+                id: m.id.clone(),
+                inputs: m.cost.clone(),
+                outputs: vec![ItemStack {
+                    id: m.id.clone(),
+                    count: 1,
+                }],
+                time: m.time,
+                station: m.station.clone(),
+            })
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Default, Debug)]
+pub struct Loadout {
+    pub slots: BTreeMap<SlotKind, String>,
+    #[serde(skip)]
+    pub revision: u64,
+}
+
+impl Loadout {
+    pub fn new(loadout: Self) -> Arc<RwLock<Self>> {
+        Arc::new(RwLock::new(loadout))
+    }
+
+    pub fn starter() -> Self {
+        Self {
+            slots: [
+                (SlotKind::Engine, "engine_mk1".to_string()),
+                (SlotKind::MiningLaser, "laser_mk1".to_string()),
+                (SlotKind::CargoHold, "cargo_mk1".to_string()),
+                (SlotKind::Scanner, "scanner_mk1".to_string()),
+            ]
+            .into(),
+            revision: 0,
+        }
+    }
+
+    pub fn install(&mut self, module: &ModuleDef, inventory: &mut Inventory) -> bool {
+        if !inventory.remove(&module.id, 1) {
+            return false;
+        }
+
+        if let Some(old) = self.slots.insert(module.slot, module.id.clone()) {
+            inventory.add(&old, 1);
+        }
+
+        self.revision += 1;
+
+        true
+    }
+
+    pub fn uninstall(&mut self, slot: SlotKind, inventory: &mut Inventory) -> bool {
+        match self.slots.get(&slot) {
+            Some(id) if inventory.space_for(id) > 0 => {
+                inventory.add(id, 1);
+                self.slots.remove(&slot);
+                self.revision += 1;
+
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct ShipStats {
+    pub accel: f32,
+    pub max_speed: f32,
+    pub deccel_mul: f32,
+    pub mining_speed: f32,
+    pub mining_range: f32,
+    pub cargo: f32,
+    pub weapon_damage: f32,
+    pub fire_rate: f32,
+    pub shield: f32,
+    pub scanner_range: f32,
+}
+
+impl Default for ShipStats {
+    fn default() -> Self {
+        Self {
+            accel: PLAYER_ACCEL,
+            max_speed: PLAYER_MAX_SPEED,
+            deccel_mul: PLAYER_DECCEL_MUL,
+            mining_speed: MINING_SPEED,
+            mining_range: MINING_RANGE,
+            cargo: INVENTORY_SIZE as f32,
+            weapon_damage: 0.0,
+            fire_rate: 0.0,
+            shield: 0.0,
+            scanner_range: SENSOR_RANGE as f32,
+        }
+    }
+}
+
+impl ShipStats {
+    pub fn new() -> Arc<RwLock<Self>> {
+        Arc::new(RwLock::new(Self::default()))
+    }
+
+    pub fn get_mut(&mut self, stat: Stat) -> &mut f32 {
+        match stat {
+            Stat::Accel => &mut self.accel,
+            Stat::MaxSpeed => &mut self.max_speed,
+            Stat::DeccelMul => &mut self.deccel_mul,
+            Stat::MiningSpeed => &mut self.mining_speed,
+            Stat::MiningRange => &mut self.mining_range,
+            Stat::Cargo => &mut self.cargo,
+            Stat::WeaponDamage => &mut self.weapon_damage,
+            Stat::FireRate => &mut self.fire_rate,
+            Stat::Shield => &mut self.shield,
+            Stat::ScannerRange => &mut self.scanner_range,
+        }
+    }
+
+    pub fn compute(loadout: &Loadout, modules: &Modules) -> Self {
+        let mut stats = Self::default();
+        let installed: Vec<_> = loadout
+            .slots
+            .values()
+            .filter_map(|id| modules.get(id))
+            .collect();
+
+        for module in &installed {
+            for (stat, value) in &module.add {
+                *stats.get_mut(*stat) += value;
+            }
+        }
+
+        for module in &installed {
+            for (stat, value) in &module.mul {
+                *stats.get_mut(*stat) *= value;
+            }
+        }
+
+        stats
+    }
+}
+
+pub struct ModuleManager {
+    pub player: Option<Id>,
+    pub tags: Arc<RwLock<TagIndex>>,
+    pub modules: Arc<RwLock<Modules>>,
+    pub revision: Option<u64>,
+}
+
+impl ModuleManager {
+    pub fn new(modules: Arc<RwLock<Modules>>, tags: Arc<RwLock<TagIndex>>) -> Self {
+        Self {
+            player: None,
+            tags,
+            modules,
+            revision: None,
+        }
+    }
+}
+
+impl System for ModuleManager {
+    fn init(&mut self, _: Arc<RwLock<Context>>, _: Arc<RwLock<World>>) -> anyhow::Result<()> {
+        self.player = self.tags.read().first("player");
+
+        Ok(())
+    }
+
+    fn update(
+        &mut self,
+        control: Arc<RwLock<Control>>,
+        context: Arc<RwLock<Context>>,
+        world: Arc<RwLock<World>>,
+    ) -> anyhow::Result<()> {
+        let event = control.read().event.clone();
+
+        if let Event::WindowEvent {
+            event: WindowEvent::RedrawRequested,
+            window_id,
+        } = event
+        {
+            if window_id != context.read().window.id() {
+                return Ok(());
+            }
+
+            let Some(player) = self.player else {
+                return Ok(());
+            };
+            let em = world.read().em.clone();
+            let em = em.read();
+            let (Some(loadout), Some(stats)) = (
+                em.get_component::<Loadout>(player),
+                em.get_component::<ShipStats>(player),
+            ) else {
+                return Ok(());
+            };
+            let loadout = loadout.read();
+
+            if self.revision == Some(loadout.revision) {
+                return Ok(());
+            }
+
+            self.revision = Some(loadout.revision);
+
+            let computed = ShipStats::compute(&loadout, &self.modules.read());
+
+            if let Some(miner) = em.get_component::<Miner>(player) {
+                let mut miner = miner.write();
+
+                miner.speed = computed.mining_speed;
+                miner.range = computed.mining_range;
+            }
+
+            if let Some(inventory) = em.get_component::<Inventory>(player) {
+                inventory.write().resize(computed.cargo.max(0.0) as usize);
+            }
+
+            if let Some(health) = em.get_component::<Health>(player) {
+                let mut health = health.write();
+                let max = PLAYER_HEALTH + computed.shield;
+
+                health.current = (health.current + (max - health.max)).clamp(0.0, max);
+                health.max = max;
+            }
+
+            *stats.write() = computed;
+        }
+
+        Ok(())
+    }
+}
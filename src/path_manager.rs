@@ -0,0 +1,584 @@
+use crate::{
+    chunk_manager::{TileMap, METAL},
+    events::{EventBus, GameEvent, Subscriber},
+};
+use hex::{
+    anyhow,
+    parking_lot::RwLock,
+    winit::event::{Event, WindowEvent},
+    world::{system_manager::System, World},
+    Context, Control,
+};
+use std::{
+    cmp::Reverse,
+    collections::{BinaryHeap, HashMap, HashSet, VecDeque},
+    sync::Arc,
+};
+
+pub const PATH_BUDGET: usize = 4096;
+pub const PATH_REQUESTS_PER_FRAME: usize = 4;
+pub const PATH_CACHE_SIZE: usize = 256;
+pub const STRAIGHT_COST: u32 = 10;
+pub const DIAGONAL_COST: u32 = 14;
+pub const DIRECTIONS: [(i64, i64); 8] = [
+    (1, 0),
+    (-1, 0),
+    (0, 1),
+    (0, -1),
+    (1, 1),
+    (1, -1),
+    (-1, 1),
+    (-1, -1),
+];
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Cell {
+    Open,
+    Blocked,
+    Unknown,
+}
+
+pub trait Grid {
+    fn cell(&self, tile: (u32, u32)) -> Cell;
+}
+
+impl Grid for TileMap {
+    fn cell(&self, tile: (u32, u32)) -> Cell {
+        if !self.is_loaded(tile) {
+            Cell::Unknown
+        } else if self.get(tile).is_some_and(|id| id == METAL) {
+            Cell::Blocked
+        } else {
+            Cell::Open
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct PathConfig {
+    pub budget: usize,
+    pub cut_corners: bool,
+    pub unknown_passable: bool,
+}
+
+impl Default for PathConfig {
+    fn default() -> Self {
+        Self {
+            budget: PATH_BUDGET,
+            cut_corners: false,
+            unknown_passable: false,
+        }
+    }
+}
+
+impl PathConfig {
+    pub fn passable(&self, cell: Cell) -> bool {
+        match cell {
+            Cell::Open => true,
+            Cell::Blocked => false,
+            Cell::Unknown => self.unknown_passable,
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum PathResult {
+    Found(Vec<(u32, u32)>),
+    NoPath,
+    BudgetExceeded,
+}
+
+#[derive(Clone, Debug)]
+pub struct Search {
+    pub result: PathResult,
+    pub chunks: HashSet<(u32, u32)>,
+}
+
+struct Probe<'a, G: Grid> {
+    grid: &'a G,
+    config: &'a PathConfig,
+    chunks: HashSet<(u32, u32)>,
+}
+
+impl<G: Grid> Probe<'_, G> {
+    fn passable(&mut self, tile: (u32, u32)) -> bool {
+        self.chunks.insert(TileMap::tile_chunk(tile).0);
+        self.config.passable(self.grid.cell(tile))
+    }
+
+    fn finish(self, result: PathResult) -> Search {
+        Search {
+            result,
+            chunks: self.chunks,
+        }
+    }
+}
+
+fn offset((x, y): (u32, u32), dx: i64, dy: i64) -> Option<(u32, u32)> {
+    Some((
+        u32::try_from(x as i64 + dx).ok()?,
+        u32::try_from(y as i64 + dy).ok()?,
+    ))
+}
+
+fn heuristic((ax, ay): (u32, u32), (bx, by): (u32, u32)) -> u32 {
+    let dx = ax.abs_diff(bx);
+    let dy = ay.abs_diff(by);
+
+    STRAIGHT_COST * dx.max(dy) + (DIAGONAL_COST - STRAIGHT_COST) * dx.min(dy)
+}
+
+pub fn find_path<G: Grid>(
+    grid: &G,
+    start: (u32, u32),
+    goal: (u32, u32),
+    config: &PathConfig,
+) -> Search {
+    let mut probe = Probe {
+        grid,
+        config,
+        chunks: HashSet::new(),
+    };
+
+    if !probe.passable(goal) {
+        return probe.finish(PathResult::NoPath);
+    }
+
+    let mut open = BinaryHeap::new();
+    let mut closed = HashSet::new();
+    let mut came = HashMap::new();
+    let mut cost = HashMap::from([(start, 0)]);
+    let mut expanded = 0;
+
+    open.push(Reverse((heuristic(start, goal), start)));
+
+    while let Some(Reverse((_, tile))) = open.pop() {
+        if tile == goal {
+            let mut path = vec![tile];
+            let mut current = tile;
+
+            while let Some(prev) = came.get(&current) {
+                current = *prev;
+                path.push(current);
+            }
+
+            path.reverse();
+
+            return probe.finish(PathResult::Found(path));
+        }
+
+        if !closed.insert(tile) {
+            continue;
+        }
+
+        expanded += 1;
+
+        if expanded > config.budget {
+            return probe.finish(PathResult::BudgetExceeded);
+        }
+
+        let g = cost[&tile];
+
+        for (dx, dy) in DIRECTIONS {
+            let Some(next) = offset(tile, dx, dy) else {
+                continue;
+            };
+
+            if closed.contains(&next) || !probe.passable(next) {
+                continue;
+            }
+
+            let diagonal = dx != 0 && dy != 0;
+
+            if diagonal {
+                let a = offset(tile, dx, 0).is_some_and(|t| probe.passable(t));
+                let b = offset(tile, 0, dy).is_some_and(|t| probe.passable(t));
+
+                if !(a && b) && !(config.cut_corners && (a || b)) {
+                    continue;
+                }
+            }
+
+            let next_cost = g + if diagonal {
+                DIAGONAL_COST
+            } else {
+                STRAIGHT_COST
+            };
+
+            if cost.get(&next).is_some_and(|c| *c <= next_cost) {
+                continue;
+            }
+
+            cost.insert(next, next_cost);
+            came.insert(next, tile);
+            open.push(Reverse((next_cost + heuristic(next, goal), next)));
+        }
+    }
+
+    probe.finish(PathResult::NoPath)
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct PathTicket(u64);
+
+#[derive(Default)]
+pub struct Pathfinder {
+    pub config: PathConfig,
+    pub pending: VecDeque<(PathTicket, (u32, u32), (u32, u32))>,
+    pub ready: HashMap<PathTicket, PathResult>,
+    pub cache: HashMap<((u32, u32), (u32, u32)), Search>,
+    next: u64,
+}
+
+impl Pathfinder {
+    pub fn new(config: PathConfig) -> Arc<RwLock<Self>> {
+        Arc::new(RwLock::new(Self {
+            config,
+            ..Default::default()
+        }))
+    }
+
+    pub fn request(&mut self, start: (u32, u32), goal: (u32, u32)) -> PathTicket {
+        let ticket = PathTicket(self.next);
+
+        self.next += 1;
+
+        match self.cache.get(&(start, goal)) {
+            Some(search) => {
+                self.ready.insert(ticket, search.result.clone());
+            }
+            None => self.pending.push_back((ticket, start, goal)),
+        }
+
+        ticket
+    }
+
+    pub fn poll(&mut self, ticket: PathTicket) -> Option<PathResult> {
+        self.ready.remove(&ticket)
+    }
+
+    pub fn cancel(&mut self, ticket: PathTicket) {
+        self.pending.retain(|(t, ..)| *t != ticket);
+        self.ready.remove(&ticket);
+    }
+
+    pub fn process<G: Grid>(&mut self, grid: &G, count: usize) {
+        for _ in 0..count {
+            let Some((ticket, start, goal)) = self.pending.pop_front() else {
+                break;
+            };
+            let result = match self.cache.get(&(start, goal)) {
+                Some(search) => search.result.clone(),
+                None => {
+                    let search = find_path(grid, start, goal, &self.config);
+                    let result = search.result.clone();
+
+                    if self.cache.len() >= PATH_CACHE_SIZE {
+                        self.cache.clear();
+                    }
+
+                    self.cache.insert((start, goal), search);
+
+                    result
+                }
+            };
+
+            self.ready.insert(ticket, result);
+        }
+    }
+
+    pub fn invalidate(&mut self, chunk: (u32, u32)) {
+        self.cache
+            .retain(|_, search| !search.chunks.contains(&chunk));
+    }
+}
+
+pub struct PathManager {
+    pub pathfinder: Arc<RwLock<Pathfinder>>,
+    pub tile_map: Arc<RwLock<TileMap>>,
+    pub events: Arc<RwLock<EventBus>>,
+    pub subscriber: Subscriber,
+}
+
+impl PathManager {
+    pub fn new(
+        pathfinder: Arc<RwLock<Pathfinder>>,
+        tile_map: Arc<RwLock<TileMap>>,
+        events: Arc<RwLock<EventBus>>,
+    ) -> Self {
+        let subscriber = events.write().subscribe();
+
+        Self {
+            pathfinder,
+            tile_map,
+            events,
+            subscriber,
+        }
+    }
+}
+
+impl System for PathManager {
+    fn update(
+        &mut self,
+        control: Arc<RwLock<Control>>,
+        context: Arc<RwLock<Context>>,
+        _: Arc<RwLock<World>>,
+    ) -> anyhow::Result<()> {
+        let event = control.read().event.clone();
+
+        if let Event::WindowEvent {
+            event: WindowEvent::RedrawRequested,
+            window_id,
+        } = event
+        {
+            if window_id != context.read().window.id() {
+                return Ok(());
+            }
+
+            let events = self.events.write().poll(self.subscriber);
+            let mut pathfinder = self.pathfinder.write();
+
+            for event in events {
+                match event {
+                    GameEvent::TileChanged { tile, .. } => {
+                        pathfinder.invalidate(TileMap::tile_chunk(tile).0);
+                    }
+                    GameEvent::ChunkLoaded { chunk } | GameEvent::ChunkUnloaded { chunk } => {
+                        pathfinder.invalidate(chunk);
+                    }
+                    _ => {}
+                }
+            }
+
+            pathfinder.process(&*self.tile_map.read(), PATH_REQUESTS_PER_FRAME);
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chunk_manager::{ChunkData, ASTEROID_1, CHUNK_SIZE};
+
+    #[derive(Default)]
+    struct TestGrid {
+        size: u32,
+        blocked: HashSet<(u32, u32)>,
+        unknown: HashSet<(u32, u32)>,
+    }
+
+    impl TestGrid {
+        fn new(size: u32) -> Self {
+            Self {
+                size,
+                ..Default::default()
+            }
+        }
+
+        fn blocked<I>(mut self, tiles: I) -> Self
+        where
+            I: IntoIterator<Item = (u32, u32)>,
+        {
+            self.blocked.extend(tiles);
+            self
+        }
+
+        fn unknown<I>(mut self, tiles: I) -> Self
+        where
+            I: IntoIterator<Item = (u32, u32)>,
+        {
+            self.unknown.extend(tiles);
+            self
+        }
+    }
+
+    impl Grid for TestGrid {
+        fn cell(&self, tile @ (x, y): (u32, u32)) -> Cell {
+            if x >= self.size || y >= self.size || self.blocked.contains(&tile) {
+                Cell::Blocked
+            } else if self.unknown.contains(&tile) {
+                Cell::Unknown
+            } else {
+                Cell::Open
+            }
+        }
+    }
+
+    fn column(x: u32, ys: std::ops::Range<u32>) -> impl Iterator<Item = (u32, u32)> {
+        ys.map(move |y| (x, y))
+    }
+
+    fn path(result: &PathResult) -> &[(u32, u32)] {
+        match result {
+            PathResult::Found(path) => path,
+            result => panic!("expected a path, got {result:?}"),
+        }
+    }
+
+    fn assert_walkable(grid: &TestGrid, path: &[(u32, u32)]) {
+        for pair in path.windows(2) {
+            let (a, b) = (pair[0], pair[1]);
+
+            assert!(a.0.abs_diff(b.0) <= 1 && a.1.abs_diff(b.1) <= 1);
+            assert_ne!(a, b);
+        }
+
+        for tile in path {
+            assert_eq!(grid.cell(*tile), Cell::Open);
+        }
+    }
+
+    #[test]
+    fn straight_path() {
+        let grid = TestGrid::new(10);
+        let search = find_path(&grid, (1, 5), (6, 5), &PathConfig::default());
+
+        assert_eq!(
+            path(&search.result),
+            &[(1, 5), (2, 5), (3, 5), (4, 5), (5, 5), (6, 5)]
+        );
+    }
+
+    #[test]
+    fn routes_around_wall() {
+        let grid = TestGrid::new(10).blocked(column(4, 0..8));
+        let search = find_path(&grid, (1, 1), (7, 1), &PathConfig::default());
+        let path = path(&search.result);
+
+        assert_eq!(path.first(), Some(&(1, 1)));
+        assert_eq!(path.last(), Some(&(7, 1)));
+        assert!(path.iter().any(|(x, y)| *x == 4 && *y >= 8));
+        assert_walkable(&grid, path);
+    }
+
+    #[test]
+    fn blocked_goal_has_no_path() {
+        let grid = TestGrid::new(10).blocked([(5, 5)]);
+        let search = find_path(&grid, (1, 1), (5, 5), &PathConfig::default());
+
+        assert_eq!(search.result, PathResult::NoPath);
+    }
+
+    #[test]
+    fn tile_map_blocks_metal_only() {
+        let mut grid = vec![vec![None; CHUNK_SIZE as usize]; CHUNK_SIZE as usize];
+
+        grid[4][3] = Some(METAL.to_string());
+        grid[5][3] = Some(ASTEROID_1.to_string());
+
+        let mut tile_map = TileMap::default();
+
+        tile_map.chunks.insert(
+            (0, 0),
+            ChunkData {
+                position: [0.0, 0.0],
+                grid,
+                hazards: Vec::new(),
+            },
+        );
+
+        assert_eq!(tile_map.cell((4, 3)), Cell::Blocked);
+        assert_eq!(tile_map.cell((5, 3)), Cell::Open);
+        assert_eq!(tile_map.cell((6, 3)), Cell::Open);
+        assert_eq!(tile_map.cell((CHUNK_SIZE + 1, 3)), Cell::Unknown);
+    }
+
+    #[test]
+    fn corner_cutting() {
+        let grid = TestGrid::new(10).blocked([(2, 1)]);
+        let strict = PathConfig::default();
+        let loose = PathConfig {
+            cut_corners: true,
+            ..PathConfig::default()
+        };
+
+        assert_eq!(
+            path(&find_path(&grid, (1, 1), (2, 2), &strict).result),
+            &[(1, 1), (1, 2), (2, 2)]
+        );
+        assert_eq!(
+            path(&find_path(&grid, (1, 1), (2, 2), &loose).result),
+            &[(1, 1), (2, 2)]
+        );
+
+        let squeeze = TestGrid::new(10).blocked([(2, 1), (1, 2)]);
+        let search = find_path(&squeeze, (1, 1), (2, 2), &loose);
+        let path = path(&search.result);
+
+        assert_ne!(path.get(1), Some(&(2, 2)));
+        assert_walkable(&squeeze, path);
+    }
+
+    #[test]
+    fn unknown_cells() {
+        let grid = TestGrid::new(10).unknown(column(4, 0..10));
+        let blocked = PathConfig::default();
+        let passable = PathConfig {
+            unknown_passable: true,
+            ..PathConfig::default()
+        };
+
+        assert_eq!(
+            find_path(&grid, (1, 1), (7, 1), &blocked).result,
+            PathResult::NoPath
+        );
+
+        let search = find_path(&grid, (1, 1), (7, 1), &passable);
+
+        assert!(path(&search.result).iter().any(|(x, _)| *x == 4));
+    }
+
+    #[test]
+    fn budget_exceeded() {
+        let grid = TestGrid::new(10).blocked(column(4, 0..9));
+        let config = PathConfig {
+            budget: 3,
+            ..PathConfig::default()
+        };
+
+        assert_eq!(
+            find_path(&grid, (1, 1), (7, 1), &config).result,
+            PathResult::BudgetExceeded
+        );
+    }
+
+    #[test]
+    fn cache_invalidation() {
+        let open = TestGrid::new(10);
+        let walled = TestGrid::new(10).blocked(column(4, 0..10));
+        let mut pathfinder = Pathfinder::default();
+        let ticket = pathfinder.request((1, 1), (7, 1));
+
+        assert_eq!(pathfinder.poll(ticket), None);
+
+        pathfinder.process(&open, PATH_REQUESTS_PER_FRAME);
+
+        let first = pathfinder.poll(ticket).unwrap();
+
+        assert!(matches!(first, PathResult::Found(_)));
+
+        let cached = pathfinder.request((1, 1), (7, 1));
+
+        assert_eq!(pathfinder.poll(cached), Some(first.clone()));
+
+        pathfinder.invalidate((5, 5));
+
+        let cached = pathfinder.request((1, 1), (7, 1));
+
+        assert_eq!(pathfinder.poll(cached), Some(first));
+
+        pathfinder.invalidate((0, 0));
+
+        assert!(pathfinder.cache.is_empty());
+
+        let ticket = pathfinder.request((1, 1), (7, 1));
+
+        assert_eq!(pathfinder.poll(ticket), None);
+
+        pathfinder.process(&walled, PATH_REQUESTS_PER_FRAME);
+
+        assert_eq!(pathfinder.poll(ticket), Some(PathResult::NoPath));
+    }
+}
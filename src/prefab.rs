@@ -0,0 +1,529 @@
+use crate::{
+    chunk_manager::SAVE_DIR, drop_manager::ItemDrop, game_manager::Player,
+    hazard_manager::Environment, health::Health, inventory::Inventory, map_manager::MapMarker,
+    station_manager::Wallet, tag::TagIndex, util,
+};
+use hex::{
+    anyhow,
+    assets::Shape,
+    components::Trans,
+    nalgebra::{Vector2, Vector4},
+    parking_lot::RwLock,
+    world::EntityManager,
+    Context, Id,
+};
+use hex_instance::components::Instance;
+use serde::{de::DeserializeOwned, Serialize};
+use serde_derive::{Deserialize, Serialize};
+use serde_json::Value;
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap},
+    fs,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+pub const PREFABS_FILE: &str = "data/prefabs.json";
+pub const ENTITIES_FILE: &str = "entities.json";
+
+type SaveFn = Box<dyn Fn(&EntityManager, Id) -> anyhow::Result<Option<Value>> + Send + Sync>;
+type LoadFn = Box<dyn Fn(&mut EntityManager, Id, Value) -> anyhow::Result<()> + Send + Sync>;
+
+#[derive(Serialize, Deserialize, Clone, Default, Debug)]
+pub struct EntityData {
+    pub components: BTreeMap<String, Value>,
+}
+
+impl EntityData {
+    pub fn with<T>(mut self, name: &str, value: &T) -> anyhow::Result<Self>
+    where
+        T: Serialize,
+    {
+        self.components
+            .insert(name.to_string(), serde_json::to_value(value)?);
+
+        Ok(self)
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug)]
+pub struct TransData {
+    pub position: [f32; 2],
+    #[serde(default)]
+    pub rotation: f32,
+    #[serde(default = "TransData::unit")]
+    pub scale: [f32; 2],
+}
+
+impl TransData {
+    fn unit() -> [f32; 2] {
+        [1.0, 1.0]
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug)]
+pub struct HealthData {
+    pub current: f32,
+    pub max: f32,
+    #[serde(default)]
+    pub invulnerability: f32,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct Sprite {
+    pub texture: String,
+    #[serde(default = "Sprite::white")]
+    pub color: [f32; 4],
+    #[serde(default = "TransData::unit")]
+    pub size: [f32; 2],
+    #[serde(default)]
+    pub layer: i32,
+}
+
+impl Sprite {
+    fn white() -> [f32; 4] {
+        [1.0; 4]
+    }
+
+    pub fn key(&self) -> String {
+        format!(
+            "{}:{:?}:{:?}:{}",
+            self.texture, self.color, self.size, self.layer
+        )
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Default, Debug)]
+pub struct Persistent;
+
+impl Persistent {
+    pub fn new() -> Arc<RwLock<Self>> {
+        Arc::new(RwLock::new(Self))
+    }
+}
+
+pub struct ComponentEntry {
+    pub save: SaveFn,
+    pub load: LoadFn,
+}
+
+#[derive(Default)]
+pub struct ComponentRegistry {
+    pub components: BTreeMap<String, ComponentEntry>,
+    pub persistent: Arc<RwLock<BTreeSet<Id>>>,
+    pub tags: Arc<RwLock<TagIndex>>,
+}
+
+impl ComponentRegistry {
+    pub fn new(context: Arc<RwLock<Context>>, tags: Arc<RwLock<TagIndex>>) -> Arc<RwLock<Self>> {
+        let mut registry = Self::with_tags(tags);
+        let instances = RwLock::new(HashMap::<String, Arc<RwLock<Instance>>>::new());
+
+        registry.register(
+            "sprite",
+            |s: &Sprite| s.clone(),
+            move |em, e, sprite: Sprite| {
+                let context = context.read_recursive();
+                let key = sprite.key();
+                let cached = instances.read().get(&key).cloned();
+                let instance = match cached {
+                    Some(instance) => instance,
+                    None => {
+                        let instance = Instance::new(
+                            &context,
+                            Arc::new(Shape::rect(&context, sprite.size.into())?),
+                            Arc::new(util::load_texture(&context, &sprite.texture)?),
+                            Vector4::from(sprite.color),
+                            sprite.layer,
+                        )?;
+
+                        instances.write().insert(key, instance.clone());
+
+                        instance
+                    }
+                };
+
+                em.add_component(e, instance);
+                em.add_component(e, Arc::new(RwLock::new(sprite)));
+
+                Ok(())
+            },
+        );
+
+        Arc::new(RwLock::new(registry))
+    }
+
+    pub fn with_tags(tags: Arc<RwLock<TagIndex>>) -> Self {
+        let mut registry = Self {
+            tags: tags.clone(),
+            ..Default::default()
+        };
+
+        registry.insert(
+            "tag",
+            {
+                let tags = tags.clone();
+
+                Box::new(move |_, e| Ok(tags.read().tag_of(e).map(Value::from)))
+            },
+            Box::new(move |em, e, value| {
+                tags.write()
+                    .tag(em, e, serde_json::from_value::<String>(value)?);
+
+                Ok(())
+            }),
+        );
+        registry.register(
+            "trans",
+            |t: &Trans| TransData {
+                position: t.position().into(),
+                rotation: t.rotation(),
+                scale: t.scale().into(),
+            },
+            |em, e, t: TransData| {
+                em.add_component(e, Trans::new(t.position.into(), t.rotation, t.scale.into()));
+
+                Ok(())
+            },
+        );
+        registry.register(
+            "player",
+            |p: &Player| <[f32; 2]>::from(p.velocity),
+            |em, e, velocity: [f32; 2]| {
+                em.add_component(
+                    e,
+                    Arc::new(RwLock::new(Player {
+                        velocity: velocity.into(),
+                        ..Default::default()
+                    })),
+                );
+
+                Ok(())
+            },
+        );
+        registry.register(
+            "health",
+            |h: &Health| HealthData {
+                current: h.current,
+                max: h.max,
+                invulnerability: h.invulnerability,
+            },
+            |em, e, h: HealthData| {
+                let health = Health::with_invulnerability(h.max, h.invulnerability);
+
+                health.write().current = h.current.min(h.max);
+                em.add_component(e, health);
+
+                Ok(())
+            },
+        );
+        registry.register(
+            "wallet",
+            |w: &Wallet| w.credits,
+            |em, e, credits: u64| {
+                em.add_component(e, Wallet::new(credits));
+
+                Ok(())
+            },
+        );
+        registry.register(
+            "map_marker",
+            |m: &MapMarker| <[f32; 4]>::from(m.color),
+            |em, e, color: [f32; 4]| {
+                em.add_component(e, MapMarker::new(color.into()));
+
+                Ok(())
+            },
+        );
+        registry.register(
+            "environment",
+            |_: &Environment| (),
+            |em, e, _: ()| {
+                em.add_component(e, Environment::new());
+
+                Ok(())
+            },
+        );
+        registry.register("persistent", |_: &Persistent| (), {
+            let persistent = registry.persistent.clone();
+
+            move |em, e, _: ()| {
+                em.add_component(e, Persistent::new());
+                persistent.write().insert(e);
+
+                Ok(())
+            }
+        });
+        registry.register_serde::<Inventory>("inventory");
+        registry.register_serde::<ItemDrop>("drop");
+
+        registry
+    }
+
+    pub fn register<C, D, S, L>(&mut self, name: &str, save: S, load: L)
+    where
+        C: Send + Sync + 'static,
+        D: Serialize + DeserializeOwned,
+        S: Fn(&C) -> D + Send + Sync + 'static,
+        L: Fn(&mut EntityManager, Id, D) -> anyhow::Result<()> + Send + Sync + 'static,
+    {
+        self.insert(
+            name,
+            Box::new(move |em, e| {
+                em.get_component::<C>(e)
+                    .map(|c| serde_json::to_value(save(&c.read())))
+                    .transpose()
+                    .map_err(Into::into)
+            }),
+            Box::new(move |em, e, value| load(em, e, serde_json::from_value(value)?)),
+        );
+    }
+
+    pub fn register_serde<C>(&mut self, name: &str)
+    where
+        C: Clone + Serialize + DeserializeOwned + Send + Sync + 'static,
+    {
+        self.register(
+            name,
+            |c: &C| c.clone(),
+            |em, e, c: C| {
+                em.add_component(e, Arc::new(RwLock::new(c)));
+
+                Ok(())
+            },
+        );
+    }
+
+    fn insert(&mut self, name: &str, save: SaveFn, load: LoadFn) {
+        self.components
+            .insert(name.to_string(), ComponentEntry { save, load });
+    }
+
+    pub fn save(&self, em: &EntityManager, e: Id) -> anyhow::Result<EntityData> {
+        let mut data = EntityData::default();
+
+        for (name, entry) in &self.components {
+            if let Some(value) = (entry.save)(em, e)? {
+                data.components.insert(name.clone(), value);
+            }
+        }
+
+        Ok(data)
+    }
+
+    pub fn load(&self, em: &mut EntityManager, e: Id, data: &EntityData) -> anyhow::Result<()> {
+        for (name, value) in &data.components {
+            let entry = self
+                .components
+                .get(name)
+                .ok_or_else(|| anyhow::anyhow!("Unknown component \"{name}\""))?;
+
+            (entry.load)(em, e, value.clone())?;
+        }
+
+        Ok(())
+    }
+
+    pub fn spawn(&self, em: &mut EntityManager, data: &EntityData) -> anyhow::Result<Id> {
+        let e = em.add(true);
+
+        if let Err(e2) = self.load(em, e, data) {
+            self.tags.write().rm(em, e);
+
+            return Err(e2);
+        }
+
+        Ok(e)
+    }
+}
+
+#[derive(Default)]
+pub struct Prefabs {
+    pub prefabs: HashMap<String, EntityData>,
+}
+
+impl Prefabs {
+    pub fn load<P>(path: P) -> anyhow::Result<Arc<RwLock<Self>>>
+    where
+        P: AsRef<Path>,
+    {
+        let prefabs = if Path::exists(path.as_ref()) {
+            serde_json::from_str(fs::read_to_string(path)?.as_str())?
+        } else {
+            HashMap::new()
+        };
+
+        Ok(Arc::new(RwLock::new(Self { prefabs })))
+    }
+
+    pub fn get(&self, name: &str) -> Option<&EntityData> {
+        self.prefabs.get(name)
+    }
+
+    pub fn instantiate(
+        &self,
+        name: &str,
+        position: Vector2<f32>,
+        overrides: EntityData,
+    ) -> anyhow::Result<EntityData> {
+        let mut data = self
+            .get(name)
+            .cloned()
+            .ok_or_else(|| anyhow::anyhow!("Unknown prefab \"{name}\""))?;
+        let mut trans = data
+            .components
+            .get("trans")
+            .cloned()
+            .map(serde_json::from_value)
+            .transpose()?
+            .unwrap_or(TransData {
+                position: [0.0; 2],
+                rotation: 0.0,
+                scale: TransData::unit(),
+            });
+
+        trans.position = position.into();
+        data.components.extend(overrides.components);
+        data.components
+            .insert("trans".to_string(), serde_json::to_value(trans)?);
+
+        Ok(data)
+    }
+
+    pub fn spawn(
+        &self,
+        registry: &ComponentRegistry,
+        em: &mut EntityManager,
+        name: &str,
+        position: Vector2<f32>,
+    ) -> anyhow::Result<Id> {
+        let data = self.instantiate(name, position, EntityData::default())?;
+
+        registry.spawn(em, &data)
+    }
+}
+
+#[derive(Serialize, Deserialize, Default)]
+pub struct ChunkEntities {
+    pub entities: Vec<EntityData>,
+}
+
+impl ChunkEntities {
+    pub fn path((x, y): (u32, u32)) -> PathBuf {
+        PathBuf::from(SAVE_DIR)
+            .join("chunks")
+            .join(format!("{x},{y}.{ENTITIES_FILE}"))
+    }
+
+    pub fn read(chunk: (u32, u32)) -> anyhow::Result<Self> {
+        let path = Self::path(chunk);
+
+        if Path::exists(&path) {
+            Ok(serde_json::from_str(fs::read_to_string(path)?.as_str())?)
+        } else {
+            Ok(Self::default())
+        }
+    }
+
+    pub fn write(&self, chunk: (u32, u32)) -> anyhow::Result<()> {
+        let path = Self::path(chunk);
+
+        if self.entities.is_empty() {
+            if Path::exists(&path) {
+                fs::remove_file(path)?;
+            }
+        } else {
+            fs::create_dir_all(PathBuf::from(SAVE_DIR).join("chunks"))?;
+            fs::write(path, serde_json::to_string(self)?)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn drop_data() -> EntityData {
+        EntityData::default()
+            .with("tag", &"drop")
+            .unwrap()
+            .with(
+                "trans",
+                &TransData {
+                    position: [3.5, 7.25],
+                    rotation: 0.5,
+                    scale: [0.5, 0.5],
+                },
+            )
+            .unwrap()
+            .with("wallet", &25u64)
+            .unwrap()
+            .with("persistent", &())
+            .unwrap()
+    }
+
+    #[test]
+    fn chunk_entities_round_trip() {
+        let tags = TagIndex::new();
+        let registry = ComponentRegistry::with_tags(tags.clone());
+        let em = EntityManager::new();
+        let mut em = em.write();
+        let e = registry.spawn(&mut em, &drop_data()).unwrap();
+
+        assert_eq!(tags.read().tag_of(e), Some("drop"));
+        assert!(registry.persistent.read().contains(&e));
+
+        let saved = ChunkEntities {
+            entities: vec![registry.save(&em, e).unwrap()],
+        };
+        let loaded: ChunkEntities =
+            serde_json::from_str(&serde_json::to_string(&saved).unwrap()).unwrap();
+
+        let tags = TagIndex::new();
+        let registry = ComponentRegistry::with_tags(tags.clone());
+        let em = EntityManager::new();
+        let mut em = em.write();
+        let e = registry.spawn(&mut em, &loaded.entities[0]).unwrap();
+        let trans = em.get_component::<Trans>(e).unwrap();
+
+        assert_eq!(tags.read().tag_of(e), Some("drop"));
+        assert_eq!(trans.read().position(), Vector2::new(3.5, 7.25));
+        assert_eq!(em.get_component::<Wallet>(e).unwrap().read().credits, 25);
+        assert!(registry.persistent.read().contains(&e));
+        assert_eq!(
+            registry.save(&em, e).unwrap().components,
+            saved.entities[0].components
+        );
+    }
+
+    #[test]
+    fn unregistered_component_fails_cleanly() {
+        let tags = TagIndex::new();
+        let registry = ComponentRegistry::with_tags(tags.clone());
+        let em = EntityManager::new();
+        let mut em = em.write();
+        let data = drop_data().with("unregistered", &1).unwrap();
+
+        assert!(registry.spawn(&mut em, &data).is_err());
+        assert_eq!(tags.read().count("drop"), 0);
+    }
+
+    #[test]
+    fn save_skips_unregistered_components() {
+        let registry = ComponentRegistry::with_tags(TagIndex::new());
+        let em = EntityManager::new();
+        let mut em = em.write();
+        let e = registry.spawn(&mut em, &drop_data()).unwrap();
+
+        em.add_component(e, Arc::new(RwLock::new(0u32)));
+
+        let saved = registry.save(&em, e).unwrap();
+
+        assert_eq!(
+            saved.components.keys().collect::<Vec<_>>(),
+            ["persistent", "tag", "trans", "wallet"]
+        );
+    }
+}
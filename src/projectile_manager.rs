@@ -0,0 +1,245 @@
+use crate::{
+    chunk_manager::TileMap,
+    events::{EventBus, GameEvent, Subscriber},
+    game_manager::Player,
+    health::{DamageSource, Health},
+    health_manager::HOSTILE_TAGS,
+    prefab::{ComponentRegistry, Prefabs},
+    spatial_manager::SpatialIndex,
+    station_manager::Docking,
+    tag::TagIndex,
+};
+use hex::{
+    anyhow,
+    components::Trans,
+    nalgebra::Vector2,
+    parking_lot::RwLock,
+    winit::event::{Event, WindowEvent},
+    world::{system_manager::System, EntityManager, World},
+    Context, Control, Id,
+};
+use std::{sync::Arc, time::Instant};
+
+pub const PROJECTILE_TAG: &str = "projectile";
+pub const PROJECTILE_SPEED: f32 = 20.0;
+pub const PROJECTILE_DAMAGE: f32 = 10.0;
+pub const PROJECTILE_LIFETIME: f32 = 1.5;
+pub const PROJECTILE_RADIUS: f32 = 0.5;
+pub const FIRE_INTERVAL: f32 = 0.25;
+
+#[derive(Clone, Debug)]
+pub struct Projectile {
+    pub owner: Id,
+    pub velocity: Vector2<f32>,
+    pub damage: f32,
+    pub age: f32,
+}
+
+impl Projectile {
+    pub fn new(owner: Id, velocity: Vector2<f32>, damage: f32) -> Arc<RwLock<Self>> {
+        Arc::new(RwLock::new(Self {
+            owner,
+            velocity,
+            damage,
+            age: 0.0,
+        }))
+    }
+}
+
+pub struct ProjectileManager {
+    pub player: Option<Id>,
+    pub tags: Arc<RwLock<TagIndex>>,
+    pub registry: Arc<RwLock<ComponentRegistry>>,
+    pub prefabs: Arc<RwLock<Prefabs>>,
+    pub tile_map: Arc<RwLock<TileMap>>,
+    pub spatial: Arc<RwLock<SpatialIndex>>,
+    pub events: Arc<RwLock<EventBus>>,
+    pub subscriber: Subscriber,
+    pub firing: bool,
+    pub cooldown: f32,
+    last_frame: Instant,
+}
+
+impl ProjectileManager {
+    pub fn new(
+        tags: Arc<RwLock<TagIndex>>,
+        registry: Arc<RwLock<ComponentRegistry>>,
+        prefabs: Arc<RwLock<Prefabs>>,
+        tile_map: Arc<RwLock<TileMap>>,
+        spatial: Arc<RwLock<SpatialIndex>>,
+        events: Arc<RwLock<EventBus>>,
+    ) -> Self {
+        let subscriber = events.write().subscribe();
+
+        Self {
+            player: None,
+            tags,
+            registry,
+            prefabs,
+            tile_map,
+            spatial,
+            events,
+            subscriber,
+            firing: false,
+            cooldown: 0.0,
+            last_frame: Instant::now(),
+        }
+    }
+
+    fn fire(&self, em: &mut EntityManager, player: Id) -> anyhow::Result<()> {
+        let (Some(trans), Some(p)) = (
+            em.get_component::<Trans>(player),
+            em.get_component::<Player>(player),
+        ) else {
+            return Ok(());
+        };
+        let (position, rotation) = {
+            let trans = trans.read();
+
+            (trans.position(), trans.rotation())
+        };
+        let Some(direction) = (p.read().aim - position).try_normalize(f32::EPSILON) else {
+            return Ok(());
+        };
+        let e = self
+            .prefabs
+            .read()
+            .spawn(&self.registry.read(), em, PROJECTILE_TAG, position)?;
+
+        if let Some(t) = em.get_component::<Trans>(e) {
+            t.write().set_rotation(rotation);
+        }
+
+        em.add_component(
+            e,
+            Projectile::new(player, direction * PROJECTILE_SPEED, PROJECTILE_DAMAGE),
+        );
+
+        Ok(())
+    }
+
+    fn hit(&self, em: &EntityManager, projectile: &Projectile, position: Vector2<f32>) -> bool {
+        let tags = self.tags.read();
+        let target = self
+            .spatial
+            .read()
+            .nearest(position, PROJECTILE_RADIUS, |e| {
+                e != projectile.owner
+                    && tags
+                        .tag_of(e)
+                        .is_some_and(|tag| HOSTILE_TAGS.contains(&tag))
+                    && em.get_component::<Health>(e).is_some()
+            });
+
+        match target.and_then(|e| em.get_component::<Health>(e)) {
+            Some(health) => {
+                health
+                    .write()
+                    .damage(projectile.damage, DamageSource::Projectile);
+
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+impl System for ProjectileManager {
+    fn init(&mut self, _: Arc<RwLock<Context>>, _: Arc<RwLock<World>>) -> anyhow::Result<()> {
+        self.player = self.tags.read().first("player");
+
+        Ok(())
+    }
+
+    fn update(
+        &mut self,
+        control: Arc<RwLock<Control>>,
+        context: Arc<RwLock<Context>>,
+        world: Arc<RwLock<World>>,
+    ) -> anyhow::Result<()> {
+        let event = control.read().event.clone();
+
+        if let Event::WindowEvent {
+            event: WindowEvent::RedrawRequested,
+            window_id,
+        } = event
+        {
+            if window_id != context.read().window.id() {
+                return Ok(());
+            }
+
+            let now = Instant::now();
+            let delta = now.duration_since(self.last_frame).as_secs_f32();
+
+            self.last_frame = now;
+
+            let Some(player) = self.player else {
+                return Ok(());
+            };
+            let em = world.read().em.clone();
+            let mut em = em.write();
+
+            for event in self.events.write().poll(self.subscriber) {
+                if let GameEvent::TriggerChanged { entity, active } = event {
+                    if entity == player {
+                        self.firing = active;
+                    }
+                }
+            }
+
+            let docked = em
+                .get_component::<Docking>(player)
+                .is_some_and(|d| d.read().is_docked());
+
+            self.cooldown = (self.cooldown - delta).max(0.0);
+
+            if self.firing && !docked && self.cooldown == 0.0 {
+                self.fire(&mut em, player)?;
+                self.cooldown = FIRE_INTERVAL;
+            }
+
+            let ids: Vec<_> = self.tags.read().all(PROJECTILE_TAG).collect();
+            let mut rm = Vec::new();
+
+            for e in ids {
+                let (Some(projectile), Some(trans)) = (
+                    em.get_component::<Projectile>(e),
+                    em.get_component::<Trans>(e),
+                ) else {
+                    continue;
+                };
+                let mut projectile = projectile.write();
+                let mut trans = trans.write();
+                let step = projectile.velocity * delta;
+
+                projectile.age += delta;
+
+                if projectile.age >= PROJECTILE_LIFETIME
+                    || self
+                        .tile_map
+                        .read()
+                        .raycast(trans.position(), step, step.magnitude())
+                        .is_some()
+                {
+                    rm.push(e);
+
+                    continue;
+                }
+
+                trans.set_position(trans.position() + step);
+
+                if self.hit(&em, &projectile, trans.position()) {
+                    rm.push(e);
+                }
+            }
+
+            let mut tags = self.tags.write();
+
+            for e in rm {
+                tags.rm(&mut em, e);
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,137 @@
+use hex::{
+    anyhow,
+    nalgebra::Vector4,
+    parking_lot::RwLock,
+    vulkano::swapchain::PresentMode,
+    winit::{
+        monitor::MonitorHandle,
+        window::{Fullscreen, Window},
+    },
+};
+use serde_derive::{Deserialize, Serialize};
+use std::{fs, path::Path, sync::Arc};
+
+pub const SETTINGS_FILE: &str = "settings.json";
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum WindowMode {
+    Windowed,
+    Borderless,
+    Fullscreen,
+}
+
+impl WindowMode {
+    pub fn fullscreen(self, monitor: Option<MonitorHandle>) -> Option<Fullscreen> {
+        match self {
+            Self::Windowed => None,
+            Self::Borderless => Some(Fullscreen::Borderless(monitor)),
+            Self::Fullscreen => monitor
+                .and_then(|m| {
+                    m.video_modes().max_by_key(|v| {
+                        (
+                            v.size().width * v.size().height,
+                            v.refresh_rate_millihertz(),
+                        )
+                    })
+                })
+                .map(Fullscreen::Exclusive)
+                .or(Some(Fullscreen::Borderless(None))),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum PresentModeSetting {
+    Immediate,
+    Mailbox,
+    Vsync,
+    AdaptiveVsync,
+}
+
+impl From<PresentModeSetting> for PresentMode {
+    fn from(mode: PresentModeSetting) -> Self {
+        match mode {
+            PresentModeSetting::Immediate => PresentMode::Immediate,
+            PresentModeSetting::Mailbox => PresentMode::Mailbox,
+            PresentModeSetting::Vsync => PresentMode::Fifo,
+            PresentModeSetting::AdaptiveVsync => PresentMode::FifoRelaxed,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct Settings {
+    pub title: String,
+    pub window_mode: WindowMode,
+    pub fullscreen_mode: WindowMode,
+    pub size: [u32; 2],
+    pub present_mode: PresentModeSetting,
+    pub threads: Option<usize>,
+    pub clear_color: [f32; 4],
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            title: "Paraselene Reimagined".into(),
+            window_mode: WindowMode::Windowed,
+            fullscreen_mode: WindowMode::Borderless,
+            size: [1280, 720],
+            present_mode: PresentModeSetting::Immediate,
+            threads: None,
+            clear_color: [0.5, 0.5, 0.5, 1.0],
+        }
+    }
+}
+
+impl Settings {
+    pub fn load<P>(path: P) -> anyhow::Result<Arc<RwLock<Self>>>
+    where
+        P: AsRef<Path>,
+    {
+        let path = path.as_ref();
+        let settings = if Path::exists(path) {
+            let content = fs::read_to_string(path)?;
+
+            serde_json::from_str(content.as_str())?
+        } else {
+            let settings = Self::default();
+
+            settings.save(path)?;
+
+            settings
+        };
+
+        Ok(Arc::new(RwLock::new(settings)))
+    }
+
+    pub fn save<P>(&self, path: P) -> anyhow::Result<()>
+    where
+        P: AsRef<Path>,
+    {
+        fs::write(path, serde_json::to_string_pretty(self)?)?;
+
+        Ok(())
+    }
+
+    pub fn threads(&self) -> usize {
+        self.threads.unwrap_or_else(|| num_cpus::get() / 2).max(1)
+    }
+
+    pub fn clear_color(&self) -> Vector4<f32> {
+        self.clear_color.into()
+    }
+
+    pub fn toggle_fullscreen(&mut self, window: &Window) {
+        self.window_mode = if self.window_mode == WindowMode::Windowed {
+            self.fullscreen_mode
+        } else {
+            WindowMode::Windowed
+        };
+
+        window.set_fullscreen(self.window_mode.fullscreen(window.current_monitor()));
+    }
+}
@@ -0,0 +1,308 @@
+use crate::{
+    chunk_manager::{ASTEROID_1, ASTEROID_2, METAL},
+    game_manager::Player,
+    health::{DamageSource, Health},
+    hud_manager::Notifications,
+    inventory::Inventory,
+    station_manager::Docking,
+    tag::TagIndex,
+};
+use hex::{
+    anyhow,
+    parking_lot::RwLock,
+    winit::event::{Event, WindowEvent},
+    world::{system_manager::System, World},
+    Context, Control, Id,
+};
+use serde_derive::{Deserialize, Serialize};
+use std::{collections::BTreeMap, fs, path::Path, sync::Arc, time::Instant};
+
+pub const SHIP_SYSTEMS_FILE: &str = "data/ship_systems.json";
+
+#[derive(Serialize, Deserialize, Clone, Copy, Default, Debug)]
+#[serde(default)]
+pub struct Refill {
+    pub fuel: f32,
+    pub energy: f32,
+    pub oxygen: f32,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct ShipConfig {
+    pub fuel_capacity: f32,
+    pub fuel_drain: f32,
+    pub energy_capacity: f32,
+    pub energy_recharge: f32,
+    pub boost_drain: f32,
+    pub boost_multiplier: f32,
+    pub oxygen_capacity: f32,
+    pub oxygen_drain: f32,
+    pub suffocation_damage: f32,
+    pub refills: BTreeMap<String, Refill>,
+}
+
+impl Default for ShipConfig {
+    fn default() -> Self {
+        Self {
+            fuel_capacity: 100.0,
+            fuel_drain: 1.0,
+            energy_capacity: 50.0,
+            energy_recharge: 5.0,
+            boost_drain: 15.0,
+            boost_multiplier: 2.0,
+            oxygen_capacity: 100.0,
+            oxygen_drain: 0.25,
+            suffocation_damage: 2.0,
+            refills: [
+                (
+                    ASTEROID_1.to_string(),
+                    Refill {
+                        oxygen: 15.0,
+                        ..Default::default()
+                    },
+                ),
+                (
+                    ASTEROID_2.to_string(),
+                    Refill {
+                        fuel: 15.0,
+                        ..Default::default()
+                    },
+                ),
+                (
+                    METAL.to_string(),
+                    Refill {
+                        fuel: 5.0,
+                        oxygen: 5.0,
+                        ..Default::default()
+                    },
+                ),
+            ]
+            .into(),
+        }
+    }
+}
+
+impl ShipConfig {
+    pub fn load<P>(path: P) -> anyhow::Result<Self>
+    where
+        P: AsRef<Path>,
+    {
+        let path = path.as_ref();
+
+        if Path::exists(path) {
+            Ok(serde_json::from_str(fs::read_to_string(path)?.as_str())?)
+        } else {
+            Ok(Self::default())
+        }
+    }
+}
+
+pub struct ShipSystems {
+    pub config: ShipConfig,
+    pub fuel: f32,
+    pub energy: f32,
+    pub oxygen: f32,
+    pub boosting: bool,
+}
+
+impl ShipSystems {
+    pub fn new(config: ShipConfig) -> Arc<RwLock<Self>> {
+        Arc::new(RwLock::new(Self {
+            fuel: config.fuel_capacity,
+            energy: config.energy_capacity,
+            oxygen: config.oxygen_capacity,
+            boosting: false,
+            config,
+        }))
+    }
+
+    pub fn can_thrust(&self) -> bool {
+        self.fuel > 0.0
+    }
+
+    pub fn boost_active(&self) -> bool {
+        self.boosting && self.energy > 0.0
+    }
+
+    pub fn boost_multiplier(&self) -> f32 {
+        if self.boost_active() {
+            self.config.boost_multiplier
+        } else {
+            1.0
+        }
+    }
+
+    pub fn drain_energy(&mut self, amount: f32) -> bool {
+        if self.energy >= amount {
+            self.energy -= amount;
+
+            true
+        } else {
+            false
+        }
+    }
+
+    pub fn update(&mut self, delta: f32, thrusting: bool) {
+        if thrusting && self.can_thrust() {
+            self.fuel = (self.fuel - self.config.fuel_drain * delta).max(0.0);
+        }
+
+        if thrusting && self.boost_active() {
+            self.energy = (self.energy - self.config.boost_drain * delta).max(0.0);
+        } else {
+            self.energy = (self.energy + self.config.energy_recharge * delta)
+                .min(self.config.energy_capacity);
+        }
+
+        self.oxygen = (self.oxygen - self.config.oxygen_drain * delta).max(0.0);
+    }
+
+    pub fn apply(&mut self, refill: &Refill) {
+        self.fuel = (self.fuel + refill.fuel).min(self.config.fuel_capacity);
+        self.energy = (self.energy + refill.energy).min(self.config.energy_capacity);
+        self.oxygen = (self.oxygen + refill.oxygen).min(self.config.oxygen_capacity);
+    }
+
+    pub fn refill(&mut self) {
+        self.fuel = self.config.fuel_capacity;
+        self.energy = self.config.energy_capacity;
+        self.oxygen = self.config.oxygen_capacity;
+    }
+
+    fn wants(&self, refill: &Refill) -> bool {
+        (refill.fuel > 0.0 && self.fuel < self.config.fuel_capacity)
+            || (refill.energy > 0.0 && self.energy < self.config.energy_capacity)
+            || (refill.oxygen > 0.0 && self.oxygen < self.config.oxygen_capacity)
+    }
+
+    pub fn refine(&mut self, inventory: &mut Inventory) -> u32 {
+        let refills: Vec<_> = self
+            .config
+            .refills
+            .iter()
+            .map(|(id, r)| (id.clone(), *r))
+            .collect();
+        let mut used = 0;
+
+        for (id, refill) in refills {
+            while self.wants(&refill) && inventory.remove(&id, 1) {
+                self.apply(&refill);
+
+                used += 1;
+            }
+        }
+
+        used
+    }
+
+    pub fn fuel_fraction(&self) -> f32 {
+        Self::fraction(self.fuel, self.config.fuel_capacity)
+    }
+
+    pub fn energy_fraction(&self) -> f32 {
+        Self::fraction(self.energy, self.config.energy_capacity)
+    }
+
+    pub fn oxygen_fraction(&self) -> f32 {
+        Self::fraction(self.oxygen, self.config.oxygen_capacity)
+    }
+
+    fn fraction(value: f32, max: f32) -> f32 {
+        if max > 0.0 {
+            (value / max).clamp(0.0, 1.0)
+        } else {
+            0.0
+        }
+    }
+}
+
+pub struct ShipManager {
+    pub player: Option<Id>,
+    pub tags: Arc<RwLock<TagIndex>>,
+    pub notifications: Arc<RwLock<Notifications>>,
+    pub last_frame: Instant,
+}
+
+impl ShipManager {
+    pub fn new(notifications: Arc<RwLock<Notifications>>, tags: Arc<RwLock<TagIndex>>) -> Self {
+        Self {
+            player: None,
+            tags,
+            notifications,
+            last_frame: Instant::now(),
+        }
+    }
+}
+
+impl System for ShipManager {
+    fn init(&mut self, _: Arc<RwLock<Context>>, _: Arc<RwLock<World>>) -> anyhow::Result<()> {
+        self.player = self.tags.read().first("player");
+
+        Ok(())
+    }
+
+    fn update(
+        &mut self,
+        control: Arc<RwLock<Control>>,
+        context: Arc<RwLock<Context>>,
+        world: Arc<RwLock<World>>,
+    ) -> anyhow::Result<()> {
+        let event = control.read().event.clone();
+
+        if let Event::WindowEvent {
+            event: WindowEvent::RedrawRequested,
+            window_id,
+        } = event
+        {
+            if window_id != context.read().window.id() {
+                return Ok(());
+            }
+
+            let now = Instant::now();
+            let delta = now.duration_since(self.last_frame).as_secs_f32();
+
+            self.last_frame = now;
+
+            let Some(player) = self.player else {
+                return Ok(());
+            };
+            let em = world.read().em.clone();
+            let em = em.read();
+            let Some(systems) = em.get_component::<ShipSystems>(player) else {
+                return Ok(());
+            };
+            let mut systems = systems.write();
+            let docked = em
+                .get_component::<Docking>(player)
+                .is_some_and(|d| d.read().is_docked());
+            let thrusting = !docked
+                && em
+                    .get_component::<Player>(player)
+                    .map(|p| p.read().force().magnitude() > 0.0)
+                    .unwrap_or(false);
+            let (fuel, oxygen) = (systems.fuel, systems.oxygen);
+
+            systems.update(delta, thrusting);
+
+            if fuel > 0.0 && systems.fuel == 0.0 {
+                self.notifications.write().push("OUT OF FUEL");
+            }
+
+            if oxygen > 0.0 && systems.oxygen == 0.0 {
+                self.notifications.write().push("OXYGEN DEPLETED");
+            }
+
+            if systems.oxygen == 0.0 {
+                if let Some(health) = em.get_component::<Health>(player) {
+                    health.write().damage(
+                        systems.config.suffocation_damage * delta,
+                        DamageSource::Suffocation,
+                    );
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
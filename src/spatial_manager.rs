@@ -0,0 +1,348 @@
+use crate::{
+    chunk_manager::TileMap,
+    drop_manager::DROP_TAG,
+    events::{EventBus, GameEvent, Subscriber},
+    health_manager::HOSTILE_TAGS,
+    tag::TagIndex,
+};
+use hex::{
+    anyhow,
+    components::Trans,
+    nalgebra::Vector2,
+    parking_lot::RwLock,
+    winit::event::{Event, WindowEvent},
+    world::{system_manager::System, World},
+    Context, Control, Id,
+};
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+};
+
+pub const SPATIAL_TAGS: &[&str] = &["player", DROP_TAG];
+
+#[derive(Clone, Copy, Debug)]
+pub struct SpatialEntry {
+    pub position: Vector2<f32>,
+    pub tile: (u32, u32),
+    pub dynamic: bool,
+}
+
+#[derive(Default)]
+pub struct SpatialIndex {
+    pub cells: HashMap<(u32, u32), Vec<Id>>,
+    pub chunks: HashMap<(u32, u32), HashSet<Id>>,
+    pub entries: HashMap<Id, SpatialEntry>,
+    pub dynamic: HashSet<Id>,
+}
+
+impl SpatialIndex {
+    pub fn new() -> Arc<RwLock<Self>> {
+        Arc::new(RwLock::new(Self::default()))
+    }
+
+    pub fn insert(&mut self, e: Id, position: Vector2<f32>, dynamic: bool) {
+        self.remove(e);
+
+        let Some(tile) = TileMap::tile_pos(position) else {
+            return;
+        };
+        let (chunk, _) = TileMap::tile_chunk(tile);
+
+        self.cells.entry(tile).or_default().push(e);
+        self.chunks.entry(chunk).or_default().insert(e);
+
+        if dynamic {
+            self.dynamic.insert(e);
+        }
+
+        self.entries.insert(
+            e,
+            SpatialEntry {
+                position,
+                tile,
+                dynamic,
+            },
+        );
+    }
+
+    pub fn remove(&mut self, e: Id) -> Option<SpatialEntry> {
+        let entry = self.entries.remove(&e)?;
+        let (chunk, _) = TileMap::tile_chunk(entry.tile);
+
+        self.dynamic.remove(&e);
+
+        if let Some(cell) = self.cells.get_mut(&entry.tile) {
+            cell.retain(|c| *c != e);
+
+            if cell.is_empty() {
+                self.cells.remove(&entry.tile);
+            }
+        }
+
+        if let Some(ids) = self.chunks.get_mut(&chunk) {
+            ids.remove(&e);
+
+            if ids.is_empty() {
+                self.chunks.remove(&chunk);
+            }
+        }
+
+        Some(entry)
+    }
+
+    pub fn update(&mut self, e: Id, position: Vector2<f32>) {
+        let Some(entry) = self.entries.get_mut(&e) else {
+            return;
+        };
+
+        if TileMap::tile_pos(position) == Some(entry.tile) {
+            entry.position = position;
+        } else {
+            let dynamic = entry.dynamic;
+
+            self.insert(e, position, dynamic);
+        }
+    }
+
+    pub fn contains(&self, e: Id) -> bool {
+        self.entries.contains_key(&e)
+    }
+
+    pub fn position(&self, e: Id) -> Option<Vector2<f32>> {
+        self.entries.get(&e).map(|entry| entry.position)
+    }
+
+    pub fn tile(&self, tile: (u32, u32)) -> &[Id] {
+        self.cells.get(&tile).map(Vec::as_slice).unwrap_or_default()
+    }
+
+    pub fn chunk(&self, chunk: (u32, u32)) -> impl Iterator<Item = Id> + '_ {
+        self.chunks.get(&chunk).into_iter().flatten().copied()
+    }
+
+    pub fn rect(&self, min: Vector2<f32>, max: Vector2<f32>) -> Vec<Id> {
+        let (Some(lo), Some(hi)) = (
+            TileMap::tile_pos(min.sup(&Vector2::default())),
+            TileMap::tile_pos(max),
+        ) else {
+            return Vec::new();
+        };
+        let mut found = Vec::new();
+
+        for x in lo.0..=hi.0 {
+            for y in lo.1..=hi.1 {
+                for e in self.cells.get(&(x, y)).into_iter().flatten() {
+                    let p = self.entries[e].position;
+
+                    if p.x >= min.x && p.y >= min.y && p.x <= max.x && p.y <= max.y {
+                        found.push(*e);
+                    }
+                }
+            }
+        }
+
+        found
+    }
+
+    pub fn radius(&self, center: Vector2<f32>, radius: f32) -> Vec<Id> {
+        let extent = Vector2::new(radius, radius);
+
+        self.rect(center - extent, center + extent)
+            .into_iter()
+            .filter(|e| (self.entries[e].position - center).magnitude() <= radius)
+            .collect()
+    }
+
+    pub fn nearest<F>(&self, center: Vector2<f32>, radius: f32, filter: F) -> Option<Id>
+    where
+        F: Fn(Id) -> bool,
+    {
+        self.radius(center, radius)
+            .into_iter()
+            .filter(|e| filter(*e))
+            .min_by(|a, b| {
+                let da = (self.entries[a].position - center).magnitude();
+                let db = (self.entries[b].position - center).magnitude();
+
+                da.total_cmp(&db)
+            })
+    }
+}
+
+pub struct SpatialManager {
+    pub tags: Arc<RwLock<TagIndex>>,
+    pub spatial: Arc<RwLock<SpatialIndex>>,
+    pub events: Arc<RwLock<EventBus>>,
+    pub subscriber: Subscriber,
+}
+
+impl SpatialManager {
+    pub fn new(
+        tags: Arc<RwLock<TagIndex>>,
+        spatial: Arc<RwLock<SpatialIndex>>,
+        events: Arc<RwLock<EventBus>>,
+    ) -> Self {
+        let subscriber = events.write().subscribe();
+
+        Self {
+            tags,
+            spatial,
+            events,
+            subscriber,
+        }
+    }
+}
+
+impl System for SpatialManager {
+    fn update(
+        &mut self,
+        control: Arc<RwLock<Control>>,
+        context: Arc<RwLock<Context>>,
+        world: Arc<RwLock<World>>,
+    ) -> anyhow::Result<()> {
+        let event = control.read().event.clone();
+
+        if let Event::WindowEvent {
+            event: WindowEvent::RedrawRequested,
+            window_id,
+        } = event
+        {
+            if window_id != context.read().window.id() {
+                return Ok(());
+            }
+
+            let events = self.events.write().poll(self.subscriber);
+            let em = world.read().em.clone();
+            let em = em.read();
+            let mut spatial = self.spatial.write();
+
+            for event in events {
+                if let GameEvent::TileChanged { tile, .. } = event {
+                    let stale: Vec<_> = spatial
+                        .tile(tile)
+                        .iter()
+                        .copied()
+                        .filter(|e| em.get_component::<Trans>(*e).is_none())
+                        .collect();
+
+                    for e in stale {
+                        spatial.remove(e);
+                    }
+                }
+            }
+
+            {
+                let tags = self.tags.read();
+
+                for tag in SPATIAL_TAGS.iter().chain(HOSTILE_TAGS) {
+                    for (e, t) in tags.query::<Trans>(&em, tag) {
+                        if !spatial.contains(e) {
+                            spatial.insert(e, t.read().position(), true);
+                        }
+                    }
+                }
+            }
+
+            for e in spatial.dynamic.iter().copied().collect::<Vec<_>>() {
+                match em.get_component::<Trans>(e) {
+                    Some(t) => spatial.update(e, t.read().position()),
+                    None => {
+                        spatial.remove(e);
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hex::world::EntityManager;
+
+    fn ids(n: usize) -> Vec<Id> {
+        let em = EntityManager::new();
+        let mut em = em.write();
+
+        (0..n).map(|_| em.add(true)).collect()
+    }
+
+    fn sorted(mut ids: Vec<Id>) -> Vec<Id> {
+        ids.sort();
+        ids
+    }
+
+    #[test]
+    fn rect_filters_by_position() {
+        let e = ids(3);
+        let mut index = SpatialIndex::default();
+
+        index.insert(e[0], Vector2::new(2.0, 2.0), false);
+        index.insert(e[1], Vector2::new(2.4, 2.4), false);
+        index.insert(e[2], Vector2::new(5.0, 5.0), false);
+
+        assert_eq!(
+            sorted(index.rect(Vector2::new(1.9, 1.9), Vector2::new(2.45, 2.45))),
+            sorted(vec![e[0], e[1]])
+        );
+        assert_eq!(
+            index.rect(Vector2::new(2.2, 2.2), Vector2::new(3.0, 3.0)),
+            vec![e[1]]
+        );
+        assert_eq!(
+            sorted(index.rect(Vector2::new(-4.0, -4.0), Vector2::new(6.0, 6.0))),
+            sorted(e.clone())
+        );
+        assert!(index
+            .rect(Vector2::new(-4.0, -4.0), Vector2::new(-1.0, -1.0))
+            .is_empty());
+    }
+
+    #[test]
+    fn radius_and_nearest() {
+        let e = ids(3);
+        let mut index = SpatialIndex::default();
+
+        index.insert(e[0], Vector2::new(10.0, 10.0), false);
+        index.insert(e[1], Vector2::new(12.0, 10.0), false);
+        index.insert(e[2], Vector2::new(10.0, 13.0), false);
+
+        let center = Vector2::new(10.0, 10.0);
+
+        assert_eq!(sorted(index.radius(center, 2.5)), sorted(vec![e[0], e[1]]));
+        assert_eq!(index.nearest(center, 5.0, |_| true), Some(e[0]));
+        assert_eq!(index.nearest(center, 5.0, |id| id != e[0]), Some(e[1]));
+        assert_eq!(index.nearest(center, 1.0, |id| id != e[0]), None);
+    }
+
+    #[test]
+    fn moves_across_cell_and_chunk_boundaries() {
+        let e = ids(1)[0];
+        let mut index = SpatialIndex::default();
+
+        index.insert(e, Vector2::new(15.2, 3.0), true);
+        index.update(e, Vector2::new(15.4, 3.0));
+
+        assert_eq!(index.tile((15, 3)), &[e]);
+        assert_eq!(index.position(e), Some(Vector2::new(15.4, 3.0)));
+
+        index.update(e, Vector2::new(16.2, 3.0));
+
+        assert!(index.tile((15, 3)).is_empty());
+        assert_eq!(index.tile((16, 3)), &[e]);
+        assert_eq!(index.chunk((0, 0)).count(), 0);
+        assert_eq!(index.chunk((1, 0)).collect::<Vec<_>>(), vec![e]);
+        assert!(index.dynamic.contains(&e));
+
+        let entry = index.remove(e).unwrap();
+
+        assert_eq!(entry.tile, (16, 3));
+        assert!(!index.contains(e));
+        assert!(index.cells.is_empty());
+        assert!(index.chunks.is_empty());
+        assert!(index.dynamic.is_empty());
+    }
+}
@@ -1,7 +1,20 @@
-use hex::parking_lot::RwLock;
+use crate::chunk_manager::SAVE_DIR;
+use hex::{anyhow, parking_lot::RwLock};
 use noise::Perlin;
 use rand::prelude::*;
-use std::sync::Arc;
+use serde_derive::{Deserialize, Serialize};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+pub const WORLD_FILE: &str = "world.json";
+
+#[derive(Serialize, Deserialize)]
+pub struct WorldData {
+    pub seed: u32,
+}
 
 #[derive(Clone)]
 pub struct State {
@@ -18,4 +31,24 @@ impl State {
             seed,
         }))
     }
+
+    pub fn path() -> PathBuf {
+        PathBuf::from(SAVE_DIR).join(WORLD_FILE)
+    }
+
+    pub fn load() -> anyhow::Result<Arc<RwLock<Self>>> {
+        let path = Self::path();
+        let seed = if Path::exists(&path) {
+            serde_json::from_str::<WorldData>(fs::read_to_string(path)?.as_str())?.seed
+        } else {
+            let seed = rand::thread_rng().gen::<u32>();
+
+            fs::create_dir_all(SAVE_DIR)?;
+            fs::write(path, serde_json::to_string(&WorldData { seed })?)?;
+
+            seed
+        };
+
+        Ok(Self::new(seed))
+    }
 }
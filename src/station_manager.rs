@@ -0,0 +1,949 @@
+use crate::{
+    chunk_manager::{TileMap, CHUNK_SIZE, MIN_CHUNK, SAVE_DIR},
+    crafting_manager::Crafter,
+    economy::Economy,
+    events::{EventBus, GameEvent},
+    font::{Font, Text},
+    game_manager::Player,
+    health::Health,
+    hud_manager::{Notifications, Screen, HUD_MARGIN},
+    inventory::Inventory,
+    map_manager::MapMarker,
+    ship_manager::ShipSystems,
+    state::State,
+    tag::TagIndex,
+    util,
+};
+use hex::{
+    anyhow,
+    assets::Shape,
+    components::Trans,
+    nalgebra::{Vector2, Vector4},
+    parking_lot::RwLock,
+    winit::event::{Event, WindowEvent},
+    world::{system_manager::System, EntityManager, World},
+    Context, Control, Id,
+};
+use hex_instance::components::Instance;
+use rand::prelude::*;
+use serde_derive::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, HashMap, HashSet, VecDeque},
+    fs,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::Instant,
+};
+
+pub const STATION_TAG: &str = "station";
+pub const STATION_RARITY: u64 = 24;
+pub const STATION_SALT: u64 = 0x5354_4154_494f_4e53;
+pub const STATION_SIZE: f32 = 3.0;
+pub const STATION_MARGIN: u32 = 3;
+pub const DOCK_RADIUS: f32 = 4.0;
+pub const DOCK_SPEED: f32 = 2.0;
+pub const DOCK_EASE: f32 = 6.0;
+pub const STARTING_CREDITS: u64 = 100;
+pub const PRICE_ELASTICITY: f32 = 0.75;
+pub const MIN_PRICE_MUL: f32 = 0.25;
+pub const MAX_PRICE_MUL: f32 = 4.0;
+pub const STATION_PANEL_Y: f32 = 40.0;
+pub const SELL_RATIO: f32 = 0.8;
+pub const REFUEL_PRICE: f32 = 0.2;
+pub const REPAIR_PRICE: f32 = 0.5;
+pub const PRICE_HISTORY_LEN: usize = 32;
+pub const PRICE_HISTORY_INTERVAL: u64 = 30;
+pub const MAX_STOCK_MUL: u32 = 4;
+pub const MAX_CATCH_UP_TICKS: u64 = 100_000;
+pub const STATION_KINDS: &[&str] = &["trading", "fabricator"];
+pub const STATION_NAMES: (&[&str], &[&str]) = (
+    &[
+        "KEPLER", "VESTA", "HALO", "CERES", "NADIR", "ORION", "TYCHO",
+    ],
+    &["DEPOT", "HUB", "OUTPOST", "YARD", "EXCHANGE", "RELAY"],
+);
+pub const MARKET_GOODS: &[(&str, f32)] = &[
+    ("asteroid_1", 2.0),
+    ("asteroid_2", 3.0),
+    ("metal", 6.0),
+    ("ice", 4.0),
+    ("radioactive", 12.0),
+    ("metal_plate", 15.0),
+    ("silicate", 8.0),
+    ("fuel_cell", 20.0),
+    ("circuit", 45.0),
+];
+
+pub struct Wallet {
+    pub credits: u64,
+}
+
+impl Wallet {
+    pub fn new(credits: u64) -> Arc<RwLock<Self>> {
+        Arc::new(RwLock::new(Self { credits }))
+    }
+
+    pub fn spend(&mut self, amount: u64) -> bool {
+        if self.credits >= amount {
+            self.credits -= amount;
+
+            true
+        } else {
+            false
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct MarketEntry {
+    pub stock: u32,
+    pub target: u32,
+    pub base_price: f32,
+    #[serde(default)]
+    pub production: f32,
+    #[serde(default)]
+    pub consumption: f32,
+    #[serde(default)]
+    pub accumulator: f32,
+    #[serde(default)]
+    pub history: VecDeque<u64>,
+}
+
+impl MarketEntry {
+    pub fn price(&self) -> u64 {
+        let mul = ((self.target + 1) as f32 / (self.stock + 1) as f32)
+            .powf(PRICE_ELASTICITY)
+            .clamp(MIN_PRICE_MUL, MAX_PRICE_MUL);
+
+        (self.base_price * mul).round().max(1.0) as u64
+    }
+
+    pub fn sell_price(&self) -> u64 {
+        (self.price() as f32 * SELL_RATIO).floor().max(1.0) as u64
+    }
+
+    pub fn step(&mut self) {
+        self.accumulator += self.production - self.consumption;
+
+        let whole = self.accumulator.trunc();
+
+        self.accumulator -= whole;
+        self.stock = (self.stock as i64 + whole as i64)
+            .clamp(0, (self.target * MAX_STOCK_MUL) as i64) as u32;
+    }
+
+    pub fn record(&mut self) {
+        self.history.push_back(self.price());
+
+        while self.history.len() > PRICE_HISTORY_LEN {
+            self.history.pop_front();
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Default, Debug)]
+pub struct Market {
+    pub entries: BTreeMap<String, MarketEntry>,
+    #[serde(default)]
+    pub tick: u64,
+}
+
+impl Market {
+    pub fn simulate_to(&mut self, tick: u64) {
+        self.tick = self.tick.max(tick.saturating_sub(MAX_CATCH_UP_TICKS));
+
+        while self.tick < tick {
+            self.tick += 1;
+
+            for entry in self.entries.values_mut() {
+                entry.step();
+
+                if self.tick % PRICE_HISTORY_INTERVAL == 0 {
+                    entry.record();
+                }
+            }
+        }
+    }
+
+    pub fn history(&self, id: &str) -> Option<&VecDeque<u64>> {
+        self.entries.get(id).map(|e| &e.history)
+    }
+
+    pub fn buy(&mut self, id: &str, wallet: &mut Wallet, inventory: &mut Inventory) -> bool {
+        let Some(entry) = self.entries.get_mut(id) else {
+            return false;
+        };
+
+        if entry.stock == 0 || inventory.space_for(id) == 0 || !wallet.spend(entry.price()) {
+            return false;
+        }
+
+        entry.stock -= 1;
+        inventory.add(id, 1);
+
+        true
+    }
+
+    pub fn sell(&mut self, id: &str, wallet: &mut Wallet, inventory: &mut Inventory) -> bool {
+        let Some(entry) = self.entries.get_mut(id) else {
+            return false;
+        };
+
+        if !inventory.remove(id, 1) {
+            return false;
+        }
+
+        wallet.credits += entry.sell_price();
+        entry.stock += 1;
+
+        true
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct StationData {
+    pub name: String,
+    pub kind: String,
+    pub position: [f32; 2],
+    pub storage: Inventory,
+    pub market: Market,
+}
+
+impl StationData {
+    pub fn position(&self) -> Vector2<f32> {
+        self.position.into()
+    }
+
+    pub fn path(chunk: (u32, u32)) -> PathBuf {
+        let (x, y) = chunk;
+
+        PathBuf::from(SAVE_DIR)
+            .join("chunks")
+            .join(format!("{x},{y}.station.json"))
+    }
+
+    pub fn seed(seed: u32, chunk: (u32, u32)) -> u64 {
+        util::chunk_hash(chunk, util::seed_hash(seed, STATION_SALT))
+    }
+
+    pub fn generate(seed: u32, chunk @ (x, y): (u32, u32)) -> Option<Self> {
+        let seed = Self::seed(seed, chunk);
+
+        if x < MIN_CHUNK || y < MIN_CHUNK || seed % STATION_RARITY != 0 {
+            return None;
+        }
+
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut offset = || rng.gen_range(STATION_MARGIN..CHUNK_SIZE - STATION_MARGIN) as f32;
+        let position = [
+            (x * CHUNK_SIZE) as f32 + offset(),
+            (y * CHUNK_SIZE) as f32 + offset(),
+        ];
+        let name = format!(
+            "{} {}",
+            STATION_NAMES.0.choose(&mut rng).unwrap(),
+            STATION_NAMES.1.choose(&mut rng).unwrap()
+        );
+        let kind = STATION_KINDS.choose(&mut rng).unwrap().to_string();
+        let market = Market {
+            entries: MARKET_GOODS
+                .iter()
+                .map(|(id, price)| {
+                    let target = rng.gen_range(10..60);
+                    let rate = rng.gen_range(0.0..0.2);
+                    let (production, consumption) = if rng.gen_bool(0.5) {
+                        (rate, 0.0)
+                    } else {
+                        (0.0, rate)
+                    };
+
+                    (
+                        id.to_string(),
+                        MarketEntry {
+                            stock: rng.gen_range(0..target * 2),
+                            target,
+                            base_price: price * rng.gen_range(0.8..1.25),
+                            production,
+                            consumption,
+                            accumulator: 0.0,
+                            history: VecDeque::new(),
+                        },
+                    )
+                })
+                .collect(),
+            tick: 0,
+        };
+
+        Some(Self {
+            name,
+            kind,
+            position,
+            storage: Inventory::default(),
+            market,
+        })
+    }
+
+    pub fn read_saved(chunk: (u32, u32)) -> anyhow::Result<Option<Self>> {
+        let path = Self::path(chunk);
+
+        if Path::exists(&path) {
+            Ok(Some(serde_json::from_str(
+                fs::read_to_string(path)?.as_str(),
+            )?))
+        } else {
+            Ok(None)
+        }
+    }
+
+    pub fn save(&self, chunk: (u32, u32)) -> anyhow::Result<()> {
+        fs::create_dir_all(PathBuf::from(SAVE_DIR).join("chunks"))?;
+        fs::write(Self::path(chunk), serde_json::to_string(self)?)?;
+
+        Ok(())
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum StationAction {
+    Undock,
+    Service,
+    Store,
+    Retrieve,
+    Buy,
+    Sell,
+    Next,
+    Previous,
+}
+
+#[derive(Default)]
+pub struct Docking {
+    pub station: Option<(u32, u32)>,
+    pub can_dock: bool,
+    pub selected: usize,
+    pub actions: Vec<StationAction>,
+}
+
+impl Docking {
+    pub fn new() -> Arc<RwLock<Self>> {
+        Arc::new(RwLock::new(Self::default()))
+    }
+
+    pub fn is_docked(&self) -> bool {
+        self.station.is_some()
+    }
+
+    pub fn request(&mut self, action: StationAction) {
+        if self.is_docked() {
+            self.actions.push(action);
+        }
+    }
+}
+
+pub struct Station {
+    pub data: StationData,
+    pub entity: Id,
+    pub dirty: bool,
+}
+
+pub struct StationManager {
+    pub player: Option<Id>,
+    pub camera: Option<Id>,
+    pub tags: Arc<RwLock<TagIndex>>,
+    pub state: Arc<RwLock<State>>,
+    pub tile_map: Arc<RwLock<TileMap>>,
+    pub notifications: Arc<RwLock<Notifications>>,
+    pub economy: Arc<RwLock<Economy>>,
+    pub events: Arc<RwLock<EventBus>>,
+    pub stations: HashMap<(u32, u32), Station>,
+    pub instance: Arc<RwLock<Instance>>,
+    pub text: Text,
+    checked: HashSet<(u32, u32)>,
+    last_frame: Instant,
+}
+
+impl StationManager {
+    pub fn new(
+        context: &Context,
+        state: Arc<RwLock<State>>,
+        tile_map: Arc<RwLock<TileMap>>,
+        notifications: Arc<RwLock<Notifications>>,
+        economy: Arc<RwLock<Economy>>,
+        events: Arc<RwLock<EventBus>>,
+        tags: Arc<RwLock<TagIndex>>,
+    ) -> anyhow::Result<Self> {
+        let shape = Arc::new(Shape::rect(context, Vector2::new(1.0, 1.0))?);
+        let texture = Arc::new(Self::station_texture(context));
+
+        Ok(Self {
+            player: None,
+            tags,
+            camera: None,
+            state,
+            tile_map,
+            notifications,
+            economy,
+            events,
+            stations: HashMap::new(),
+            instance: Instance::new(context, shape, texture, Vector4::new(1.0, 1.0, 1.0, 1.0), 1)?,
+            text: Text::new(Font::new(context, Vector4::new(1.0, 1.0, 1.0, 1.0), 0)?),
+            checked: HashSet::new(),
+            last_frame: Instant::now(),
+        })
+    }
+
+    fn station_texture(context: &Context) -> hex::assets::Texture {
+        let size = 24u32;
+        let mut img = Vec::with_capacity((size * size * 4) as usize);
+
+        for y in 0..size {
+            for x in 0..size {
+                let d = Vector2::new(x as f32 + 0.5, y as f32 + 0.5)
+                    - Vector2::new(size as f32 / 2.0, size as f32 / 2.0);
+                let r = d.magnitude();
+                let pixel = if (9.0..11.5).contains(&r) {
+                    [200, 210, 220, 255]
+                } else if r < 4.0 {
+                    [90, 180, 230, 255]
+                } else if r < 11.5 && (d.x.abs() < 1.5 || d.y.abs() < 1.5) {
+                    [140, 150, 160, 255]
+                } else {
+                    [0, 0, 0, 0]
+                };
+
+                img.extend_from_slice(&pixel);
+            }
+        }
+
+        util::pixel_texture(context, &img, size, size)
+    }
+
+    fn spawn(&self, em: &mut EntityManager, data: &StationData) -> Id {
+        let e = em.add(true);
+
+        em.add_component(e, self.instance.clone());
+        em.add_component(
+            e,
+            Trans::new(
+                data.position(),
+                0.0,
+                Vector2::new(STATION_SIZE, STATION_SIZE),
+            ),
+        );
+        em.add_component(e, MapMarker::new(Vector4::new(0.9, 0.9, 1.0, 1.0)));
+
+        self.tags.write().tag(em, e, STATION_TAG);
+
+        e
+    }
+
+    fn clear_footprint(&self, em: &mut EntityManager, position: Vector2<f32>) {
+        let mut tile_map = self.tile_map.write();
+        let half = (STATION_SIZE / 2.0).ceil() as i32;
+
+        for i in -half..=half {
+            for j in -half..=half {
+                let Some(tile) = TileMap::tile_pos(position + Vector2::new(i as f32, j as f32))
+                else {
+                    continue;
+                };
+
+                if tile_map.get(tile).is_some() {
+                    let old = tile_map.set(tile, None);
+
+                    self.events.write().publish(GameEvent::TileChanged {
+                        tile,
+                        old,
+                        new: None,
+                    });
+
+                    if let Some(e) = tile_map.entities.remove(&tile) {
+                        em.rm(e);
+                    }
+                }
+            }
+        }
+    }
+
+    fn load_stations(&mut self, em: &mut EntityManager) -> anyhow::Result<()> {
+        let loaded: HashSet<_> = self.tile_map.read().chunks.keys().copied().collect();
+
+        for chunk in self
+            .stations
+            .keys()
+            .copied()
+            .filter(|c| !loaded.contains(c))
+            .collect::<Vec<_>>()
+        {
+            if let Some(station) = self.stations.remove(&chunk) {
+                if station.dirty {
+                    station.data.save(chunk)?;
+                }
+
+                self.tags.write().rm(em, station.entity);
+            }
+        }
+
+        self.checked.retain(|c| loaded.contains(c));
+
+        for chunk in loaded {
+            if !self.checked.insert(chunk) {
+                continue;
+            }
+
+            let (mut data, new) = match StationData::read_saved(chunk)? {
+                Some(data) => (data, false),
+                None => match StationData::generate(self.state.read().seed, chunk) {
+                    Some(data) => (data, true),
+                    None => continue,
+                },
+            };
+
+            if new {
+                self.clear_footprint(em, data.position());
+            }
+
+            let entity = self.spawn(em, &data);
+            let mut economy = self.economy.write();
+
+            data.market.simulate_to(economy.tick);
+            economy.observe(chunk, &data);
+
+            self.stations.insert(
+                chunk,
+                Station {
+                    data,
+                    entity,
+                    dirty: true,
+                },
+            );
+        }
+
+        Ok(())
+    }
+
+    pub fn save_all(&mut self) -> anyhow::Result<()> {
+        for (chunk, station) in &mut self.stations {
+            if station.dirty {
+                station.data.save(*chunk)?;
+                station.dirty = false;
+            }
+        }
+
+        self.economy.read().save()
+    }
+
+    fn simulate(&mut self, delta: f32) {
+        let mut economy = self.economy.write();
+
+        if economy.advance(delta) == 0 {
+            return;
+        }
+
+        for (chunk, station) in &mut self.stations {
+            station.data.market.simulate_to(economy.tick);
+            station.dirty = true;
+
+            economy.observe(*chunk, &station.data);
+        }
+    }
+
+    fn service(em: &EntityManager, player: Id, wallet: &mut Wallet) -> Option<u64> {
+        let systems = em.get_component::<ShipSystems>(player)?;
+        let health = em.get_component::<Health>(player)?;
+        let mut systems = systems.write();
+        let mut health = health.write();
+        let fuel = systems.config.fuel_capacity - systems.fuel;
+        let oxygen = systems.config.oxygen_capacity - systems.oxygen;
+        let hull = health.max - health.current;
+        let cost = ((fuel + oxygen) * REFUEL_PRICE + hull * REPAIR_PRICE).ceil() as u64;
+
+        if !wallet.spend(cost) {
+            return None;
+        }
+
+        systems.refill();
+        health.current = health.max;
+
+        Some(cost)
+    }
+
+    fn act(
+        &mut self,
+        em: &EntityManager,
+        player: Id,
+        chunk: (u32, u32),
+        action: StationAction,
+    ) -> Option<String> {
+        let docking = em.get_component::<Docking>(player)?;
+        let wallet = em.get_component::<Wallet>(player)?;
+        let inventory = em.get_component::<Inventory>(player)?;
+        let mut docking = docking.write();
+        let mut wallet = wallet.write();
+        let mut inventory = inventory.write();
+        let station = self.stations.get_mut(&chunk)?;
+        let goods: Vec<_> = station.data.market.entries.keys().cloned().collect();
+        let good = goods.get(docking.selected % goods.len().max(1)).cloned();
+
+        match action {
+            StationAction::Undock => {
+                docking.station = None;
+                docking.can_dock = false;
+
+                if let Some(crafter) = em.get_component::<Crafter>(player) {
+                    crafter.write().station = None;
+                }
+
+                Some("UNDOCKED".to_string())
+            }
+            StationAction::Service => Some(match Self::service(em, player, &mut wallet) {
+                Some(cost) => format!("SERVICED -{cost}CR"),
+                None => "INSUFFICIENT CREDITS".to_string(),
+            }),
+            StationAction::Store => {
+                let stack = inventory.selected()?.clone();
+                let left = station.data.storage.add(&stack.id, stack.count);
+
+                inventory.remove(&stack.id, stack.count - left);
+                station.dirty = true;
+
+                None
+            }
+            StationAction::Retrieve => {
+                for slot in station.data.storage.slots.iter_mut() {
+                    if let Some(stack) = slot.as_mut() {
+                        let left = inventory.add(&stack.id, stack.count);
+
+                        stack.count = left;
+
+                        if left == 0 {
+                            *slot = None;
+                        }
+                    }
+                }
+
+                station.dirty = true;
+
+                None
+            }
+            StationAction::Buy => {
+                let good = good?;
+
+                if station.data.market.buy(&good, &mut wallet, &mut inventory) {
+                    station.dirty = true;
+
+                    None
+                } else {
+                    Some("CANNOT BUY".to_string())
+                }
+            }
+            StationAction::Sell => {
+                let good = good?;
+
+                if station.data.market.sell(&good, &mut wallet, &mut inventory) {
+                    station.dirty = true;
+
+                    None
+                } else {
+                    Some("NOTHING TO SELL".to_string())
+                }
+            }
+            StationAction::Next => {
+                docking.selected = docking.selected.wrapping_add(1);
+
+                None
+            }
+            StationAction::Previous => {
+                docking.selected = docking.selected.wrapping_sub(1);
+
+                None
+            }
+        }
+    }
+
+    fn interface_text(&self, em: &EntityManager, player: Id) -> String {
+        let (Some(docking), Some(wallet), Some(inventory)) = (
+            em.get_component::<Docking>(player),
+            em.get_component::<Wallet>(player),
+            em.get_component::<Inventory>(player),
+        ) else {
+            return String::new();
+        };
+        let docking = docking.read();
+        let Some(station) = docking.station.and_then(|c| self.stations.get(&c)) else {
+            return String::new();
+        };
+        let goods = &station.data.market.entries;
+        let selected = docking.selected % goods.len().max(1);
+        let inventory = inventory.read();
+        let mut lines = vec![
+            format!("{} ({})", station.data.name, station.data.kind),
+            format!("CREDITS {}", wallet.read().credits),
+            String::new(),
+        ];
+
+        for (i, (id, entry)) in goods.iter().enumerate() {
+            lines.push(format!(
+                "{}{} B{} S{} STOCK {} HAVE {}",
+                if i == selected { ">" } else { " " },
+                id,
+                entry.price(),
+                entry.sell_price(),
+                entry.stock,
+                inventory.count(id),
+            ));
+        }
+
+        let stored: u32 = station
+            .data
+            .storage
+            .slots
+            .iter()
+            .flatten()
+            .map(|s| s.count)
+            .sum();
+
+        lines.push(String::new());
+        lines.push(format!("STORAGE {stored}"));
+        let economy = self.economy.read();
+
+        if let Some(route) = docking
+            .station
+            .and_then(|c| economy.routes(Some(c)).into_iter().next())
+        {
+            lines.push(format!(
+                "ROUTE {} TO {} +{}",
+                route.id,
+                economy.name(route.to).unwrap_or("?"),
+                route.profit()
+            ));
+        }
+
+        if let Some(history) = goods
+            .keys()
+            .nth(selected)
+            .and_then(|id| station.data.market.history(id))
+        {
+            lines.push(format!(
+                "HISTORY {}",
+                history
+                    .iter()
+                    .rev()
+                    .take(8)
+                    .map(|p| p.to_string())
+                    .collect::<Vec<_>>()
+                    .join(" ")
+            ));
+        }
+
+        lines.push("K BUY  L SELL  ,/. SELECT".to_string());
+        lines.push("F SERVICE  G STORE  T TAKE  E UNDOCK".to_string());
+
+        lines.join("\n")
+    }
+}
+
+impl System for StationManager {
+    fn init(&mut self, _: Arc<RwLock<Context>>, _: Arc<RwLock<World>>) -> anyhow::Result<()> {
+        self.player = self.tags.read().first("player");
+        self.camera = self.tags.read().first("camera");
+
+        Ok(())
+    }
+
+    fn update(
+        &mut self,
+        control: Arc<RwLock<Control>>,
+        context: Arc<RwLock<Context>>,
+        world: Arc<RwLock<World>>,
+    ) -> anyhow::Result<()> {
+        let event = control.read().event.clone();
+
+        match event {
+            Event::WindowEvent {
+                event: WindowEvent::CloseRequested,
+                window_id,
+            } if window_id == context.read().window.id() => {
+                self.save_all()?;
+            }
+            Event::WindowEvent {
+                event: WindowEvent::RedrawRequested,
+                window_id,
+            } if window_id == context.read().window.id() => {
+                let (Some(player), Some(camera)) = (self.player, self.camera) else {
+                    return Ok(());
+                };
+                let em = world.read().em.clone();
+                let mut em = em.write();
+
+                let now = Instant::now();
+                let delta = now.duration_since(self.last_frame).as_secs_f32();
+
+                self.last_frame = now;
+                self.load_stations(&mut em)?;
+                self.simulate(delta);
+
+                let Some(docking) = em.get_component::<Docking>(player) else {
+                    return Ok(());
+                };
+                let (Some(t), Some(p)) = (
+                    em.get_component::<Trans>(player),
+                    em.get_component::<Player>(player),
+                ) else {
+                    return Ok(());
+                };
+                let position = t.read().position();
+                let speed = p.read().velocity.magnitude();
+                let nearest = self
+                    .stations
+                    .iter()
+                    .map(|(c, s)| (*c, (s.data.position() - position).magnitude()))
+                    .filter(|(_, d)| *d <= DOCK_RADIUS)
+                    .min_by(|(_, a), (_, b)| a.total_cmp(b))
+                    .map(|(c, _)| c);
+                let (docked, actions) = {
+                    let mut docking = docking.write();
+
+                    match (docking.station, nearest) {
+                        (None, None) => docking.can_dock = true,
+                        (None, Some(chunk)) if docking.can_dock && speed <= DOCK_SPEED => {
+                            docking.station = Some(chunk);
+                            docking.selected = 0;
+
+                            if let Some(station) = self.stations.get(&chunk) {
+                                self.notifications
+                                    .write()
+                                    .push(format!("DOCKED AT {}", station.data.name));
+
+                                if let Some(crafter) = em.get_component::<Crafter>(player) {
+                                    crafter.write().station = Some(station.data.kind.clone());
+                                }
+                            }
+                        }
+                        (Some(chunk), _) if !self.stations.contains_key(&chunk) => {
+                            docking.station = None;
+                        }
+                        _ => {}
+                    }
+
+                    (docking.station, std::mem::take(&mut docking.actions))
+                };
+
+                if let Some(chunk) = docked {
+                    if let Some(station) = self.stations.get(&chunk) {
+                        let offset = station.data.position() - position;
+
+                        t.write()
+                            .set_position(position + offset * (DOCK_EASE * delta).min(1.0));
+                        p.write().velocity = Vector2::default();
+                    }
+
+                    for action in actions {
+                        if let Some(message) = self.act(&em, player, chunk, action) {
+                            self.notifications.write().push(message);
+                        }
+                    }
+
+                    if let Some(station) = self.stations.get(&chunk) {
+                        self.economy.write().observe(chunk, &station.data);
+                    }
+                }
+
+                let text = self.interface_text(&em, player);
+
+                self.text.set(&mut em, &text);
+
+                if let Some(screen) = Screen::new(&context.read(), &em, camera) {
+                    let size = Font::measure(&text, 1.0);
+
+                    screen.place_text(
+                        &self.text,
+                        &em,
+                        Vector2::new((screen.size.x - size.x) / 2.0, HUD_MARGIN + STATION_PANEL_Y),
+                    );
+                }
+            }
+            _ => {}
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(stock: u32, target: u32, production: f32, consumption: f32) -> MarketEntry {
+        MarketEntry {
+            stock,
+            target,
+            base_price: 10.0,
+            production,
+            consumption,
+            accumulator: 0.0,
+            history: VecDeque::new(),
+        }
+    }
+
+    fn market() -> Market {
+        let mut market = Market::default();
+
+        market
+            .entries
+            .insert("metal".to_string(), entry(10, 20, 0.35, 0.1));
+        market
+            .entries
+            .insert("fuel_cell".to_string(), entry(40, 20, 0.0, 0.27));
+
+        market
+    }
+
+    fn snapshot(market: &Market) -> String {
+        serde_json::to_string(market).unwrap()
+    }
+
+    #[test]
+    fn simulate_to_matches_single_steps() {
+        let ticks = PRICE_HISTORY_INTERVAL * 5 + 7;
+        let mut batched = market();
+        let mut stepped = market();
+
+        batched.simulate_to(ticks);
+
+        for tick in 1..=ticks {
+            stepped.simulate_to(tick);
+        }
+
+        assert_eq!(batched.tick, ticks);
+        assert_eq!(snapshot(&batched), snapshot(&stepped));
+        assert_eq!(batched.history("metal").map(VecDeque::len), Some(5));
+    }
+
+    #[test]
+    fn simulate_to_caps_catch_up() {
+        let target = MAX_CATCH_UP_TICKS * 3;
+        let mut capped = market();
+        let mut expected = market();
+
+        capped.simulate_to(target);
+        expected.tick = target - MAX_CATCH_UP_TICKS;
+        expected.simulate_to(target);
+
+        assert_eq!(capped.tick, target);
+        assert_eq!(snapshot(&capped), snapshot(&expected));
+    }
+
+    #[test]
+    fn simulate_to_never_rewinds() {
+        let mut market = market();
+
+        market.simulate_to(50);
+
+        let before = snapshot(&market);
+
+        market.simulate_to(10);
+
+        assert_eq!(market.tick, 50);
+        assert_eq!(snapshot(&market), before);
+    }
+}
@@ -1,5 +1,8 @@
 use hex::{parking_lot::RwLock, world::entity_manager::EntityManager, Id};
-use std::sync::Arc;
+use std::{
+    collections::{BTreeSet, HashMap},
+    sync::Arc,
+};
 
 #[derive(Clone)]
 pub struct Tag(pub String);
@@ -11,11 +14,221 @@ impl Tag {
     {
         Arc::new(RwLock::new(Self(t.into())))
     }
+}
+
+/// Each entity carries at most one tag. Entities in the index must be removed
+/// through `TagIndex::rm`; a raw `em.rm` leaves a stale id that `query`
+/// asserts on in debug builds.
+#[derive(Default)]
+pub struct TagIndex {
+    pub tags: HashMap<String, BTreeSet<Id>>,
+    pub entities: HashMap<Id, String>,
+}
+
+impl TagIndex {
+    pub fn new() -> Arc<RwLock<Self>> {
+        Arc::new(RwLock::new(Self::default()))
+    }
+
+    pub fn tag<S>(&mut self, em: &mut EntityManager, e: Id, tag: S)
+    where
+        S: Into<String>,
+    {
+        let tag = tag.into();
+
+        debug_assert!(
+            !matches!(self.tag_of(e), Some(old) if old != tag),
+            "entity {e:?} is already tagged {:?}, untag it before tagging it {tag:?}",
+            self.tag_of(e),
+        );
+
+        self.untag(e);
+        self.tags.entry(tag.clone()).or_default().insert(e);
+        self.entities.insert(e, tag.clone());
+
+        em.add_component(e, Tag::new(tag));
+    }
+
+    pub fn untag(&mut self, e: Id) -> Option<String> {
+        let tag = self.entities.remove(&e)?;
+
+        if let Some(ids) = self.tags.get_mut(&tag) {
+            ids.remove(&e);
+
+            if ids.is_empty() {
+                self.tags.remove(&tag);
+            }
+        }
+
+        Some(tag)
+    }
+
+    pub fn rm(&mut self, em: &mut EntityManager, e: Id) {
+        self.untag(e);
 
-    pub fn find(&self, em: &EntityManager) -> Option<Id> {
-        em.entities().find_map(|e| {
-            em.get_component::<Tag>(e)
-                .and_then(|t| (self.0 == t.read().0).then_some(e))
+        em.rm(e);
+    }
+
+    pub fn tag_of(&self, e: Id) -> Option<&str> {
+        self.entities.get(&e).map(|t| t.as_str())
+    }
+
+    pub fn all(&self, tag: &str) -> impl Iterator<Item = Id> + '_ {
+        self.tags.get(tag).into_iter().flatten().copied()
+    }
+
+    pub fn first(&self, tag: &str) -> Option<Id> {
+        self.all(tag).next()
+    }
+
+    pub fn count(&self, tag: &str) -> usize {
+        self.tags.get(tag).map(|ids| ids.len()).unwrap_or_default()
+    }
+
+    pub fn query<'a, C>(
+        &'a self,
+        em: &'a EntityManager,
+        tag: &str,
+    ) -> impl Iterator<Item = (Id, Arc<RwLock<C>>)> + 'a
+    where
+        C: Send + Sync + 'static,
+    {
+        self.all(tag).filter_map(move |e| {
+            debug_assert!(
+                em.get_component::<Tag>(e).is_some(),
+                "entity {e:?} was removed without TagIndex::rm",
+            );
+
+            em.get_component::<C>(e).map(|c| (e, c))
         })
     }
+
+    pub fn query_one<C>(&self, em: &EntityManager, tag: &str) -> Option<(Id, Arc<RwLock<C>>)>
+    where
+        C: Send + Sync + 'static,
+    {
+        self.query(em, tag).next()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, PartialEq)]
+    struct Marker(u32);
+
+    fn marked(em: &mut EntityManager, tags: &mut TagIndex, tag: &str, n: u32) -> Id {
+        let e = em.add(true);
+
+        tags.tag(em, e, tag);
+        em.add_component(e, Arc::new(RwLock::new(Marker(n))));
+
+        e
+    }
+
+    #[test]
+    fn tag_and_untag() {
+        let em = EntityManager::new();
+        let mut em = em.write();
+        let mut tags = TagIndex::default();
+        let e = marked(&mut em, &mut tags, "drop", 0);
+
+        assert_eq!(tags.tag_of(e), Some("drop"));
+        assert_eq!(tags.first("drop"), Some(e));
+        assert_eq!(tags.untag(e).as_deref(), Some("drop"));
+        assert_eq!(tags.tag_of(e), None);
+        assert_eq!(tags.count("drop"), 0);
+        assert!(!tags.tags.contains_key("drop"));
+        assert_eq!(tags.untag(e), None);
+    }
+
+    #[test]
+    fn rm_removes_entity_and_index() {
+        let em = EntityManager::new();
+        let mut em = em.write();
+        let mut tags = TagIndex::default();
+        let e = marked(&mut em, &mut tags, "drop", 0);
+
+        tags.rm(&mut em, e);
+
+        assert_eq!(tags.tag_of(e), None);
+        assert!(em.get_component::<Marker>(e).is_none());
+        assert_eq!(tags.query::<Marker>(&em, "drop").count(), 0);
+    }
+
+    #[test]
+    fn query_returns_every_tagged_entity() {
+        let em = EntityManager::new();
+        let mut em = em.write();
+        let mut tags = TagIndex::default();
+        let a = marked(&mut em, &mut tags, "drop", 1);
+        let b = marked(&mut em, &mut tags, "drop", 2);
+        let other = marked(&mut em, &mut tags, "station", 3);
+        let bare = em.add(true);
+
+        tags.tag(&mut em, bare, "drop");
+
+        let mut found: Vec<_> = tags
+            .query::<Marker>(&em, "drop")
+            .map(|(e, m)| (e, m.read().0))
+            .collect();
+
+        found.sort_by_key(|(_, n)| *n);
+
+        assert_eq!(found, vec![(a, 1), (b, 2)]);
+        assert_eq!(tags.count("drop"), 3);
+        assert_eq!(
+            tags.query_one::<Marker>(&em, "station").map(|(e, _)| e),
+            Some(other)
+        );
+    }
+
+    #[test]
+    fn retag_after_untag_moves_entity() {
+        let em = EntityManager::new();
+        let mut em = em.write();
+        let mut tags = TagIndex::default();
+        let e = marked(&mut em, &mut tags, "drop", 0);
+
+        tags.tag(&mut em, e, "drop");
+
+        assert_eq!(tags.count("drop"), 1);
+
+        tags.untag(e);
+        tags.tag(&mut em, e, "station");
+
+        assert_eq!(tags.tag_of(e), Some("station"));
+        assert_eq!(tags.count("drop"), 0);
+        assert_eq!(tags.first("station"), Some(e));
+        assert_eq!(
+            em.get_component::<Tag>(e).map(|t| t.read().0.clone()),
+            Some("station".to_string())
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    #[cfg(debug_assertions)]
+    fn retag_without_untag_panics() {
+        let em = EntityManager::new();
+        let mut em = em.write();
+        let mut tags = TagIndex::default();
+        let e = marked(&mut em, &mut tags, "drop", 0);
+
+        tags.tag(&mut em, e, "station");
+    }
+
+    #[test]
+    #[should_panic]
+    #[cfg(debug_assertions)]
+    fn raw_rm_is_caught_by_query() {
+        let em = EntityManager::new();
+        let mut em = em.write();
+        let mut tags = TagIndex::default();
+        let e = marked(&mut em, &mut tags, "drop", 0);
+
+        em.rm(e);
+        tags.query::<Marker>(&em, "drop").count();
+    }
 }
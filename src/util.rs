@@ -1,4 +1,11 @@
-use hex::{assets::*, nalgebra::*, vulkano::image::sampler::Sampler, *};
+use hex::{
+    assets::*,
+    components::{Camera, Trans},
+    nalgebra::*,
+    vulkano::image::sampler::{Filter, Sampler, SamplerCreateInfo},
+    winit::dpi::{PhysicalPosition, PhysicalSize},
+    *,
+};
 use image::{ImageFormat, ImageReader};
 
 pub fn load_texture(context: &Context, path: &str) -> anyhow::Result<Texture> {
@@ -14,19 +21,64 @@ pub fn load_texture(context: &Context, path: &str) -> anyhow::Result<Texture> {
     Ok(Texture::new(context, sampler, &img, dims.0, dims.1).unwrap())
 }
 
-pub fn mouse_pos_world(
-    dims: Vector2<f32>,
-    camera_scale: Vector2<f32>,
-    window_dims: (u32, u32),
-    mouse_pos: (f64, f64),
+pub fn pixel_texture(context: &Context, img: &[u8], width: u32, height: u32) -> Texture {
+    let sampler = Sampler::new(
+        context.device.clone(),
+        SamplerCreateInfo {
+            mag_filter: Filter::Nearest,
+            min_filter: Filter::Nearest,
+            ..Default::default()
+        },
+    )
+    .unwrap();
+
+    Texture::new(context, sampler, img, width, height).unwrap()
+}
+
+pub fn view_proj(camera: &Camera, transform: &Trans) -> Matrix3<f32> {
+    let dims = camera.dimensions();
+    let proj = Matrix3::new_nonuniform_scaling(&Vector2::new(2.0 / dims.x, 2.0 / dims.y));
+    let view = (Matrix3::new_translation(&transform.position())
+        * Matrix3::new_rotation(transform.rotation())
+        * Matrix3::new_nonuniform_scaling(&transform.scale()))
+    .try_inverse()
+    .unwrap_or_else(Matrix3::identity);
+
+    proj * view
+}
+
+pub fn screen_to_world(
+    camera: &Camera,
+    transform: &Trans,
+    window_dims: PhysicalSize<u32>,
+    screen_pos: PhysicalPosition<f64>,
 ) -> Option<Vector2<f32>> {
-    let (x, y) = mouse_pos;
-    let (width, height) = window_dims;
+    if window_dims.width == 0 || window_dims.height == 0 {
+        return None;
+    }
+
+    let ndc = Vector2::new(
+        (2.0 * screen_pos.x / window_dims.width as f64 - 1.0) as f32,
+        (1.0 - 2.0 * screen_pos.y / window_dims.height as f64) as f32,
+    );
+
+    view_proj(camera, transform)
+        .try_inverse()
+        .map(|inv| (inv * ndc.push(1.0)).xy())
+}
 
-    Some(Vector2::new(
-        camera_scale.x * ((x / width as f64) as f32 * dims.x - dims.x / 2.0),
-        -camera_scale.y * ((y / height as f64) as f32 * dims.y - dims.y / 2.0),
-    ))
+pub fn world_to_screen(
+    camera: &Camera,
+    transform: &Trans,
+    window_dims: PhysicalSize<u32>,
+    world_pos: Vector2<f32>,
+) -> PhysicalPosition<f64> {
+    let ndc = (view_proj(camera, transform) * world_pos.push(1.0)).xy();
+
+    PhysicalPosition::new(
+        (ndc.x as f64 + 1.0) / 2.0 * window_dims.width as f64,
+        (1.0 - ndc.y as f64) / 2.0 * window_dims.height as f64,
+    )
 }
 
 pub fn lerp(f1: f32, f2: f32, t: f32) -> f32 {
@@ -66,3 +118,18 @@ pub fn gcd(u: i32, v: i32) -> i32 {
 
     (u << gcd_exponent_on_two) as i32
 }
+
+pub fn mix(mut z: u64) -> u64 {
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+
+    z ^ (z >> 31)
+}
+
+pub fn chunk_hash((x, y): (u32, u32), salt: u64) -> u64 {
+    mix((((x as u64) << 32) | y as u64).wrapping_add(salt))
+}
+
+pub fn seed_hash(seed: u32, salt: u64) -> u64 {
+    mix((seed as u64).wrapping_add(salt))
+}
@@ -0,0 +1,379 @@
+use crate::{
+    chunk_manager::SAVE_DIR,
+    font::{Font, Text},
+    hud_manager::{HudManager, Screen},
+    tag::TagIndex,
+    util,
+};
+use hex::{
+    anyhow,
+    assets::{Shape, Texture},
+    components::Trans,
+    nalgebra::{Vector2, Vector4},
+    parking_lot::RwLock,
+    winit::event::{Event, WindowEvent},
+    world::{system_manager::System, World},
+    Context, Control, Id,
+};
+use hex_instance::components::Instance;
+use serde_derive::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    f32::consts::{FRAC_PI_2, FRAC_PI_4},
+    fs,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+pub const WAYPOINTS_FILE: &str = "waypoints.json";
+pub const SPAWN_POSITION: [f32; 2] = [0.0, 100.0];
+pub const WAYPOINT_COLOR: [f32; 4] = [1.0, 0.9, 0.2, 1.0];
+pub const SPAWN_COLOR: [f32; 4] = [0.3, 0.6, 1.0, 1.0];
+pub const DEATH_COLOR: [f32; 4] = [1.0, 0.2, 0.2, 1.0];
+pub const MARKER_COLOR: [f32; 4] = [1.0, 0.3, 1.0, 1.0];
+pub const WAYPOINT_EDGE_MARGIN: f32 = 12.0;
+pub const WAYPOINT_ICON_SIZE: f32 = 5.0;
+pub const WAYPOINT_REMOVE_RADIUS: f32 = 3.0;
+pub const ARROW_SIZE: u32 = 8;
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Waypoint {
+    pub name: String,
+    pub position: [f32; 2],
+    pub color: [f32; 4],
+}
+
+impl Waypoint {
+    pub fn new<S>(name: S, position: Vector2<f32>, color: [f32; 4]) -> Self
+    where
+        S: Into<String>,
+    {
+        Self {
+            name: name.into(),
+            position: position.into(),
+            color,
+        }
+    }
+
+    pub fn position(&self) -> Vector2<f32> {
+        self.position.into()
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Waypoints {
+    pub waypoints: Vec<Waypoint>,
+    pub spawn: Waypoint,
+    pub last_death: Option<Waypoint>,
+    #[serde(skip)]
+    pub markers: BTreeMap<String, Waypoint>,
+    #[serde(skip)]
+    pub dirty: bool,
+}
+
+impl Default for Waypoints {
+    fn default() -> Self {
+        Self {
+            waypoints: Vec::new(),
+            spawn: Waypoint::new("SPAWN", SPAWN_POSITION.into(), SPAWN_COLOR),
+            last_death: None,
+            markers: BTreeMap::new(),
+            dirty: false,
+        }
+    }
+}
+
+impl Waypoints {
+    pub fn path() -> PathBuf {
+        PathBuf::from(SAVE_DIR).join(WAYPOINTS_FILE)
+    }
+
+    pub fn load() -> anyhow::Result<Arc<RwLock<Self>>> {
+        let path = Self::path();
+        let waypoints = if Path::exists(&path) {
+            serde_json::from_str(fs::read_to_string(path)?.as_str())?
+        } else {
+            Self::default()
+        };
+
+        Ok(Arc::new(RwLock::new(waypoints)))
+    }
+
+    pub fn save(&mut self) -> anyhow::Result<()> {
+        fs::create_dir_all(SAVE_DIR)?;
+        fs::write(Self::path(), serde_json::to_string_pretty(self)?)?;
+
+        self.dirty = false;
+
+        Ok(())
+    }
+
+    pub fn add<S>(&mut self, name: S, position: Vector2<f32>)
+    where
+        S: Into<String>,
+    {
+        self.waypoints
+            .push(Waypoint::new(name, position, WAYPOINT_COLOR));
+        self.dirty = true;
+    }
+
+    pub fn add_numbered(&mut self, position: Vector2<f32>) {
+        let name = format!("WP{}", self.waypoints.len() + 1);
+
+        self.add(name, position);
+    }
+
+    pub fn remove_near(&mut self, position: Vector2<f32>, radius: f32) -> Option<Waypoint> {
+        let (i, _) = self
+            .waypoints
+            .iter()
+            .enumerate()
+            .map(|(i, w)| (i, (w.position() - position).magnitude()))
+            .filter(|(_, d)| *d <= radius)
+            .min_by(|(_, a), (_, b)| a.total_cmp(b))?;
+
+        self.dirty = true;
+
+        Some(self.waypoints.remove(i))
+    }
+
+    pub fn set_last_death(&mut self, position: Vector2<f32>) {
+        self.last_death = Some(Waypoint::new("DEATH", position, DEATH_COLOR));
+        self.dirty = true;
+    }
+
+    pub fn set_marker<K, S>(&mut self, key: K, name: S, position: Vector2<f32>)
+    where
+        K: Into<String>,
+        S: Into<String>,
+    {
+        self.markers
+            .insert(key.into(), Waypoint::new(name, position, MARKER_COLOR));
+    }
+
+    pub fn remove_marker(&mut self, key: &str) -> Option<Waypoint> {
+        self.markers.remove(key)
+    }
+
+    pub fn all(&self) -> impl Iterator<Item = (String, &Waypoint)> {
+        std::iter::once(("spawn".to_string(), &self.spawn))
+            .chain(self.last_death.iter().map(|w| ("death".to_string(), w)))
+            .chain(
+                self.waypoints
+                    .iter()
+                    .enumerate()
+                    .map(|(i, w)| (format!("waypoint:{i}"), w)),
+            )
+            .chain(self.markers.iter().map(|(k, w)| (format!("marker:{k}"), w)))
+    }
+}
+
+pub struct WaypointManager {
+    pub player: Option<Id>,
+    pub camera: Option<Id>,
+    pub tags: Arc<RwLock<TagIndex>>,
+    pub waypoints: Arc<RwLock<Waypoints>>,
+    pub font: Arc<Font>,
+    pub shape: Arc<Shape>,
+    pub arrow: Arc<Texture>,
+    pub icon: Arc<Texture>,
+    instances: HashMap<([u32; 4], bool), Arc<RwLock<Instance>>>,
+    drawn: HashMap<String, (Id, bool, Text)>,
+}
+
+impl WaypointManager {
+    pub fn new(
+        context: &Context,
+        waypoints: Arc<RwLock<Waypoints>>,
+        tags: Arc<RwLock<TagIndex>>,
+    ) -> anyhow::Result<Self> {
+        Ok(Self {
+            player: None,
+            tags,
+            camera: None,
+            waypoints,
+            font: Font::new(context, Vector4::new(1.0, 1.0, 1.0, 1.0), 0)?,
+            shape: Arc::new(Shape::rect(context, Vector2::new(1.0, 1.0))?),
+            arrow: Arc::new(Self::arrow_texture(context)),
+            icon: Arc::new(util::pixel_texture(context, &[255; 4], 1, 1)),
+            instances: HashMap::new(),
+            drawn: HashMap::new(),
+        })
+    }
+
+    fn arrow_texture(context: &Context) -> Texture {
+        let mut img = Vec::with_capacity((ARROW_SIZE * ARROW_SIZE * 4) as usize);
+
+        for y in 0..ARROW_SIZE {
+            for x in 0..ARROW_SIZE {
+                let filled =
+                    (x as f32 + 0.5 - ARROW_SIZE as f32 / 2.0).abs() <= (y as f32 + 1.0) / 2.0;
+
+                img.extend_from_slice(&[255, 255, 255, if filled { 255 } else { 0 }]);
+            }
+        }
+
+        util::pixel_texture(context, &img, ARROW_SIZE, ARROW_SIZE)
+    }
+
+    fn instance(
+        &mut self,
+        context: &Context,
+        color: [f32; 4],
+        arrow: bool,
+    ) -> anyhow::Result<Arc<RwLock<Instance>>> {
+        let key = (color.map(f32::to_bits), arrow);
+
+        if let Some(instance) = self.instances.get(&key) {
+            return Ok(instance.clone());
+        }
+
+        let texture = if arrow {
+            self.arrow.clone()
+        } else {
+            self.icon.clone()
+        };
+        let instance = Instance::new(context, self.shape.clone(), texture, color.into(), 0)?;
+
+        self.instances.insert(key, instance.clone());
+
+        Ok(instance)
+    }
+}
+
+impl System for WaypointManager {
+    fn init(&mut self, _: Arc<RwLock<Context>>, _: Arc<RwLock<World>>) -> anyhow::Result<()> {
+        self.player = self.tags.read().first("player");
+        self.camera = self.tags.read().first("camera");
+
+        Ok(())
+    }
+
+    fn update(
+        &mut self,
+        control: Arc<RwLock<Control>>,
+        context: Arc<RwLock<Context>>,
+        world: Arc<RwLock<World>>,
+    ) -> anyhow::Result<()> {
+        let event = control.read().event.clone();
+
+        if let Event::WindowEvent {
+            event: WindowEvent::RedrawRequested,
+            window_id,
+        } = event
+        {
+            if window_id != context.read().window.id() {
+                return Ok(());
+            }
+
+            let (Some(player), Some(camera)) = (self.player, self.camera) else {
+                return Ok(());
+            };
+            let waypoints: Vec<_> = {
+                let mut waypoints = self.waypoints.write();
+
+                if waypoints.dirty {
+                    waypoints.save()?;
+                }
+
+                waypoints.all().map(|(k, w)| (k, w.clone())).collect()
+            };
+            let context = context.read();
+            let em = world.read().em.clone();
+            let mut em = em.write();
+            let Some(screen) = Screen::new(&context, &em, camera) else {
+                return Ok(());
+            };
+            let player_position = em
+                .get_component::<Trans>(player)
+                .map(|t| t.read().position())
+                .unwrap_or_default();
+            let center = screen.size / 2.0;
+            let world_center = screen.at(center.x, center.y);
+            let half = center - Vector2::new(WAYPOINT_EDGE_MARGIN, WAYPOINT_EDGE_MARGIN);
+            let keys: HashSet<_> = waypoints.iter().map(|(k, _)| k.clone()).collect();
+
+            self.drawn.retain(|k, (e, _, text)| {
+                let keep = keys.contains(k);
+
+                if !keep {
+                    em.rm(*e);
+                    text.clear(&mut em);
+                }
+
+                keep
+            });
+
+            for (key, waypoint) in waypoints {
+                let position = waypoint.position();
+                let hud = screen.to_screen(position);
+                let offset = hud - center;
+                let on_screen = offset.x.abs() <= half.x && offset.y.abs() <= half.y;
+                let (hud, rotation, label) = if on_screen {
+                    (hud, screen.rotation + FRAC_PI_4, waypoint.name.clone())
+                } else {
+                    let scale = (half.x / offset.x.abs()).min(half.y / offset.y.abs());
+                    let direction = position - world_center;
+
+                    (
+                        center + offset * scale,
+                        direction.y.atan2(direction.x) - FRAC_PI_2,
+                        format!(
+                            "{} {:.0}M",
+                            waypoint.name,
+                            (position - player_position).magnitude()
+                        ),
+                    )
+                };
+                let arrow = !on_screen;
+
+                if self.drawn.get(&key).is_some_and(|(_, a, _)| *a != arrow) {
+                    if let Some((e, _, _)) = self.drawn.get(&key) {
+                        em.rm(*e);
+                    }
+
+                    let instance = self.instance(&context, waypoint.color, arrow)?;
+
+                    if let Some((e, a, _)) = self.drawn.get_mut(&key) {
+                        *e = HudManager::rect(&mut em, instance);
+                        *a = arrow;
+                    }
+                }
+
+                if !self.drawn.contains_key(&key) {
+                    let instance = self.instance(&context, waypoint.color, arrow)?;
+                    let e = HudManager::rect(&mut em, instance);
+
+                    self.drawn
+                        .insert(key.clone(), (e, arrow, Text::new(self.font.clone())));
+                }
+
+                let (e, _, text) = self.drawn.get_mut(&key).unwrap();
+                let size = Vector2::new(WAYPOINT_ICON_SIZE, WAYPOINT_ICON_SIZE);
+
+                screen.place(&em, *e, hud - size / 2.0, size);
+
+                if let Some(t) = em.get_component::<Trans>(*e) {
+                    t.write().set_rotation(rotation);
+                }
+
+                text.set(&mut em, &label);
+
+                let label_size = Font::measure(&label, 1.0);
+
+                screen.place_text(
+                    text,
+                    &em,
+                    Vector2::new(
+                        (hud.x - label_size.x / 2.0)
+                            .clamp(0.0, (screen.size.x - label_size.x).max(0.0)),
+                        (hud.y + WAYPOINT_ICON_SIZE)
+                            .clamp(0.0, (screen.size.y - label_size.y).max(0.0)),
+                    ),
+                );
+            }
+        }
+
+        Ok(())
+    }
+}